@@ -2,4 +2,7 @@ extern crate lalrpop;
 
 fn main() {
     lalrpop::process_root().unwrap();
+    lexer_generator::codegen::process_spec_file("src/lexer.tokens").unwrap();
+    // Default target triple of the generated code
+    println!("cargo:rustc-env=HULK_HOST_TRIPLE={}", std::env::var("TARGET").unwrap());
 }
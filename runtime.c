@@ -1,14 +1,494 @@
+#include <stdint.h>
+#include <stdio.h>
 #include <stdlib.h>
 #include <string.h>
+#include <ctype.h>
 #include <math.h>
 
+// ---------------------------------------------------------------------------
+// Garbage collector
+//
+// Precise mark-and-sweep collector for HULK objects and strings. Every block
+// returned by `hulk_gc_alloc` is preceded by a hidden `GcHeader`; the layout of
+// `%T_type` seen by the generated code starts right after it.
+//
+// Roots are the addresses of stack slots holding `ptr` values. The generated
+// code registers them with `hulk_gc_push_root` and unregisters them in LIFO
+// order with `hulk_gc_pop_roots`.
+//
+// Environment variables:
+//   HULK_GC_STATS=1   print collection statistics at exit
+//   HULK_GC_STRESS=1  collect before every allocation (for debugging)
+// ---------------------------------------------------------------------------
+
+// Describes which fields of an object hold pointers, as byte offsets.
+// Emitted by codegen as `@T_gc_map` for every type.
+typedef struct {
+    int64_t count;
+    int64_t offsets[];
+} GcPointerMap;
+
+typedef struct GcHeader {
+    struct GcHeader* next;
+    const GcPointerMap* map; // NULL for blocks without pointers (strings)
+    size_t size;
+    int marked;
+} GcHeader;
+
+#define GC_PAYLOAD(header) ((void*)((GcHeader*)(header) + 1))
+#define GC_HEADER(payload) ((GcHeader*)(payload) - 1)
+#define GC_INITIAL_THRESHOLD (1024 * 1024)
+
+static GcHeader* gc_objects = NULL;
+
+// Open-addressing set of live payload addresses, used to tell heap pointers
+// apart from string literals and other non-collected memory.
+static void** gc_table = NULL;
+static size_t gc_table_capacity = 0;
+static size_t gc_table_count = 0;
+
+static void*** gc_roots = NULL;
+static size_t gc_roots_count = 0;
+static size_t gc_roots_capacity = 0;
+
+static size_t gc_live_bytes = 0;
+static size_t gc_allocated_since_collection = 0;
+static size_t gc_threshold = GC_INITIAL_THRESHOLD;
+
+static size_t gc_collections = 0;
+static size_t gc_total_allocated = 0;
+static size_t gc_total_freed = 0;
+static int gc_stress = 0;
+
+static size_t gc_hash(void* ptr, size_t capacity) {
+    return (size_t)(((uintptr_t)ptr >> 4) * 11400714819323198485ull) & (capacity - 1);
+}
+
+static void gc_table_insert(void* payload) {
+    size_t index = gc_hash(payload, gc_table_capacity);
+    while (gc_table[index] != NULL) {
+        index = (index + 1) & (gc_table_capacity - 1);
+    }
+    gc_table[index] = payload;
+    gc_table_count++;
+}
+
+static void gc_table_rebuild(size_t capacity) {
+    free(gc_table);
+    gc_table = calloc(capacity, sizeof(void*));
+    if (gc_table == NULL) {
+        fprintf(stderr, "HULK runtime: out of memory\n");
+        exit(1);
+    }
+    gc_table_capacity = capacity;
+    gc_table_count = 0;
+    for (GcHeader* object = gc_objects; object != NULL; object = object->next) {
+        gc_table_insert(GC_PAYLOAD(object));
+    }
+}
+
+static int gc_is_heap_pointer(void* ptr) {
+    if (ptr == NULL || gc_table_capacity == 0) return 0;
+    size_t index = gc_hash(ptr, gc_table_capacity);
+    while (gc_table[index] != NULL) {
+        if (gc_table[index] == ptr) return 1;
+        index = (index + 1) & (gc_table_capacity - 1);
+    }
+    return 0;
+}
+
+static void gc_mark(void* ptr) {
+    if (!gc_is_heap_pointer(ptr)) return;
+    GcHeader* header = GC_HEADER(ptr);
+    if (header->marked) return;
+    header->marked = 1;
+    if (header->map == NULL) return;
+    for (int64_t i = 0; i < header->map->count; i++) {
+        gc_mark(*(void**)((char*)ptr + header->map->offsets[i]));
+    }
+}
+
+static void gc_collect(void) {
+    for (size_t i = 0; i < gc_roots_count; i++) {
+        gc_mark(*gc_roots[i]);
+    }
+
+    GcHeader** link = &gc_objects;
+    while (*link != NULL) {
+        GcHeader* object = *link;
+        if (object->marked) {
+            object->marked = 0;
+            link = &object->next;
+        } else {
+            *link = object->next;
+            gc_live_bytes -= object->size;
+            gc_total_freed++;
+            free(object);
+        }
+    }
+
+    gc_collections++;
+    gc_allocated_since_collection = 0;
+    gc_threshold = gc_live_bytes * 2 > GC_INITIAL_THRESHOLD ? gc_live_bytes * 2 : GC_INITIAL_THRESHOLD;
+    gc_table_rebuild(gc_table_capacity);
+}
+
+static void gc_print_stats(void) {
+    fprintf(stderr,
+            "[hulk gc] collections: %zu, allocations: %zu, freed: %zu, live bytes: %zu\n",
+            gc_collections, gc_total_allocated, gc_total_freed, gc_live_bytes);
+}
+
+__attribute__((constructor)) static void gc_init(void) {
+    const char* stats = getenv("HULK_GC_STATS");
+    const char* stress = getenv("HULK_GC_STRESS");
+    gc_stress = stress != NULL && strcmp(stress, "1") == 0;
+    if (stats != NULL && strcmp(stats, "1") == 0) {
+        atexit(gc_print_stats);
+    }
+    gc_table_rebuild(1024);
+}
+
+// Allocates a zeroed block of `size` bytes managed by the collector.
+// `map` lists the offsets of the pointer fields in the block, or is NULL.
+void* hulk_gc_alloc(int64_t size, const GcPointerMap* map) {
+    if (gc_stress || gc_allocated_since_collection >= gc_threshold) {
+        gc_collect();
+    }
+    GcHeader* header = calloc(1, sizeof(GcHeader) + (size_t)size);
+    if (header == NULL) {
+        fprintf(stderr, "HULK runtime: out of memory\n");
+        exit(1);
+    }
+    header->map = map;
+    header->size = (size_t)size;
+    header->next = gc_objects;
+    gc_objects = header;
+
+    gc_live_bytes += (size_t)size;
+    gc_allocated_since_collection += (size_t)size;
+    gc_total_allocated++;
+
+    if ((gc_table_count + 1) * 2 > gc_table_capacity) {
+        gc_table_rebuild(gc_table_capacity * 2);
+    } else {
+        gc_table_insert(GC_PAYLOAD(header));
+    }
+    return GC_PAYLOAD(header);
+}
+
+// Registers the stack slot `slot` as a root.
+void hulk_gc_push_root(void** slot) {
+    if (gc_roots_count == gc_roots_capacity) {
+        gc_roots_capacity = gc_roots_capacity == 0 ? 256 : gc_roots_capacity * 2;
+        gc_roots = realloc(gc_roots, gc_roots_capacity * sizeof(void**));
+        if (gc_roots == NULL) {
+            fprintf(stderr, "HULK runtime: out of memory\n");
+            exit(1);
+        }
+    }
+    gc_roots[gc_roots_count++] = slot;
+}
+
+// Returns the number of registered roots, to be restored with `hulk_gc_pop_roots`.
+int64_t hulk_gc_root_count(void) {
+    return (int64_t)gc_roots_count;
+}
+
+// Unregisters every root pushed after `hulk_gc_root_count` returned `count`.
+void hulk_gc_pop_roots(int64_t count) {
+    gc_roots_count = (size_t)count;
+}
+
+// ---------------------------------------------------------------------------
+// Runtime errors
+//
+// Every HULK function pushes its name on a shadow call stack when it is
+// entered (`hulk_trace_push`) and pops it before returning (`hulk_trace_pop`).
+// `hulk_panic` reports a failed runtime check at a location of the HULK source
+// together with the functions on that stack, innermost first.
+// ---------------------------------------------------------------------------
+
+static const char** trace_frames = NULL;
+static size_t trace_depth = 0;
+static size_t trace_capacity = 0;
+
+// Records that the HULK function `name` was entered.
+void hulk_trace_push(const char* name) {
+    if (trace_depth == trace_capacity) {
+        trace_capacity = trace_capacity == 0 ? 256 : trace_capacity * 2;
+        trace_frames = realloc(trace_frames, trace_capacity * sizeof(const char*));
+        if (trace_frames == NULL) {
+            fprintf(stderr, "HULK runtime: out of memory\n");
+            exit(1);
+        }
+    }
+    trace_frames[trace_depth++] = name;
+}
+
+// Records that the innermost HULK function returned.
+void hulk_trace_pop(void) {
+    if (trace_depth > 0) {
+        trace_depth--;
+    }
+}
+
+// Reports the runtime error `msg` raised at `file:line:col` and exits.
+// A line of 0 means the location is unknown (e.g. inside builtin code).
+__attribute__((noreturn)) void hulk_panic(const char* msg, const char* file, int64_t line, int64_t col) {
+    fflush(stdout);
+    if (line > 0) {
+        fprintf(stderr, "Runtime error at %s:%lld:%lld: %s\n", file, (long long)line, (long long)col, msg);
+    } else {
+        fprintf(stderr, "Runtime error in %s: %s\n", file, msg);
+    }
+    if (trace_depth > 0) {
+        fprintf(stderr, "Stack trace (innermost first):\n");
+        for (size_t i = trace_depth; i > 0; i--) {
+            fprintf(stderr, "    at %s\n", trace_frames[i - 1]);
+        }
+    }
+    exit(1);
+}
+
+// ---------------------------------------------------------------------------
+// Strings
+// ---------------------------------------------------------------------------
+
 // String concatenation function
 char* concat(char* s1, char* s2) {
+    int64_t roots = hulk_gc_root_count();
+    hulk_gc_push_root((void**)&s1);
+    hulk_gc_push_root((void**)&s2);
     size_t len1 = strlen(s1);
     size_t len2 = strlen(s2);
-    char* result = malloc(len1 + len2 + 1);
-    if (result == NULL) return NULL;
+    char* result = hulk_gc_alloc(len1 + len2 + 1, NULL);
     strcpy(result, s1);
     strcat(result, s2);
+    hulk_gc_pop_roots(roots);
+    return result;
+}
+
+// Formats a HULK number: integral values without decimals and any other value
+// with the fewest significant digits that read back to the same double.
+char* hulk_num_to_str(double value) {
+    char buffer[32];
+    if (value == 0) {
+        // Also prints -0 as 0
+        strcpy(buffer, "0");
+    } else if (value == floor(value) && fabs(value) < 1e15) {
+        snprintf(buffer, sizeof(buffer), "%.0f", value);
+    } else {
+        for (int precision = 1; precision <= 17; precision++) {
+            snprintf(buffer, sizeof(buffer), "%.*g", precision, value);
+            if (strtod(buffer, NULL) == value) {
+                break;
+            }
+        }
+    }
+    char* result = hulk_gc_alloc(strlen(buffer) + 1, NULL);
+    strcpy(result, buffer);
+    return result;
+}
+
+// ---------------------------------------------------------------------------
+// String methods
+//
+// Positions and lengths count the characters of the UTF-8 encoded strings.
+// Numeric arguments are truncated towards zero and clamped to the string, so
+// no method reads outside of it.
+// ---------------------------------------------------------------------------
+
+static int utf8_is_continuation(char c) {
+    return ((unsigned char)c & 0xC0) == 0x80;
+}
+
+static int64_t utf8_length(const char* s) {
+    int64_t length = 0;
+    for (; *s != '\0'; s++) {
+        if (!utf8_is_continuation(*s)) {
+            length++;
+        }
+    }
+    return length;
+}
+
+// Byte offset of the `index`-th character of `s`, or of its end if it is shorter.
+static size_t utf8_offset(const char* s, int64_t index) {
+    size_t offset = 0;
+    while (index > 0 && s[offset] != '\0') {
+        offset++;
+        while (utf8_is_continuation(s[offset])) {
+            offset++;
+        }
+        index--;
+    }
+    return offset;
+}
+
+// Converts a HULK number to a character position, clamping negatives and NaN to 0.
+static int64_t to_position(double value) {
+    if (!(value > 0)) {
+        return 0;
+    }
+    if (value >= (double)INT32_MAX) {
+        return INT32_MAX;
+    }
+    return (int64_t)value;
+}
+
+// Copies `length` bytes of `s` starting at `offset` into a new string.
+static char* string_slice(char* s, size_t offset, size_t length) {
+    int64_t roots = hulk_gc_root_count();
+    hulk_gc_push_root((void**)&s);
+    char* result = hulk_gc_alloc(length + 1, NULL);
+    memcpy(result, s + offset, length);
+    hulk_gc_pop_roots(roots);
     return result;
-}
\ No newline at end of file
+}
+
+// `s.size()`
+double hulk_str_size(char* s) {
+    return (double)utf8_length(s);
+}
+
+// `s.substr(start, length)`: the characters of `s` from `start`, at most `length`.
+char* hulk_str_substr(char* s, double start, double length) {
+    size_t from = utf8_offset(s, to_position(start));
+    size_t to = from + utf8_offset(s + from, to_position(length));
+    return string_slice(s, from, to - from);
+}
+
+// `s.at(index)`: the character at `index`, or NULL when it is out of range
+// (the generated code reports it).
+char* hulk_str_at(char* s, double index) {
+    if (index < 0 || index >= (double)utf8_length(s)) {
+        return NULL;
+    }
+    size_t from = utf8_offset(s, (int64_t)index);
+    size_t to = from + utf8_offset(s + from, 1);
+    return string_slice(s, from, to - from);
+}
+
+// `s.indexOf(value)`: position of the first occurrence of `value`, or -1.
+double hulk_str_index_of(char* s, char* value) {
+    const char* found = strstr(s, value);
+    if (found == NULL) {
+        return -1;
+    }
+    int64_t index = 0;
+    for (const char* c = s; c < found; c++) {
+        if (!utf8_is_continuation(*c)) {
+            index++;
+        }
+    }
+    return (double)index;
+}
+
+static char* string_map_ascii(char* s, int (*map)(int)) {
+    char* result = string_slice(s, 0, strlen(s));
+    for (char* c = result; *c != '\0'; c++) {
+        if (((unsigned char)*c & 0x80) == 0) {
+            *c = (char)map(*c);
+        }
+    }
+    return result;
+}
+
+// `s.toUpper()`, for ASCII letters.
+char* hulk_str_to_upper(char* s) {
+    return string_map_ascii(s, toupper);
+}
+
+// `s.toLower()`, for ASCII letters.
+char* hulk_str_to_lower(char* s) {
+    return string_map_ascii(s, tolower);
+}
+
+// `s.parse()`: the number written in `s` (a decimal number with an optional
+// sign and exponent, surrounded by optional whitespace), or NaN if there is none
+// (the generated code reports it).
+double hulk_str_parse(char* s) {
+    const char* c = s;
+    while (isspace((unsigned char)*c)) {
+        c++;
+    }
+    const char* start = c;
+    if (*c == '+' || *c == '-') {
+        c++;
+    }
+    int digits = 0;
+    for (; isdigit((unsigned char)*c); c++) {
+        digits++;
+    }
+    if (*c == '.') {
+        for (c++; isdigit((unsigned char)*c); c++) {
+            digits++;
+        }
+    }
+    if (digits == 0) {
+        return NAN;
+    }
+    if (*c == 'e' || *c == 'E') {
+        c++;
+        if (*c == '+' || *c == '-') {
+            c++;
+        }
+        if (!isdigit((unsigned char)*c)) {
+            return NAN;
+        }
+        while (isdigit((unsigned char)*c)) {
+            c++;
+        }
+    }
+    while (isspace((unsigned char)*c)) {
+        c++;
+    }
+    if (*c != '\0') {
+        return NAN;
+    }
+    return strtod(start, NULL);
+}
+
+// ---------------------------------------------------------------------------
+// Console input
+// ---------------------------------------------------------------------------
+
+// `read()`: the next line of the standard input without its line terminator,
+// or "" at the end of the input.
+char* hulk_read_line(void) {
+    size_t capacity = 64, length = 0;
+    char* buffer = malloc(capacity);
+    int c;
+    while ((c = getchar()) != EOF && c != '\n') {
+        if (length + 1 == capacity) {
+            capacity *= 2;
+            buffer = realloc(buffer, capacity);
+        }
+        buffer[length++] = (char)c;
+    }
+    if (length > 0 && buffer[length - 1] == '\r') {
+        length--;
+    }
+    char* result = hulk_gc_alloc(length + 1, NULL);
+    memcpy(result, buffer, length);
+    result[length] = '\0';
+    free(buffer);
+    return result;
+}
+
+// `readNumber()`: the next line parsed like `parse()`, or NaN if it is not a
+// number (the generated code reports it).
+double hulk_read_number(void) {
+    return hulk_str_parse(hulk_read_line());
+}
+
+// `eof()`: 1 when there is nothing left to read from the standard input.
+int32_t hulk_eof(void) {
+    int c = getchar();
+    if (c == EOF) {
+        return 1;
+    }
+    ungetc(c, stdin);
+    return 0;
+}
@@ -1,4 +1,4 @@
-use crate::types_tree::tree_node::TypeNode;
+use crate::{tokens::Span, types_tree::tree_node::TypeNode};
 
 use super::expression::Expression;
 
@@ -19,13 +19,15 @@ impl ExpressionList {
 pub struct BlockNode {
     pub expression_list: Box<ExpressionList>,
     pub node_type: Option<TypeNode>,
+    pub span: Span,
 }
 
 impl BlockNode {
-    pub fn new(expression_list: ExpressionList) -> Self {
+    pub fn new(expression_list: ExpressionList, span: Span) -> Self {
         BlockNode {
             expression_list: Box::new(expression_list),
             node_type: None,
+            span,
         }
     }
     pub fn set_type(&mut self, node_type: TypeNode) {
@@ -9,6 +9,7 @@ use super::literals::{BooleanLiteralNode, IdentifierNode, NumberLiteralNode, Str
 use super::unary_op::UnaryOpNode;
 use super::while_loop::WhileNode;
 use crate::ast_nodes::print::PrintNode;
+use crate::ast_nodes::read::{ReadKind, ReadNode};
 use crate::ast_nodes::type_instance::TypeInstanceNode;
 use crate::ast_nodes::type_member_access::{TypeFunctionAccessNode, TypePropAccessNode};
 use crate::tokens::{OperatorToken, Span};
@@ -34,6 +35,7 @@ pub enum Expression {
     TypeFunctionAccess(TypeFunctionAccessNode),
     TypePropAccess(TypePropAccessNode),
     Print(PrintNode),
+    Read(ReadNode),
 }
 
 impl Expression {
@@ -71,8 +73,8 @@ impl Expression {
         Expression::ForLoop(ForNode::new(variable, start, end, body, span))
     }
 
-    pub fn new_code_block(expression_list: ExpressionList) -> Self {
-        Expression::CodeBlock(BlockNode::new(expression_list))
+    pub fn new_code_block(expression_list: ExpressionList, span: Span) -> Self {
+        Expression::CodeBlock(BlockNode::new(expression_list, span))
     }
 
     pub fn new_binary_op(
@@ -125,6 +127,10 @@ impl Expression {
         Expression::Print(PrintNode::new(expression, span))
     }
 
+    pub fn new_read(kind: ReadKind, span: Span) -> Self {
+        Expression::Read(ReadNode::new(kind, span))
+    }
+
     pub fn span(&self) -> Span {
         match self {
             Expression::Number(n) => n.span,
@@ -134,7 +140,7 @@ impl Expression {
             Expression::FunctionCall(f) => f.span,
             Expression::WhileLoop(w) => w.span,
             Expression::ForLoop(f) => f.span,
-            Expression::CodeBlock(b) => b.expression_list.expressions[0].span(),
+            Expression::CodeBlock(b) => b.span,
             Expression::BinaryOp(b) => b.span,
             Expression::UnaryOp(u) => u.span,
             Expression::IfElse(i) => i.span,
@@ -144,6 +150,7 @@ impl Expression {
             Expression::TypeFunctionAccess(t) => t.span,
             Expression::TypePropAccess(t) => t.span,
             Expression::Print(p) => p.span,
+            Expression::Read(r) => r.span,
         }
     }
 }
@@ -168,6 +175,7 @@ impl Accept for Expression {
             Expression::TypeFunctionAccess(node) => visitor.visit_type_function_access(node),
             Expression::TypePropAccess(node) => visitor.visit_type_prop_access(node),
             Expression::Print(node) => visitor.visit_print(node),
+            Expression::Read(node) => visitor.visit_read(node),
         }
     }
 }
@@ -4,17 +4,23 @@ use super::expression::Expression;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Assignment {
-    //TODO Add optional Signature Assignment
     pub identifier: String,
+    pub signature: Option<String>,
     pub expression: Box<Expression>,
     pub node_type: Option<TypeNode>,
     pub span: Span,
 }
 
 impl Assignment {
-    pub fn new(identifier: String, expression: Expression, span: Span) -> Self {
+    pub fn new(
+        identifier: String,
+        signature: Option<String>,
+        expression: Expression,
+        span: Span,
+    ) -> Self {
         Assignment {
             identifier,
+            signature,
             expression: Box::new(expression),
             node_type: None,
             span,
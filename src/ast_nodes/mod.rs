@@ -14,4 +14,5 @@ pub mod destructive_assign;
 pub mod type_def;
 pub mod type_instance;
 pub mod type_member_access;
-pub mod print;
\ No newline at end of file
+pub mod print;
+pub mod read;
\ No newline at end of file
@@ -0,0 +1,32 @@
+use crate::{tokens::Span, types_tree::tree_node::TypeNode};
+
+/// What a console input expression reads from the standard input.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ReadKind {
+    /// `read()`: the next line without its newline, or `""` at the end of the input.
+    Line,
+    /// `readNumber()`: the next line, as a number.
+    Number,
+    /// `eof()`: whether the whole input was read.
+    Eof,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ReadNode {
+    pub kind: ReadKind,
+    pub node_type: Option<TypeNode>,
+    pub span: Span,
+}
+
+impl ReadNode {
+    pub fn new(kind: ReadKind, span: Span) -> Self {
+        ReadNode {
+            kind,
+            node_type: None,
+            span,
+        }
+    }
+    pub fn set_type(&mut self, node_type: TypeNode) {
+        self.node_type = Some(node_type);
+    }
+}
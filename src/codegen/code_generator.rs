@@ -1,5 +1,9 @@
 use super::context::CodeGenContext;
+use super::ir::{Module, Type, Value};
 use super::llvm_utils::*;
+use super::target::Target;
+use super::visitor_codegen::GeneratorResult;
+use crate::ast_nodes::expression::Expression;
 use crate::ast_nodes::program::{Program, Statement};
 use crate::visitor::accept::Accept;
 
@@ -15,55 +19,106 @@ impl CodeGenerator {
         }
     }
 
-    pub fn generate(&mut self, program: &mut Program) -> String {
-        let mut module_code: Vec<String> = vec![];
-        generate_header(&mut module_code);
-        declare_printf(&mut module_code, &mut self.context);
-        generate_runtime_declarations(&mut module_code);
-        module_code.push("".into());
+    /// A code generator for a target other than the host.
+    pub fn with_target(target: Target) -> Self {
+        let mut generator = Self::new();
+        generator.context.target = target;
+        generator
+    }
 
-        let mut body_context = CodeGenContext::new();
-        std::mem::swap(&mut self.context, &mut body_context);
-        
-        let globals = self.context.take_globals();
-        std::mem::swap(&mut self.context, &mut body_context);
+    pub fn generate(&mut self, program: &mut Program) -> String {
+        self.generate_module(program).to_string()
+    }
 
-        module_code.extend(globals);
-        if !module_code.last().map(|s| s.is_empty()).unwrap_or(false) {
-            module_code.push("".into());
-        }
+    /// Generates the whole program into the in-memory module.
+    pub fn generate_module(&mut self, program: &mut Program) -> &Module {
+        generate_header(&mut self.context.module, &self.context.target);
+        declare_printf(&mut self.context);
+        generate_runtime_declarations(&mut self.context.module);
+        self.declare_string_methods();
 
         self.init_all_type_methods_and_props(program);
 
-        module_code.extend(self.get_definitions(program));
-        let main_code = &self.get_main_code(program);
-        generate_main_wrapper(&mut module_code, &main_code , self.context.str_constants.clone());
-        module_code.join("\n")
+        self.generate_definitions(program);
+        self.generate_main(program);
+        &self.context.module
+    }
+
+    /// Generates `expression`, attributing the instructions it emits to its span.
+    pub(crate) fn generate_expression(&mut self, expression: &mut Expression) -> GeneratorResult {
+        let previous = self.context.builder().set_location(Some(expression.span()));
+        let result = expression.accept(self);
+        self.context.builder().set_location(previous);
+        result
+    }
+
+    /// Returns the value a branch stores into the result of an `if` expression.
+    ///
+    /// A number or boolean branch of an `Object`-typed `if` (one without `else`, or whose
+    /// branches only share `Object`) is boxed: it is stored in a block of its own on the
+    /// garbage-collected heap, whose pointer is the branch's value.
+    pub(crate) fn branch_value(&mut self, branch: &GeneratorResult, llvm_type: &Type) -> Value {
+        if branch.llvm_type == *llvm_type {
+            return branch.value.clone();
+        }
+        assert!(
+            *llvm_type == Type::Ptr && matches!(branch.llvm_type, Type::Double | Type::I1),
+            "Cannot convert a branch of type {} to {}",
+            branch.llvm_type,
+            llvm_type
+        );
+        let builder = self.context.builder();
+        let boxed = builder.call(Type::Ptr, Value::Global("hulk_gc_alloc".to_string()), vec![
+            (Type::I64, Value::Int(8)),
+            (Type::Ptr, Value::default_of(&Type::Ptr)),
+        ]);
+        builder.store(branch.llvm_type.clone(), branch.value.clone(), boxed.clone());
+        boxed
     }
 
+    /// Converts a `Number` or `Boolean` operand of `@` to a `String`, as HULK does
+    /// implicitly. Strings are returned unchanged.
+    pub(crate) fn string_of(&mut self, value: GeneratorResult) -> GeneratorResult {
+        let string = match value.llvm_type {
+            Type::Double => self.context.builder().call(
+                Type::Ptr,
+                Value::Global("hulk_num_to_str".to_string()),
+                vec![(Type::Double, value.value)],
+            ),
+            Type::I1 => {
+                let true_str = self.context.module.add_string("true");
+                let false_str = self.context.module.add_string("false");
+                self.context.builder().select(value.value, Type::Ptr, true_str, false_str)
+            }
+            _ => return value,
+        };
+        GeneratorResult::new(string, Type::Ptr, "String".to_string())
+    }
 
-    fn get_main_code(&mut self, program: &mut Program) -> Vec<String> {
-        let main_code;
+    fn generate_main(&mut self, program: &mut Program) {
+        self.context.begin_function("main", Type::I32);
+        let first_statement = program.statements.iter().find_map(|statement| match statement {
+            Statement::StatementExpression(expression) => Some(expression.span()),
+            _ => None,
+        });
+        if let Some(span) = first_statement {
+            self.debug_subprogram("main", span, None, &[]);
+        }
+        self.trace_enter("main");
         for statement in &mut program.statements {
-            match statement {
-                Statement::StatementExpression(_) => {
-                    statement.accept(self);
-                }
-                _ => continue,
+            if let Statement::StatementExpression(expression) = statement {
+                self.generate_expression(expression);
             }
         }
-        main_code = self.context.code.clone();
-        self.context.code.clear();
-        main_code
+        self.trace_leave();
+        self.context.builder().ret(Some((Type::I32, Value::Int(0))));
+        self.context.end_function();
     }
 
-    fn get_definitions(&mut self, program: &mut Program) -> Vec<String> {
-        let definitions ;
-        self.context.add_line(format!("%VTableType = type [ {} x ptr ]", self.context.max_functions));
-        let vtable_declarations: Vec<String> = self.context.types_vtables.iter()
-            .map(|vtable| format!("ptr {}", vtable))
-            .collect();
-        self.context.add_line(format!("@super_vtable = global [{} x ptr] [{}]", self.context.count_types, vtable_declarations.join(", ")));
+    fn generate_definitions(&mut self, program: &mut Program) {
+        let vtable_type = Type::Array(self.context.max_functions as usize, Box::new(Type::Ptr));
+        self.context.module.types.push(("VTableType".to_string(), vtable_type));
+        self.generate_super_vtable();
         self.generate_get_vtable_method();
         for statement in &mut program.statements {
             match statement {
@@ -71,27 +126,11 @@ impl CodeGenerator {
                     statement.accept(self);
                 }
                 Statement::StatementFunctionDef(_) => {
-                    statement.accept(self); 
+                    statement.accept(self);
                 }
                 _ => continue,
             }
-        } 
-        definitions = self.context.code.clone();
-        self.context.code.clear();
-        definitions 
+        }
     }
 
 }
-
-fn generate_main_wrapper(module_code: &mut Vec<String>, body_code: &[String] , global_consts: Vec<String>) {
-    for global_const in global_consts {
-        module_code.push(global_const);
-    }
-    module_code.push("define i32 @main() {".into());
-    module_code.push("entry:".into());
-    for line in body_code {
-        module_code.push("  ".to_string() + line);
-    }
-    module_code.push("  ret i32 0".into());
-    module_code.push("}".into());
-}
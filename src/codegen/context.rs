@@ -1,41 +1,25 @@
 use std::collections::{HashSet, HashMap};
 
-#[derive(Clone, Copy, PartialEq, Debug)]
-pub enum Type {
-    Double,
-    Boolean,
-    String,
-}
+use super::ir::{Module, Type, Value};
+use super::ir_builder::FunctionBuilder;
+use super::source_map::SourceMap;
+use super::target::Target;
 
 pub struct CodeGenContext {
-    // Vector of strings representing the generated code
-    pub code: Vec<String>,
-    // Global variables, used to store global state
-    pub globals: Vec<String>,
+    // Module being generated
+    pub module: Module,
+    // Machine the module is generated for
+    pub target: Target,
+    // Program being compiled, used to report the location of runtime errors
+    pub source: Option<SourceMap>,
+    // Whether arithmetic is checked at runtime (division by zero)
+    pub checked: bool,
+    // Builder of the function being generated
+    pub function: Option<FunctionBuilder>,
     // Set of global constants, used to avoid re-declaring them
     pub global_constants: HashSet<String>,
-    // String constants used in the code, stored as a vector of strings
-    pub str_constants: Vec<String>,
-    // Counter for string constants, used to generate unique names
-    pub str_counter: usize,
-    // Counter for temporary variables, used to generate unique names
-    pub temp_counter: usize,
-    // Unique ID for the context, used to differentiate between different contexts
-    pub id: usize,
-    // Current scope ID, used to differentiate between variables in different scopes
-    scope_id: i32,
-    // Temporary variables, mapping temporary names to types
-    pub temp_types: HashMap<String, String>,
-    // String literals used in the code, mapping literal value to unique name
-    pub string_literals: HashMap<String, String>,
-    // Next string literal ID, used for unique naming of string constants
-    pub next_string_id: usize,
-    // Set of runtime functions that are used in the code
-    pub runtime_functions: HashSet<String>,
-    // Variables in the current scope, mapping variable names to types
-    pub variables: HashMap<String, String>,
-    // Scopes stack, each scope is a HashMap of variable names to types
-    pub scopes: Vec<HashMap<String, String>>,
+    // Scopes stack, each scope maps the visible variable names to their slots
+    pub scopes: Vec<HashMap<String, Variable>>,
     // (type, function_name) -> function_llvm_name
     pub function_member_llvm_names: HashMap<(String, String), String>,
     // (type) -> type_parent
@@ -52,6 +36,8 @@ pub struct CodeGenContext {
     pub type_functions_ids: HashMap<(String,String),i32>,
     // Current self type, used for methods
     pub current_self: Option<String>,
+    // Pointer to the object a method was called on
+    pub self_value: Option<Value>,
     // Maximum number of functions in a type's vtable
     pub max_functions: i32,
     // Count of types defined
@@ -66,29 +52,25 @@ pub struct CodeGenContext {
     pub types_members: HashMap<String, Vec<(String,String)>>,
 }
 
+/// A variable visible in the current scope.
 #[derive(Clone)]
-pub struct VariableInfo {
-    pub temp: String,
-    pub ty: String,
+pub struct Variable {
+    // Pointer to the stack slot holding the variable
+    pub slot: Value,
+    // LLVM type of the value stored in the slot
+    pub ty: Type,
 }
 
 impl Default for CodeGenContext {
     fn default() -> Self {
         Self {
-            code: Vec::new(),
-            globals: Vec::new(),
+            module: Module::new("hulk"),
+            target: Target::host(),
+            source: None,
+            checked: false,
+            function: None,
             global_constants: HashSet::new(),
-            str_constants: Vec::new(),
-            str_counter: 0,
-            temp_counter: 1,
-            id: 1,
-            scope_id: 0,
-            temp_types: HashMap::new(),
-            string_literals: HashMap::new(),
-            next_string_id: 0,
-            runtime_functions: HashSet::new(),
-            variables: HashMap::new(),
-            scopes: Vec::new(),
+            scopes: vec![HashMap::new()],
             function_member_llvm_names: HashMap::new(),
             inherits: HashMap::new(),
             constructor_args_types: HashMap::new(),
@@ -97,6 +79,7 @@ impl Default for CodeGenContext {
             type_members_ids: HashMap::new(),
             type_functions_ids: HashMap::new(),
             current_self: None,
+            self_value: None,
             max_functions: 0,
             count_types: 0,
             type_id: HashMap::new(),
@@ -112,137 +95,51 @@ impl CodeGenContext {
         Self::default()
     }
 
-    pub fn add_line(&mut self, line: String) {
-        self.code.push(line);
+    /// The builder of the function being generated.
+    pub fn builder(&mut self) -> &mut FunctionBuilder {
+        self.function.as_mut().expect("No function is being generated")
     }
 
-    pub fn new_temp(&mut self, ty: String) -> String {
-        let id = self.temp_counter;
-        self.temp_counter += 1;
-        let name = format!("%{}", id);
-        self.temp_types.insert(name.clone(), ty);
-        name
+    /// Starts generating a new function.
+    pub fn begin_function(&mut self, name: &str, ret: Type) {
+        self.function = Some(FunctionBuilder::new(name, ret));
     }
 
-    pub fn take_code(&mut self) -> Vec<String> {
-        let mut result = Vec::new();
-        result.extend(std::mem::take(&mut self.globals));
-        result.extend(std::mem::take(&mut self.code));
-        result
+    /// Adds the function being generated to the module.
+    pub fn end_function(&mut self) {
+        let builder = self.function.take().expect("No function is being generated");
+        self.module.functions.push(builder.finish());
     }
+
     pub fn add_global_constant(&mut self, name: &str) {
         self.global_constants.insert(name.to_string());
     }
-    
+
     pub fn is_global_constant(&self, name: &str) -> bool {
         self.global_constants.contains(name)
     }
 
-    pub fn take_globals(&mut self) -> Vec<String> {
-        std::mem::take(&mut self.globals)
-    }
-
-    pub fn take_body(&mut self) -> Vec<String> {
-        std::mem::take(&mut self.code)
-    }
-
-    pub fn get_type(&mut self, temp: &str) -> String {
-        self.temp_types.get(temp).expect("Unknown temporary").clone()
-    }
-
-    pub fn is_bool(&mut self, name: &str) -> bool {
-        self.get_type(name) == "Boolean"
-    }
-
-    pub fn is_string(&mut self, name: &str) -> bool {
-        self.get_type(name) == "String"
-    }
-
     pub fn enter_scope(&mut self) {
-        self.scope_id += 1;
-        self.scopes.push(self.variables.clone())
+        self.scopes.push(HashMap::new());
     }
 
     pub fn exit_scope(&mut self) {
-        self.variables = self.scopes.pop().unwrap_or_default();
+        self.scopes.pop();
     }
 
-    pub fn get_scope(&self) -> i32 {
-        self.scope_id
+    pub fn add_variable(&mut self, name: String, slot: Value, ty: Type) {
+        self.scopes
+            .last_mut()
+            .expect("No scope to declare the variable in")
+            .insert(name, Variable { slot, ty });
     }
 
-    pub fn add_string_literal(&mut self, value: &str) -> String {
-        if let Some(name) = self.string_literals.get(value) {
-            return name.clone();
-        }
-
-        let escaped = value
-            .replace("\\", "\\\\")
-            .replace("\"", "\\\"")
-            .replace("\n", "\\n")
-            .replace("\t", "\\t");
-
-        let name = format!("@.str.{}", self.next_string_id);
-        self.next_string_id += 1;
-
-        let global = format!(
-            "{} = private unnamed_addr constant [{} x i8] c\"{}\\00\"",
-            name,
-            escaped.len() + 1,
-            escaped
-        );
-        self.globals.push(global);
-        self.string_literals.insert(value.to_string(), name.clone());
-        name
-    }
-
-    pub fn add_str_const(&mut self, value: String, len: usize) -> String {
-        let constant_name = format!("@.str.{}", self.str_counter);
-        self.str_counter += 1;
-        
-        let escaped_value = value
-            .replace('\\', "\\\\")
-            .replace('\"', "\\\"")
-            .replace('\n', "\\0A")
-            .replace('\0', "\\00");
-        
-        let line = format!(
-            "{} = private unnamed_addr constant [{} x i8] c\"{}\\00\"", 
-            constant_name, len + 1, escaped_value
-        );
-        
-        if !self.str_constants.contains(&line) { 
-            self.str_constants.push(line);
-        }
-        constant_name
-    }
-    
-    pub fn add_global_declaration(&mut self, decl: String) {
-        self.globals.push(decl);
-    }
-    
-    pub fn add_variable(&mut self, name: String, ty: String) {
-        self.variables.insert(
-            name,
-            ty,
-        );
-    }
-    
-    pub fn get_variable(&self, name: String) -> String {
-        let mut current_scope = self.scope_id.clone();
-        while current_scope >= 0 {
-            let register = format!("%{}.{}",name,current_scope);
-            if let Some(_) = self.variables.get(&register) {
-                return register;
+    pub fn get_variable(&self, name: &str) -> Variable {
+        for scope in self.scopes.iter().rev() {
+            if let Some(variable) = scope.get(name) {
+                return variable.clone();
             }
-            current_scope -= 1;
         }
-        panic!("Variable not found: {}",name.to_string())
+        panic!("Variable not found: {}", name)
     }
-
-    pub fn new_id(&mut self) -> usize {
-        let id = self.id;
-        self.id += 1;
-        id
-    }
-}
\ No newline at end of file
+}
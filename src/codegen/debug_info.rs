@@ -0,0 +1,326 @@
+//! Source-level debug information of a module, printed as LLVM `!DI*` metadata
+//! from which the backend emits DWARF.
+//!
+//! Scopes (one subprogram per function, one lexical block per `let`) and local
+//! variables are registered while generating code and referenced by id from the
+//! functions and instructions of the module. Lines and columns are computed from
+//! the byte offsets of `Span`s with the `SourceMap` of the program.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use super::code_generator::CodeGenerator;
+use super::ir::{Location, Value};
+use super::source_map::SourceMap;
+use crate::tokens::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScopeId(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VariableId(pub usize);
+
+/// HULK type of a variable, a parameter or a return value.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DebugType {
+    Number,
+    Boolean,
+    String,
+    /// A pointer to an instance of the named type.
+    Object(String),
+}
+
+impl DebugType {
+    pub fn from_hulk(type_name: &str) -> Self {
+        match type_name {
+            "Number" => DebugType::Number,
+            "Boolean" => DebugType::Boolean,
+            "String" => DebugType::String,
+            name => DebugType::Object(name.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Scope {
+    /// A function, method or constructor defined in the program.
+    Subprogram {
+        name: String,
+        line: usize,
+        /// `None` for `main`, which returns the exit status.
+        ret: Option<DebugType>,
+        params: Vec<DebugType>,
+    },
+    LexicalBlock { parent: ScopeId, line: usize, column: usize },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalVariable {
+    pub name: String,
+    pub scope: ScopeId,
+    pub line: usize,
+    /// 1-based position of the variable among the parameters of its function.
+    pub arg: Option<u32>,
+    pub ty: DebugType,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugInfo {
+    pub file: String,
+    pub directory: String,
+    /// Width of a pointer on the target, in bits.
+    pub pointer_width: u32,
+    pub scopes: Vec<Scope>,
+    pub variables: Vec<LocalVariable>,
+    source: SourceMap,
+}
+
+impl DebugInfo {
+    pub fn new(source: SourceMap, pointer_width: u32) -> Self {
+        let path = std::path::absolute(&source.path).unwrap_or_else(|_| source.path.clone().into());
+        let file = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let directory = path.parent().map(|dir| dir.to_string_lossy().into_owned()).unwrap_or_default();
+        DebugInfo { file, directory, pointer_width, scopes: Vec::new(), variables: Vec::new(), source }
+    }
+
+    /// Line and column of `offset` in the user's file, see `SourceMap::line_col`.
+    pub fn line_col(&self, offset: usize) -> Option<(usize, usize)> {
+        self.source.line_col(offset)
+    }
+
+    pub fn add_scope(&mut self, scope: Scope) -> ScopeId {
+        self.scopes.push(scope);
+        ScopeId(self.scopes.len() - 1)
+    }
+
+    pub fn add_variable(&mut self, variable: LocalVariable) -> VariableId {
+        self.variables.push(variable);
+        VariableId(self.variables.len() - 1)
+    }
+}
+
+/// Numbers the metadata nodes of a module while it is printed.
+pub(crate) struct MetadataPrinter<'a> {
+    info: &'a DebugInfo,
+    nodes: Vec<String>,
+    file: usize,
+    unit: usize,
+    scopes: Vec<usize>,
+    variables: Vec<usize>,
+    types: HashMap<DebugType, usize>,
+    locations: HashMap<(usize, usize, ScopeId), usize>,
+}
+
+impl<'a> MetadataPrinter<'a> {
+    pub fn new(info: &'a DebugInfo) -> Self {
+        let mut printer = MetadataPrinter {
+            info,
+            nodes: Vec::new(),
+            file: 0,
+            unit: 0,
+            scopes: Vec::new(),
+            variables: Vec::new(),
+            types: HashMap::new(),
+            locations: HashMap::new(),
+        };
+        printer.file = printer.node(format!(
+            "!DIFile(filename: {}, directory: {})",
+            quoted(&info.file),
+            quoted(&info.directory)
+        ));
+        printer.unit = printer.node(format!(
+            "distinct !DICompileUnit(language: DW_LANG_C, file: !{}, producer: \"hulk_compiler\", \
+             isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug)",
+            printer.file
+        ));
+        for scope in &info.scopes {
+            let node = match scope {
+                Scope::Subprogram { name, line, ret, params } => {
+                    let mut types = vec![match ret {
+                        Some(ret) => format!("!{}", printer.debug_type(ret)),
+                        None => "null".to_string(),
+                    }];
+                    types.extend(params.iter().map(|param| format!("!{}", printer.debug_type(param))));
+                    let types = printer.node(format!("!{{{}}}", types.join(", ")));
+                    let subroutine = printer.node(format!("!DISubroutineType(types: !{})", types));
+                    format!(
+                        "distinct !DISubprogram(name: {}, scope: !{}, file: !{}, line: {}, \
+                         type: !{}, scopeLine: {}, spFlags: DISPFlagDefinition, unit: !{})",
+                        quoted(name),
+                        printer.file,
+                        printer.file,
+                        line,
+                        subroutine,
+                        line,
+                        printer.unit
+                    )
+                }
+                Scope::LexicalBlock { parent, line, column } => format!(
+                    "distinct !DILexicalBlock(scope: !{}, file: !{}, line: {}, column: {})",
+                    printer.scopes[parent.0], printer.file, line, column
+                ),
+            };
+            let id = printer.node(node);
+            printer.scopes.push(id);
+        }
+        for variable in &info.variables {
+            let ty = printer.debug_type(&variable.ty);
+            let arg = variable.arg.map(|arg| format!("arg: {}, ", arg)).unwrap_or_default();
+            let id = printer.node(format!(
+                "!DILocalVariable(name: {}, {}scope: !{}, file: !{}, line: {}, type: !{})",
+                quoted(&variable.name),
+                arg,
+                printer.scopes[variable.scope.0],
+                printer.file,
+                variable.line,
+                ty
+            ));
+            printer.variables.push(id);
+        }
+        printer
+    }
+
+    fn node(&mut self, text: String) -> usize {
+        self.nodes.push(text);
+        self.nodes.len() - 1
+    }
+
+    fn debug_type(&mut self, ty: &DebugType) -> usize {
+        if let Some(id) = self.types.get(ty) {
+            return *id;
+        }
+        let width = self.info.pointer_width;
+        let text = match ty {
+            DebugType::Number => "!DIBasicType(name: \"Number\", size: 64, encoding: DW_ATE_float)".to_string(),
+            DebugType::Boolean => "!DIBasicType(name: \"Boolean\", size: 8, encoding: DW_ATE_boolean)".to_string(),
+            DebugType::String => {
+                let char_type =
+                    self.node("!DIBasicType(name: \"char\", size: 8, encoding: DW_ATE_signed_char)".to_string());
+                format!("!DIDerivedType(tag: DW_TAG_pointer_type, name: \"String\", baseType: !{}, size: {})", char_type, width)
+            }
+            DebugType::Object(name) => {
+                let object = self.node(format!(
+                    "!DICompositeType(tag: DW_TAG_structure_type, name: {}, file: !{}, flags: DIFlagFwdDecl)",
+                    quoted(name),
+                    self.file
+                ));
+                format!("!DIDerivedType(tag: DW_TAG_pointer_type, baseType: !{}, size: {})", object, width)
+            }
+        };
+        let id = self.node(text);
+        self.types.insert(ty.clone(), id);
+        id
+    }
+
+    pub fn scope(&self, scope: ScopeId) -> usize {
+        self.scopes[scope.0]
+    }
+
+    pub fn variable(&self, variable: VariableId) -> usize {
+        self.variables[variable.0]
+    }
+
+    /// The `!DILocation` of an instruction, if it has a line and a scope.
+    pub fn location(&mut self, location: &Location) -> Option<usize> {
+        let scope = location.scope?;
+        let (line, column) = self.info.line_col(location.span.start)?;
+        if let Some(id) = self.locations.get(&(line, column, scope)) {
+            return Some(*id);
+        }
+        let id = self.node(format!("!DILocation(line: {}, column: {}, scope: !{})", line, column, self.scopes[scope.0]));
+        self.locations.insert((line, column, scope), id);
+        Some(id)
+    }
+
+    /// Named metadata and every node, printed at the end of the module.
+    pub fn finish(mut self) -> String {
+        let dwarf_version = self.node("!{i32 2, !\"Dwarf Version\", i32 4}".to_string());
+        let debug_info_version = self.node("!{i32 2, !\"Debug Info Version\", i32 3}".to_string());
+        let mut text = String::new();
+        let _ = writeln!(text, "!llvm.dbg.cu = !{{!{}}}", self.unit);
+        let _ = writeln!(text, "!llvm.module.flags = !{{!{}, !{}}}", dwarf_version, debug_info_version);
+        let _ = writeln!(text);
+        for (id, node) in self.nodes.iter().enumerate() {
+            let _ = writeln!(text, "!{} = {}", id, node);
+        }
+        text
+    }
+}
+
+fn quoted(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for byte in text.bytes() {
+        if byte.is_ascii_graphic() && byte != b'"' && byte != b'\\' || byte == b' ' {
+            quoted.push(byte as char);
+        } else {
+            let _ = write!(quoted, "\\{:02X}", byte);
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+impl CodeGenerator {
+    /// Emits debug info describing the program given to `set_source`.
+    pub fn enable_debug_info(&mut self) {
+        let source = self.context.source.clone().expect("The source must be set to emit debug info");
+        let pointer_width = self.context.target.pointer_width;
+        self.context.module.debug_info = Some(DebugInfo::new(source, pointer_width));
+    }
+
+    /// Attaches a debug scope to the function being generated, defined at `span`.
+    /// Builtin functions get none.
+    pub(crate) fn debug_subprogram(&mut self, name: &str, span: Span, ret: Option<&str>, params: &[String]) {
+        let Some(info) = self.context.module.debug_info.as_mut() else {
+            return;
+        };
+        let Some((line, _)) = info.line_col(span.start) else {
+            return;
+        };
+        let scope = info.add_scope(Scope::Subprogram {
+            name: name.to_string(),
+            line,
+            ret: ret.map(DebugType::from_hulk),
+            params: params.iter().map(|param| DebugType::from_hulk(param)).collect(),
+        });
+        self.context.builder().set_subprogram(scope);
+    }
+
+    /// Opens the lexical block of a `let` at `span` and returns the scope to
+    /// restore with `debug_exit_block`.
+    pub(crate) fn debug_enter_block(&mut self, span: Span) -> Option<ScopeId> {
+        let parent = self.context.builder().scope()?;
+        let info = self.context.module.debug_info.as_mut()?;
+        if let Some((line, column)) = info.line_col(span.start) {
+            let block = info.add_scope(Scope::LexicalBlock { parent, line, column });
+            self.context.builder().set_scope(Some(block));
+        }
+        Some(parent)
+    }
+
+    pub(crate) fn debug_exit_block(&mut self, parent: Option<ScopeId>) {
+        self.context.builder().set_scope(parent);
+    }
+
+    /// Describes the variable `name` of HULK type `ty`, declared at `span`, whose
+    /// value is stored at `address`. `arg` is its position among the parameters.
+    pub(crate) fn debug_variable(&mut self, name: &str, ty: &str, span: Span, address: &Value, arg: Option<u32>) {
+        let Some(scope) = self.context.builder().scope() else {
+            return;
+        };
+        let Some(info) = self.context.module.debug_info.as_mut() else {
+            return;
+        };
+        let Some((line, _)) = info.line_col(span.start) else {
+            return;
+        };
+        let variable = info.add_variable(LocalVariable {
+            name: name.to_string(),
+            scope,
+            line,
+            arg,
+            ty: DebugType::from_hulk(ty),
+        });
+        self.context.builder().dbg_declare(address.clone(), variable);
+    }
+}
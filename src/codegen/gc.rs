@@ -0,0 +1,52 @@
+//! Root registration for the runtime garbage collector.
+//!
+//! Every stack slot that holds a `ptr` to a heap value is registered with
+//! `hulk_gc_push_root` right after its first store. Roots are released in LIFO
+//! order with `hulk_gc_pop_roots` when the slot goes out of use, so a region of
+//! generated code takes the current root count lazily (`mark`) before the first
+//! registration and restores it at the end.
+//!
+//! Pointer values that only live in a register while another allocation may run
+//! (the left operand of `@`, the receiver of a method call, the object being
+//! built by a constructor) are spilled into a rooted slot first.
+
+use super::code_generator::CodeGenerator;
+use super::ir::{Type, Value};
+use super::visitor_codegen::GeneratorResult;
+
+impl CodeGenerator {
+    /// Registers `slot` as a GC root when it holds a value of type `ty`
+    /// that the collector may need to trace.
+    pub(crate) fn gc_root(&mut self, slot: &Value, ty: &Type, mark: &mut Option<Value>) {
+        if *ty != Type::Ptr {
+            return;
+        }
+        let builder = self.context.builder();
+        if mark.is_none() {
+            *mark = Some(builder.call(Type::I64, Value::Global("hulk_gc_root_count".to_string()), vec![]));
+        }
+        builder.call_void(Value::Global("hulk_gc_push_root".to_string()), vec![(Type::Ptr, slot.clone())]);
+    }
+
+    /// Stores a pointer value into a rooted slot, keeping it alive until the
+    /// roots of `mark` are released.
+    pub(crate) fn gc_spill(&mut self, value: &GeneratorResult, mark: &mut Option<Value>) {
+        // Globals (string literals) and `null` are never collected
+        if value.llvm_type != Type::Ptr || matches!(value.value, Value::Global(_) | Value::Null) {
+            return;
+        }
+        let builder = self.context.builder();
+        let slot = builder.alloca(Type::Ptr, None);
+        builder.store(Type::Ptr, value.value.clone(), slot.clone());
+        self.gc_root(&slot, &Type::Ptr, mark);
+    }
+
+    /// Releases every root registered since `mark` was taken.
+    pub(crate) fn gc_release(&mut self, mark: Option<Value>) {
+        if let Some(count) = mark {
+            self.context
+                .builder()
+                .call_void(Value::Global("hulk_gc_pop_roots".to_string()), vec![(Type::I64, count)]);
+        }
+    }
+}
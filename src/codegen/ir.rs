@@ -0,0 +1,556 @@
+//! In-memory representation of the LLVM module built by the code generator.
+//!
+//! The visitor builds functions made of basic blocks of typed instructions over SSA
+//! values (see `ir_builder`), and the whole module is printed as LLVM text at the end.
+//! Values and blocks are identified by indices, and their textual names are only
+//! chosen when printing, so two definitions can never collide.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter, Write};
+
+use super::debug_info::{DebugInfo, MetadataPrinter, ScopeId, VariableId};
+use crate::tokens::Span;
+
+/// An LLVM type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Void,
+    I1,
+    I8,
+    I32,
+    I64,
+    Double,
+    Ptr,
+    /// A named type declared in the module, printed as `%name`.
+    Named(String),
+    Array(usize, Box<Type>),
+    Struct(Vec<Type>),
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Void => write!(f, "void"),
+            Type::I1 => write!(f, "i1"),
+            Type::I8 => write!(f, "i8"),
+            Type::I32 => write!(f, "i32"),
+            Type::I64 => write!(f, "i64"),
+            Type::Double => write!(f, "double"),
+            Type::Ptr => write!(f, "ptr"),
+            Type::Named(name) => write!(f, "%{}", name),
+            Type::Array(len, elem) => write!(f, "[{} x {}]", len, elem),
+            Type::Struct(fields) => write!(f, "{{ {} }}", join(fields.iter().map(|t| t.to_string()))),
+        }
+    }
+}
+
+/// Identifies an SSA value (a parameter or an instruction result) inside its function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ValueId(pub usize);
+
+/// Identifies a basic block inside its function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlockId(pub usize);
+
+/// An instruction operand.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Local(ValueId),
+    Global(String),
+    Double(f64),
+    Int(i64),
+    Bool(bool),
+    Null,
+}
+
+impl Value {
+    /// The default value of a HULK expression of the given LLVM type
+    /// when no branch produced one (e.g. an `if` without `else`).
+    pub fn default_of(ty: &Type) -> Value {
+        match ty {
+            Type::Double => Value::Double(0.0),
+            Type::I1 => Value::Bool(false),
+            Type::I8 | Type::I32 | Type::I64 => Value::Int(0),
+            _ => Value::Null,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    FAdd,
+    FSub,
+    FMul,
+    FDiv,
+    Add,
+    Sub,
+    And,
+    Or,
+    Xor,
+}
+
+impl Display for BinaryOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            BinaryOp::FAdd => "fadd",
+            BinaryOp::FSub => "fsub",
+            BinaryOp::FMul => "fmul",
+            BinaryOp::FDiv => "fdiv",
+            BinaryOp::Add => "add",
+            BinaryOp::Sub => "sub",
+            BinaryOp::And => "and",
+            BinaryOp::Or => "or",
+            BinaryOp::Xor => "xor",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Ordered comparisons between doubles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FloatPredicate {
+    Oeq,
+    One,
+    Ogt,
+    Oge,
+    Olt,
+    Ole,
+}
+
+impl Display for FloatPredicate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FloatPredicate::Oeq => "oeq",
+            FloatPredicate::One => "one",
+            FloatPredicate::Ogt => "ogt",
+            FloatPredicate::Oge => "oge",
+            FloatPredicate::Olt => "olt",
+            FloatPredicate::Ole => "ole",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Comparisons between integers or pointers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntPredicate {
+    Eq,
+    Ne,
+    Slt,
+    Sle,
+    Sgt,
+    Sge,
+}
+
+impl Display for IntPredicate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            IntPredicate::Eq => "eq",
+            IntPredicate::Ne => "ne",
+            IntPredicate::Slt => "slt",
+            IntPredicate::Sle => "sle",
+            IntPredicate::Sgt => "sgt",
+            IntPredicate::Sge => "sge",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A non-terminator instruction. Every instruction producing a value defines `dest`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instruction {
+    Alloca { dest: ValueId, ty: Type },
+    Load { dest: ValueId, ty: Type, ptr: Value },
+    Store { ty: Type, value: Value, ptr: Value },
+    Binary { dest: ValueId, op: BinaryOp, ty: Type, lhs: Value, rhs: Value },
+    FCmp { dest: ValueId, predicate: FloatPredicate, lhs: Value, rhs: Value },
+    ICmp { dest: ValueId, predicate: IntPredicate, ty: Type, lhs: Value, rhs: Value },
+    Select { dest: ValueId, condition: Value, ty: Type, then_value: Value, else_value: Value },
+    /// `getelementptr ty, ptr ptr, indices...`
+    GetElementPtr { dest: ValueId, ty: Type, ptr: Value, indices: Vec<(Type, Value)> },
+    PtrToInt { dest: ValueId, value: Value, ty: Type },
+    /// A call; `variadic` holds the fixed parameter types of a variadic callee.
+    Call {
+        dest: Option<ValueId>,
+        ret: Type,
+        callee: Value,
+        args: Vec<(Type, Value)>,
+        variadic: Option<Vec<Type>>,
+    },
+    /// `llvm.dbg.declare`: the debug variable `variable` lives at `address`.
+    DbgDeclare { address: Value, variable: VariableId },
+}
+
+impl Instruction {
+    /// The value defined by the instruction, if any.
+    pub fn dest(&self) -> Option<ValueId> {
+        match self {
+            Instruction::Alloca { dest, .. }
+            | Instruction::Load { dest, .. }
+            | Instruction::Binary { dest, .. }
+            | Instruction::FCmp { dest, .. }
+            | Instruction::ICmp { dest, .. }
+            | Instruction::Select { dest, .. }
+            | Instruction::GetElementPtr { dest, .. }
+            | Instruction::PtrToInt { dest, .. } => Some(*dest),
+            Instruction::Call { dest, .. } => *dest,
+            Instruction::Store { .. } | Instruction::DbgDeclare { .. } => None,
+        }
+    }
+}
+
+/// The instruction ending a basic block.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Terminator {
+    Br(BlockId),
+    CondBr { condition: Value, then_block: BlockId, else_block: BlockId },
+    Ret(Option<(Type, Value)>),
+    Unreachable,
+}
+
+/// The HULK expression an instruction was generated for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Location {
+    pub span: Span,
+    /// Debug scope of the instruction, when the module has debug info.
+    pub scope: Option<ScopeId>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasicBlock {
+    pub label: String,
+    pub instructions: Vec<Instruction>,
+    /// Location of each instruction, indexed like `instructions`.
+    pub locations: Vec<Option<Location>>,
+    pub terminator: Option<Terminator>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub name: String,
+    pub ret: Type,
+    pub params: Vec<(Type, ValueId)>,
+    pub blocks: Vec<BasicBlock>,
+    /// Order in which the blocks are printed; the entry block comes first.
+    pub layout: Vec<BlockId>,
+    /// Optional name hint of every value, indexed by `ValueId`.
+    pub value_names: Vec<Option<String>>,
+    /// Debug scope of the function, when the module has debug info.
+    pub subprogram: Option<ScopeId>,
+}
+
+impl Function {
+    /// Chooses the textual name of every value: hinted values keep their hint plus
+    /// their id, the rest are numbered in order of definition as LLVM requires.
+    fn local_names(&self) -> Vec<String> {
+        let mut names = vec![String::new(); self.value_names.len()];
+        let mut next = 0;
+        let mut name = |id: ValueId| {
+            names[id.0] = match &self.value_names[id.0] {
+                Some(hint) => format!("%{}.{}", hint, id.0),
+                None => {
+                    next += 1;
+                    format!("%{}", next - 1)
+                }
+            };
+        };
+        for (_, id) in &self.params {
+            name(*id);
+        }
+        for block in &self.layout {
+            for instruction in &self.blocks[block.0].instructions {
+                if let Some(dest) = instruction.dest() {
+                    name(dest);
+                }
+            }
+        }
+        names
+    }
+}
+
+/// A constant initializer of a global.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constant {
+    Value(Value),
+    /// Raw bytes printed as `c"..."`; the terminating NUL must be included.
+    Bytes(Vec<u8>),
+    Array(Type, Vec<Constant>),
+    Struct(Vec<(Type, Constant)>),
+    /// Byte offset of field `field` inside the named struct `ty`.
+    FieldOffset { ty: Type, field: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Global {
+    pub name: String,
+    pub ty: Type,
+    pub init: Constant,
+    pub constant: bool,
+    /// Printed as `private unnamed_addr`, for string literals and formats.
+    pub private: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Declaration {
+    pub name: String,
+    pub ret: Type,
+    pub params: Vec<Type>,
+    pub variadic: bool,
+}
+
+/// A whole LLVM module.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Module {
+    pub name: String,
+    /// Empty when the data layout is left to LLVM.
+    pub data_layout: String,
+    pub target_triple: String,
+    /// Named types, in declaration order.
+    pub types: Vec<(String, Type)>,
+    pub globals: Vec<Global>,
+    pub declarations: Vec<Declaration>,
+    pub functions: Vec<Function>,
+    pub debug_info: Option<DebugInfo>,
+    /// String literal contents -> name of the global holding them.
+    strings: HashMap<Vec<u8>, String>,
+}
+
+impl Module {
+    pub fn new(name: &str) -> Self {
+        Module {
+            name: name.to_string(),
+            data_layout: String::new(),
+            target_triple: String::new(),
+            types: Vec::new(),
+            globals: Vec::new(),
+            declarations: Vec::new(),
+            functions: Vec::new(),
+            debug_info: None,
+            strings: HashMap::new(),
+        }
+    }
+
+    /// Declares an external function, unless it is already declared.
+    pub fn declare(&mut self, name: &str, ret: Type, params: Vec<Type>, variadic: bool) {
+        if self.declarations.iter().any(|d| d.name == name) {
+            return;
+        }
+        self.declarations.push(Declaration { name: name.to_string(), ret, params, variadic });
+    }
+
+    pub fn add_global(&mut self, global: Global) {
+        self.globals.push(global);
+    }
+
+    /// Returns a pointer to a NUL-terminated constant holding `value`,
+    /// emitting the constant the first time the string is used.
+    pub fn add_string(&mut self, value: &str) -> Value {
+        let mut bytes = value.as_bytes().to_vec();
+        bytes.push(0);
+        if let Some(name) = self.strings.get(&bytes) {
+            return Value::Global(name.clone());
+        }
+        let name = format!(".str.{}", self.strings.len());
+        self.globals.push(Global {
+            name: name.clone(),
+            ty: Type::Array(bytes.len(), Box::new(Type::I8)),
+            init: Constant::Bytes(bytes.clone()),
+            constant: true,
+            private: true,
+        });
+        self.strings.insert(bytes, name.clone());
+        Value::Global(name)
+    }
+}
+
+fn join(items: impl Iterator<Item = String>) -> String {
+    items.collect::<Vec<_>>().join(", ")
+}
+
+/// Prints an operand; locals are looked up in the names of the enclosing function.
+fn operand(value: &Value, names: &[String]) -> String {
+    match value {
+        Value::Local(id) => names[id.0].clone(),
+        Value::Global(name) => format!("@{}", name),
+        // Hexadecimal keeps every double exact
+        Value::Double(value) => format!("0x{:016X}", value.to_bits()),
+        Value::Int(value) => value.to_string(),
+        Value::Bool(value) => value.to_string(),
+        Value::Null => "null".to_string(),
+    }
+}
+
+fn constant(value: &Constant) -> String {
+    match value {
+        Constant::Value(value) => operand(value, &[]),
+        Constant::Bytes(bytes) => {
+            let mut text = String::from("c\"");
+            for byte in bytes {
+                if byte.is_ascii_graphic() && *byte != b'"' && *byte != b'\\' || *byte == b' ' {
+                    text.push(*byte as char);
+                } else {
+                    let _ = write!(text, "\\{:02X}", byte);
+                }
+            }
+            text.push('"');
+            text
+        }
+        Constant::Array(elem, items) => {
+            format!("[ {} ]", join(items.iter().map(|item| format!("{} {}", elem, constant(item)))))
+        }
+        Constant::Struct(fields) => {
+            format!("{{ {} }}", join(fields.iter().map(|(ty, item)| format!("{} {}", ty, constant(item)))))
+        }
+        Constant::FieldOffset { ty, field } => {
+            format!("ptrtoint (ptr getelementptr ({}, ptr null, i32 0, i32 {}) to i64)", ty, field)
+        }
+    }
+}
+
+fn typed_args(args: &[(Type, Value)], names: &[String]) -> String {
+    join(args.iter().map(|(ty, value)| format!("{} {}", ty, operand(value, names))))
+}
+
+fn instruction(instruction: &Instruction, names: &[String], metadata: Option<&MetadataPrinter>) -> String {
+    let op = |value: &Value| operand(value, names);
+    match instruction {
+        Instruction::Alloca { dest, ty } => format!("{} = alloca {}", names[dest.0], ty),
+        Instruction::Load { dest, ty, ptr } => format!("{} = load {}, ptr {}", names[dest.0], ty, op(ptr)),
+        Instruction::Store { ty, value, ptr } => format!("store {} {}, ptr {}", ty, op(value), op(ptr)),
+        Instruction::Binary { dest, op: binary, ty, lhs, rhs } => {
+            format!("{} = {} {} {}, {}", names[dest.0], binary, ty, op(lhs), op(rhs))
+        }
+        Instruction::FCmp { dest, predicate, lhs, rhs } => {
+            format!("{} = fcmp {} double {}, {}", names[dest.0], predicate, op(lhs), op(rhs))
+        }
+        Instruction::ICmp { dest, predicate, ty, lhs, rhs } => {
+            format!("{} = icmp {} {} {}, {}", names[dest.0], predicate, ty, op(lhs), op(rhs))
+        }
+        Instruction::Select { dest, condition, ty, then_value, else_value } => format!(
+            "{} = select i1 {}, {} {}, {} {}",
+            names[dest.0], op(condition), ty, op(then_value), ty, op(else_value)
+        ),
+        Instruction::GetElementPtr { dest, ty, ptr, indices } => {
+            format!("{} = getelementptr {}, ptr {}, {}", names[dest.0], ty, op(ptr), typed_args(indices, names))
+        }
+        Instruction::PtrToInt { dest, value, ty } => format!("{} = ptrtoint ptr {} to {}", names[dest.0], op(value), ty),
+        Instruction::Call { dest, ret, callee, args, variadic } => {
+            let signature = match variadic {
+                Some(fixed) => format!("{} ({}, ...)", ret, join(fixed.iter().map(|t| t.to_string()))),
+                None => ret.to_string(),
+            };
+            let call = format!("call {} {}({})", signature, op(callee), typed_args(args, names));
+            match dest {
+                Some(dest) => format!("{} = {}", names[dest.0], call),
+                None => call,
+            }
+        }
+        Instruction::DbgDeclare { address, variable } => {
+            let variable = metadata.expect("Debug variable in a module without debug info").variable(*variable);
+            format!(
+                "call void @llvm.dbg.declare(metadata ptr {}, metadata !{}, metadata !DIExpression())",
+                op(address),
+                variable
+            )
+        }
+    }
+}
+
+fn terminator(terminator: &Terminator, function: &Function, names: &[String]) -> String {
+    let label = |block: &BlockId| format!("label %{}", function.blocks[block.0].label);
+    match terminator {
+        Terminator::Br(target) => format!("br {}", label(target)),
+        Terminator::CondBr { condition, then_block, else_block } => {
+            format!("br i1 {}, {}, {}", operand(condition, names), label(then_block), label(else_block))
+        }
+        Terminator::Ret(Some((ty, value))) => format!("ret {} {}", ty, operand(value, names)),
+        Terminator::Ret(None) => "ret void".to_string(),
+        Terminator::Unreachable => "unreachable".to_string(),
+    }
+}
+
+/// Prints a function; with `metadata`, its debug scope and the location of its
+/// instructions are attached as `!dbg`.
+fn write_function(f: &mut impl fmt::Write, function: &Function, mut metadata: Option<&mut MetadataPrinter>) -> fmt::Result {
+    let names = function.local_names();
+    let params = join(function.params.iter().map(|(ty, id)| format!("{} {}", ty, names[id.0])));
+    let subprogram = match (&metadata, function.subprogram) {
+        (Some(metadata), Some(scope)) => format!(" !dbg !{}", metadata.scope(scope)),
+        _ => String::new(),
+    };
+    writeln!(f, "define {} @{}({}){} {{", function.ret, function.name, params, subprogram)?;
+    for (index, block) in function.layout.iter().enumerate() {
+        let block = &function.blocks[block.0];
+        if index > 0 {
+            writeln!(f)?;
+        }
+        writeln!(f, "{}:", block.label)?;
+        for (inst, location) in block.instructions.iter().zip(&block.locations) {
+            let text = instruction(inst, &names, metadata.as_deref());
+            let location = match (&mut metadata, location) {
+                (Some(metadata), Some(location)) => metadata.location(location),
+                _ => None,
+            };
+            match location {
+                Some(location) => writeln!(f, "  {}, !dbg !{}", text, location)?,
+                None => writeln!(f, "  {}", text)?,
+            }
+        }
+        match &block.terminator {
+            Some(term) => writeln!(f, "  {}", terminator(term, function, &names))?,
+            None => writeln!(f, "  unreachable")?,
+        }
+    }
+    write!(f, "}}")
+}
+
+impl Display for Function {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_function(f, self, None)
+    }
+}
+
+impl Display for Module {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "; ModuleID = '{}'", self.name)?;
+        if !self.data_layout.is_empty() {
+            writeln!(f, "target datalayout = \"{}\"", self.data_layout)?;
+        }
+        writeln!(f, "target triple = \"{}\"", self.target_triple)?;
+        writeln!(f)?;
+        for (name, ty) in &self.types {
+            writeln!(f, "%{} = type {}", name, ty)?;
+        }
+        writeln!(f)?;
+        for global in &self.globals {
+            let linkage = if global.private { "private unnamed_addr " } else { "" };
+            let kind = if global.constant { "constant" } else { "global" };
+            writeln!(f, "@{} = {}{} {} {}", global.name, linkage, kind, global.ty, constant(&global.init))?;
+        }
+        writeln!(f)?;
+        for declaration in &self.declarations {
+            let mut params: Vec<String> = declaration.params.iter().map(|t| t.to_string()).collect();
+            if declaration.variadic {
+                params.push("...".to_string());
+            }
+            writeln!(f, "declare {} @{}({})", declaration.ret, declaration.name, params.join(", "))?;
+        }
+        let Some(debug_info) = &self.debug_info else {
+            for function in &self.functions {
+                writeln!(f)?;
+                writeln!(f, "{}", function)?;
+            }
+            return Ok(());
+        };
+        writeln!(f, "declare void @llvm.dbg.declare(metadata, metadata, metadata)")?;
+        let mut metadata = MetadataPrinter::new(debug_info);
+        for function in &self.functions {
+            let mut text = String::new();
+            write_function(&mut text, function, Some(&mut metadata))?;
+            writeln!(f)?;
+            writeln!(f, "{}", text)?;
+        }
+        writeln!(f)?;
+        write!(f, "{}", metadata.finish())
+    }
+}
@@ -0,0 +1,240 @@
+//! Builder used by the code generator to append instructions to a `Function`.
+
+use super::debug_info::{ScopeId, VariableId};
+use super::ir::{
+    BasicBlock, BinaryOp, BlockId, FloatPredicate, Function, Instruction, IntPredicate,
+    Location, Terminator, Type, Value, ValueId,
+};
+use crate::tokens::Span;
+
+/// Builds one function, appending instructions at the end of the current block.
+pub struct FunctionBuilder {
+    function: Function,
+    current: BlockId,
+    /// Number of `alloca`s at the start of the entry block.
+    allocas: usize,
+    /// Span of the expression being generated, attached to every new instruction.
+    location: Option<Span>,
+    /// Debug scope attached to every new instruction.
+    scope: Option<ScopeId>,
+}
+
+impl FunctionBuilder {
+    /// Starts a function with an empty `entry` block as the current block.
+    pub fn new(name: &str, ret: Type) -> Self {
+        let mut builder = FunctionBuilder {
+            function: Function {
+                name: name.to_string(),
+                ret,
+                params: Vec::new(),
+                blocks: Vec::new(),
+                layout: Vec::new(),
+                value_names: Vec::new(),
+                subprogram: None,
+            },
+            current: BlockId(0),
+            allocas: 0,
+            location: None,
+            scope: None,
+        };
+        let entry = builder.new_block("entry");
+        builder.function.blocks[entry.0].label = "entry".to_string();
+        builder.position_at_end(entry);
+        builder
+    }
+
+    /// Adds a parameter named after `hint` and returns its value.
+    pub fn add_param(&mut self, ty: Type, hint: &str) -> Value {
+        let id = self.new_value(Some(hint));
+        self.function.params.push((ty, id));
+        Value::Local(id)
+    }
+
+    /// Creates a new empty block; its label is made unique with the block id.
+    pub fn new_block(&mut self, hint: &str) -> BlockId {
+        let id = BlockId(self.function.blocks.len());
+        self.function.blocks.push(BasicBlock {
+            label: format!("{}.{}", hint, id.0),
+            instructions: Vec::new(),
+            locations: Vec::new(),
+            terminator: None,
+        });
+        id
+    }
+
+    /// Makes `block` the current block. Blocks are printed in the order they are
+    /// first positioned at.
+    pub fn position_at_end(&mut self, block: BlockId) {
+        if !self.function.layout.contains(&block) {
+            self.function.layout.push(block);
+        }
+        self.current = block;
+    }
+
+    pub fn current_block(&self) -> BlockId {
+        self.current
+    }
+
+    /// Whether the current block already ends with a terminator.
+    pub fn is_terminated(&self) -> bool {
+        self.function.blocks[self.current.0].terminator.is_some()
+    }
+
+    fn new_value(&mut self, hint: Option<&str>) -> ValueId {
+        self.function.value_names.push(hint.map(|h| h.to_string()));
+        ValueId(self.function.value_names.len() - 1)
+    }
+
+    /// Sets the span attached to the instructions built from now on and returns
+    /// the previous one.
+    pub fn set_location(&mut self, location: Option<Span>) -> Option<Span> {
+        std::mem::replace(&mut self.location, location)
+    }
+
+    /// Sets the debug scope of the instructions built from now on and returns the
+    /// previous one.
+    pub fn set_scope(&mut self, scope: Option<ScopeId>) -> Option<ScopeId> {
+        std::mem::replace(&mut self.scope, scope)
+    }
+
+    pub fn scope(&self) -> Option<ScopeId> {
+        self.scope
+    }
+
+    /// Attaches the debug scope of the function, which becomes the current scope.
+    pub fn set_subprogram(&mut self, subprogram: ScopeId) {
+        self.function.subprogram = Some(subprogram);
+        self.scope = Some(subprogram);
+    }
+
+    fn current_location(&self) -> Option<Location> {
+        self.location.map(|span| Location { span, scope: self.scope })
+    }
+
+    fn push(&mut self, instruction: Instruction) {
+        let location = self.current_location();
+        let block = &mut self.function.blocks[self.current.0];
+        block.instructions.push(instruction);
+        block.locations.push(location);
+    }
+
+    /// Allocates a stack slot in the entry block, so that slots created inside
+    /// loops are not allocated again on every iteration.
+    pub fn alloca(&mut self, ty: Type, hint: Option<&str>) -> Value {
+        let dest = self.new_value(hint);
+        let location = self.current_location();
+        let entry = &mut self.function.blocks[0];
+        entry.instructions.insert(self.allocas, Instruction::Alloca { dest, ty });
+        entry.locations.insert(self.allocas, location);
+        self.allocas += 1;
+        Value::Local(dest)
+    }
+
+    pub fn load(&mut self, ty: Type, ptr: Value) -> Value {
+        let dest = self.new_value(None);
+        self.push(Instruction::Load { dest, ty, ptr });
+        Value::Local(dest)
+    }
+
+    pub fn store(&mut self, ty: Type, value: Value, ptr: Value) {
+        self.push(Instruction::Store { ty, value, ptr });
+    }
+
+    pub fn binary(&mut self, op: BinaryOp, ty: Type, lhs: Value, rhs: Value) -> Value {
+        let dest = self.new_value(None);
+        self.push(Instruction::Binary { dest, op, ty, lhs, rhs });
+        Value::Local(dest)
+    }
+
+    pub fn fcmp(&mut self, predicate: FloatPredicate, lhs: Value, rhs: Value) -> Value {
+        let dest = self.new_value(None);
+        self.push(Instruction::FCmp { dest, predicate, lhs, rhs });
+        Value::Local(dest)
+    }
+
+    pub fn icmp(&mut self, predicate: IntPredicate, ty: Type, lhs: Value, rhs: Value) -> Value {
+        let dest = self.new_value(None);
+        self.push(Instruction::ICmp { dest, predicate, ty, lhs, rhs });
+        Value::Local(dest)
+    }
+
+    pub fn select(&mut self, condition: Value, ty: Type, then_value: Value, else_value: Value) -> Value {
+        let dest = self.new_value(None);
+        self.push(Instruction::Select { dest, condition, ty, then_value, else_value });
+        Value::Local(dest)
+    }
+
+    pub fn gep(&mut self, ty: Type, ptr: Value, indices: Vec<(Type, Value)>) -> Value {
+        let dest = self.new_value(None);
+        self.push(Instruction::GetElementPtr { dest, ty, ptr, indices });
+        Value::Local(dest)
+    }
+
+    /// Address of field `field` of the struct of type `ty` pointed to by `ptr`.
+    pub fn struct_gep(&mut self, ty: Type, ptr: Value, field: u32) -> Value {
+        self.gep(ty, ptr, vec![(Type::I32, Value::Int(0)), (Type::I32, Value::Int(field as i64))])
+    }
+
+    /// Size in bytes of `ty`, computed with the `getelementptr null, 1` idiom.
+    pub fn size_of(&mut self, ty: Type) -> Value {
+        let end = self.gep(ty, Value::Null, vec![(Type::I32, Value::Int(1))]);
+        self.ptr_to_int(end, Type::I64)
+    }
+
+    pub fn ptr_to_int(&mut self, value: Value, ty: Type) -> Value {
+        let dest = self.new_value(None);
+        self.push(Instruction::PtrToInt { dest, value, ty });
+        Value::Local(dest)
+    }
+
+    /// Calls a function returning a value.
+    pub fn call(&mut self, ret: Type, callee: Value, args: Vec<(Type, Value)>) -> Value {
+        let dest = self.new_value(None);
+        self.push(Instruction::Call { dest: Some(dest), ret, callee, args, variadic: None });
+        Value::Local(dest)
+    }
+
+    /// Calls a function returning `void`.
+    pub fn call_void(&mut self, callee: Value, args: Vec<(Type, Value)>) {
+        self.push(Instruction::Call { dest: None, ret: Type::Void, callee, args, variadic: None });
+    }
+
+    /// Calls a variadic function whose fixed parameters are `fixed`.
+    pub fn call_variadic(&mut self, ret: Type, callee: Value, fixed: Vec<Type>, args: Vec<(Type, Value)>) -> Value {
+        let dest = self.new_value(None);
+        self.push(Instruction::Call { dest: Some(dest), ret, callee, args, variadic: Some(fixed) });
+        Value::Local(dest)
+    }
+
+    /// Declares that the debug variable `variable` lives at `address`.
+    pub fn dbg_declare(&mut self, address: Value, variable: VariableId) {
+        self.push(Instruction::DbgDeclare { address, variable });
+    }
+
+    fn terminate(&mut self, terminator: Terminator) {
+        let block = &mut self.function.blocks[self.current.0];
+        if block.terminator.is_none() {
+            block.terminator = Some(terminator);
+        }
+    }
+
+    pub fn br(&mut self, target: BlockId) {
+        self.terminate(Terminator::Br(target));
+    }
+
+    pub fn cond_br(&mut self, condition: Value, then_block: BlockId, else_block: BlockId) {
+        self.terminate(Terminator::CondBr { condition, then_block, else_block });
+    }
+
+    pub fn ret(&mut self, value: Option<(Type, Value)>) {
+        self.terminate(Terminator::Ret(value));
+    }
+
+    pub fn unreachable(&mut self) {
+        self.terminate(Terminator::Unreachable);
+    }
+
+    pub fn finish(self) -> Function {
+        self.function
+    }
+}
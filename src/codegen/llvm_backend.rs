@@ -0,0 +1,642 @@
+//! Native backend built on the LLVM C API, enabled with the `llvm` cargo feature.
+//!
+//! The `ir::Module` produced by the code generator is rebuilt in memory through
+//! `inkwell`, checked with the LLVM verifier and compiled straight to an object file.
+//! The generated IR only uses opaque `ptr`s, which LLVM 14 supports behind the
+//! `-opaque-pointers` option; loads, `getelementptr`s and calls are therefore built
+//! with the typed `*2` entry points of the C API instead of the inkwell wrappers,
+//! which infer the pointee type. Debug info, when present, is rebuilt with the
+//! DIBuilder so that the object file carries the same DWARF as the textual module.
+
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::path::Path;
+use std::sync::Once;
+
+use inkwell::basic_block::BasicBlock;
+use inkwell::builder::{Builder, BuilderError};
+use inkwell::context::Context;
+use inkwell::debug_info::{
+    AsDIScope, DIFile, DIFlags, DIFlagsConstants, DILocalVariable, DILocation, DIScope, DISubprogram, DIType,
+    DWARFEmissionKind, DWARFSourceLanguage, DebugInfoBuilder,
+};
+use inkwell::llvm_sys::core::{LLVMBuildCall2, LLVMBuildGEP2, LLVMBuildLoad2, LLVMConstArray};
+use inkwell::llvm_sys::support::LLVMParseCommandLineOptions;
+use inkwell::module::{FlagBehavior, Linkage, Module};
+use inkwell::targets::{
+    CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetData, TargetTriple,
+};
+use inkwell::types::{AsTypeRef, BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FunctionType};
+use inkwell::values::{AnyValue, AsValueRef, BasicValueEnum, InstructionValue};
+use inkwell::{AddressSpace, FloatPredicate, IntPredicate, OptimizationLevel};
+
+use super::debug_info::{DebugInfo, DebugType, Scope, ScopeId};
+use super::ir::{self, BinaryOp, Constant, Instruction, Location, Terminator, Type, Value, ValueId};
+use crate::semantic_analyzer::semantic_errors::get_line_context;
+use crate::tokens::Span;
+
+/// Compiles `module` into the object file `path`.
+///
+/// When the verifier rejects the module, the error lists its messages and points
+/// every offending instruction back to the HULK expression it was generated for.
+pub fn emit_object(module: &ir::Module, source: &str, missplacement: i32, path: &Path) -> Result<(), String> {
+    enable_opaque_pointers();
+    Target::initialize_all(&InitializationConfig::default());
+
+    let triple = TargetTriple::create(&module.target_triple);
+    let target = Target::from_triple(&triple).map_err(|e| e.to_string())?;
+    let machine = target
+        .create_target_machine(
+            &triple,
+            "",
+            "",
+            OptimizationLevel::None,
+            RelocMode::PIC,
+            CodeModel::Default,
+        )
+        .ok_or_else(|| format!("Unsupported target: {}", triple))?;
+
+    let context = Context::create();
+    let mut lowering = Lowering {
+        context: &context,
+        module: context.create_module(&module.name),
+        builder: context.create_builder(),
+        target_data: machine.get_target_data(),
+        ir: module,
+        debug: None,
+        located: Vec::new(),
+    };
+    lowering.module.set_triple(&triple);
+    lowering.module.set_data_layout(&lowering.target_data.get_data_layout());
+    lowering.lower().map_err(|e| e.to_string())?;
+    if let Some(debug) = &lowering.debug {
+        debug.builder.finalize();
+    }
+
+    if let Err(message) = lowering.module.verify() {
+        return Err(lowering.locate(&message.to_string(), source, missplacement));
+    }
+    machine
+        .write_to_file(&lowering.module, FileType::Object, path)
+        .map_err(|e| e.to_string())
+}
+
+/// Makes every pointer type created from now on the opaque `ptr`.
+fn enable_opaque_pointers() {
+    static ONCE: Once = Once::new();
+    ONCE.call_once(|| {
+        let args = [c"hulk".as_ptr(), c"-opaque-pointers".as_ptr()];
+        unsafe { LLVMParseCommandLineOptions(args.len() as i32, args.as_ptr(), std::ptr::null()) };
+    });
+}
+
+fn name(hint: &Option<String>) -> CString {
+    CString::new(hint.as_deref().unwrap_or("")).expect("Value names never contain NUL bytes")
+}
+
+struct Lowering<'a, 'ctx> {
+    context: &'ctx Context,
+    module: Module<'ctx>,
+    builder: Builder<'ctx>,
+    target_data: TargetData,
+    ir: &'a ir::Module,
+    debug: Option<DebugLowering<'ctx>>,
+    /// Instructions generated for a known HULK expression.
+    located: Vec<(InstructionValue<'ctx>, Span)>,
+}
+
+impl<'ctx> Lowering<'_, 'ctx> {
+    fn lower(&mut self) -> Result<(), BuilderError> {
+        // Struct types are created opaque first, as their fields may refer to each other
+        for (name, ty) in &self.ir.types {
+            if let Type::Struct(_) = ty {
+                self.context.opaque_struct_type(name);
+            }
+        }
+        for (name, ty) in &self.ir.types {
+            if let Type::Struct(fields) = ty {
+                let fields: Vec<_> = fields.iter().map(|field| self.basic_type(field)).collect();
+                self.named_struct(name).set_body(&fields, false);
+            }
+        }
+        for declaration in &self.ir.declarations {
+            let fn_type = self.fn_type(&declaration.ret, &declaration.params, declaration.variadic);
+            self.module.add_function(&declaration.name, fn_type, Some(Linkage::External));
+        }
+        for function in &self.ir.functions {
+            let params: Vec<Type> = function.params.iter().map(|(ty, _)| ty.clone()).collect();
+            let fn_type = self.fn_type(&function.ret, &params, false);
+            self.module.add_function(&function.name, fn_type, None);
+        }
+        if let Some(info) = &self.ir.debug_info {
+            self.debug = Some(DebugLowering::new(self.context, &self.module, info));
+        }
+        // Globals are declared before any initializer, which may point to another global
+        for global in &self.ir.globals {
+            self.module.add_global(self.basic_type(&global.ty), None, &global.name);
+        }
+        for global in &self.ir.globals {
+            let value = self.module.get_global(&global.name).expect("Global declared above");
+            value.set_initializer(&self.constant(&global.ty, &global.init));
+            value.set_constant(global.constant);
+            if global.private {
+                value.set_linkage(Linkage::Private);
+                value.set_unnamed_addr(true);
+            }
+        }
+        for function in &self.ir.functions {
+            self.lower_function(function)?;
+        }
+        Ok(())
+    }
+
+    fn named_struct(&self, name: &str) -> inkwell::types::StructType<'ctx> {
+        self.context
+            .get_struct_type(name)
+            .unwrap_or_else(|| panic!("Struct type not declared: {}", name))
+    }
+
+    fn ptr_type(&self) -> inkwell::types::PointerType<'ctx> {
+        self.context.i8_type().ptr_type(AddressSpace::default())
+    }
+
+    fn basic_type(&self, ty: &Type) -> BasicTypeEnum<'ctx> {
+        match ty {
+            Type::Void => panic!("`void` is not a first-class type"),
+            Type::I1 => self.context.bool_type().into(),
+            Type::I8 => self.context.i8_type().into(),
+            Type::I32 => self.context.i32_type().into(),
+            Type::I64 => self.context.i64_type().into(),
+            Type::Double => self.context.f64_type().into(),
+            Type::Ptr => self.ptr_type().into(),
+            Type::Named(name) => match self.ir.types.iter().find(|(n, _)| n == name) {
+                Some((_, Type::Struct(_))) => self.named_struct(name).into(),
+                // Named non-struct types are plain aliases
+                Some((_, ty)) => self.basic_type(ty),
+                None => panic!("Type not declared: {}", name),
+            },
+            Type::Array(len, elem) => self.basic_type(elem).array_type(*len as u32).into(),
+            Type::Struct(fields) => {
+                let fields: Vec<_> = fields.iter().map(|field| self.basic_type(field)).collect();
+                self.context.struct_type(&fields, false).into()
+            }
+        }
+    }
+
+    fn fn_type(&self, ret: &Type, params: &[Type], variadic: bool) -> FunctionType<'ctx> {
+        let params: Vec<BasicMetadataTypeEnum> = params.iter().map(|ty| self.basic_type(ty).into()).collect();
+        match ret {
+            Type::Void => self.context.void_type().fn_type(&params, variadic),
+            ty => self.basic_type(ty).fn_type(&params, variadic),
+        }
+    }
+
+    fn constant(&self, ty: &Type, constant: &Constant) -> BasicValueEnum<'ctx> {
+        match constant {
+            Constant::Value(value) => self.operand(ty, value, &HashMap::new()),
+            Constant::Bytes(bytes) => self.context.const_string(bytes, false).into(),
+            Constant::Array(elem, items) => {
+                let mut items: Vec<_> = items.iter().map(|item| self.constant(elem, item).as_value_ref()).collect();
+                let elem = self.basic_type(elem);
+                unsafe {
+                    let array = LLVMConstArray(elem.as_type_ref(), items.as_mut_ptr(), items.len() as u32);
+                    BasicValueEnum::new(array)
+                }
+            }
+            Constant::Struct(fields) => {
+                let fields: Vec<_> = fields.iter().map(|(ty, field)| self.constant(ty, field)).collect();
+                self.basic_type(ty).into_struct_type().const_named_struct(&fields).into()
+            }
+            Constant::FieldOffset { ty, field } => {
+                let offset = self
+                    .target_data
+                    .offset_of_element(&self.basic_type(ty).into_struct_type(), *field)
+                    .expect("Field index out of bounds");
+                self.context.i64_type().const_int(offset, false).into()
+            }
+        }
+    }
+
+    fn operand(
+        &self,
+        ty: &Type,
+        value: &Value,
+        locals: &HashMap<ValueId, BasicValueEnum<'ctx>>,
+    ) -> BasicValueEnum<'ctx> {
+        match value {
+            Value::Local(id) => *locals.get(id).expect("Value used before its definition"),
+            Value::Global(name) => match self.module.get_global(name) {
+                Some(global) => global.as_pointer_value().into(),
+                None => self
+                    .module
+                    .get_function(name)
+                    .unwrap_or_else(|| panic!("Global not declared: {}", name))
+                    .as_global_value()
+                    .as_pointer_value()
+                    .into(),
+            },
+            Value::Double(value) => self.context.f64_type().const_float(*value).into(),
+            Value::Int(value) => self.basic_type(ty).into_int_type().const_int(*value as u64, true).into(),
+            Value::Bool(value) => self.context.bool_type().const_int(*value as u64, false).into(),
+            Value::Null => self.ptr_type().const_null().into(),
+        }
+    }
+
+    fn lower_function(&mut self, function: &ir::Function) -> Result<(), BuilderError> {
+        let value = self.module.get_function(&function.name).expect("Function declared above");
+        let mut locals = HashMap::new();
+        for (index, (_, id)) in function.params.iter().enumerate() {
+            let param = value.get_nth_param(index as u32).expect("Parameter declared above");
+            if let Some(hint) = &function.value_names[id.0] {
+                param.set_name(hint);
+            }
+            locals.insert(*id, param);
+        }
+        if let (Some(debug), Some(scope)) = (&self.debug, function.subprogram) {
+            value.set_subprogram(debug.subprograms[&scope]);
+        }
+        let blocks: HashMap<_, _> = function
+            .layout
+            .iter()
+            .map(|id| (*id, self.context.append_basic_block(value, &function.blocks[id.0].label)))
+            .collect();
+
+        for id in &function.layout {
+            let block = &function.blocks[id.0];
+            self.builder.position_at_end(blocks[id]);
+            for (instruction, location) in block.instructions.iter().zip(&block.locations) {
+                let last = blocks[id].get_last_instruction();
+                match location.as_ref().and_then(|location| self.debug_location(location)) {
+                    Some(location) => self.builder.set_current_debug_location(location),
+                    None => self.builder.unset_current_debug_location(),
+                }
+                let result = self.lower_instruction(function, instruction, &locals)?;
+                if let (Some(dest), Some(result)) = (instruction.dest(), result) {
+                    locals.insert(dest, result);
+                }
+                // Instructions over constants are folded by the builder and emit nothing
+                let built = blocks[id].get_last_instruction().filter(|inst| Some(*inst) != last);
+                if let (Some(inst), Some(location)) = (built, location) {
+                    self.located.push((inst, location.span));
+                }
+            }
+            self.builder.unset_current_debug_location();
+            self.lower_terminator(block.terminator.as_ref(), &blocks, &locals)?;
+        }
+        Ok(())
+    }
+
+    /// The `DILocation` of an instruction, if it has a line and a scope.
+    fn debug_location(&self, location: &Location) -> Option<DILocation<'ctx>> {
+        let debug = self.debug.as_ref()?;
+        let info = self.ir.debug_info.as_ref()?;
+        let scope = location.scope?;
+        let (line, column) = info.line_col(location.span.start)?;
+        Some(debug.builder.create_debug_location(self.context, line as u32, column as u32, debug.scopes[scope.0], None))
+    }
+
+    fn lower_instruction(
+        &self,
+        function: &ir::Function,
+        instruction: &Instruction,
+        locals: &HashMap<ValueId, BasicValueEnum<'ctx>>,
+    ) -> Result<Option<BasicValueEnum<'ctx>>, BuilderError> {
+        let hint = |dest: &ValueId| function.value_names[dest.0].clone();
+        let b = &self.builder;
+        let value: BasicValueEnum = match instruction {
+            Instruction::Alloca { dest, ty } => {
+                let name = hint(dest).unwrap_or_default();
+                b.build_alloca(self.basic_type(ty), &name)?.into()
+            }
+            Instruction::Load { dest, ty, ptr } => {
+                let ptr = self.operand(&Type::Ptr, ptr, locals);
+                let name = name(&hint(dest));
+                unsafe {
+                    let load = LLVMBuildLoad2(
+                        b.as_mut_ptr(),
+                        self.basic_type(ty).as_type_ref(),
+                        ptr.as_value_ref(),
+                        name.as_ptr(),
+                    );
+                    BasicValueEnum::new(load)
+                }
+            }
+            Instruction::Store { ty, value, ptr } => {
+                let value = self.operand(ty, value, locals);
+                let ptr = self.operand(&Type::Ptr, ptr, locals).into_pointer_value();
+                b.build_store(ptr, value)?;
+                return Ok(None);
+            }
+            Instruction::Binary { dest, op, ty, lhs, rhs } => {
+                let name = hint(dest).unwrap_or_default();
+                let lhs = self.operand(ty, lhs, locals);
+                let rhs = self.operand(ty, rhs, locals);
+                match op {
+                    BinaryOp::FAdd => b.build_float_add(lhs.into_float_value(), rhs.into_float_value(), &name)?.into(),
+                    BinaryOp::FSub => b.build_float_sub(lhs.into_float_value(), rhs.into_float_value(), &name)?.into(),
+                    BinaryOp::FMul => b.build_float_mul(lhs.into_float_value(), rhs.into_float_value(), &name)?.into(),
+                    BinaryOp::FDiv => b.build_float_div(lhs.into_float_value(), rhs.into_float_value(), &name)?.into(),
+                    BinaryOp::Add => b.build_int_add(lhs.into_int_value(), rhs.into_int_value(), &name)?.into(),
+                    BinaryOp::Sub => b.build_int_sub(lhs.into_int_value(), rhs.into_int_value(), &name)?.into(),
+                    BinaryOp::And => b.build_and(lhs.into_int_value(), rhs.into_int_value(), &name)?.into(),
+                    BinaryOp::Or => b.build_or(lhs.into_int_value(), rhs.into_int_value(), &name)?.into(),
+                    BinaryOp::Xor => b.build_xor(lhs.into_int_value(), rhs.into_int_value(), &name)?.into(),
+                }
+            }
+            Instruction::FCmp { dest, predicate, lhs, rhs } => {
+                let name = hint(dest).unwrap_or_default();
+                let lhs = self.operand(&Type::Double, lhs, locals).into_float_value();
+                let rhs = self.operand(&Type::Double, rhs, locals).into_float_value();
+                let predicate = match predicate {
+                    ir::FloatPredicate::Oeq => FloatPredicate::OEQ,
+                    ir::FloatPredicate::One => FloatPredicate::ONE,
+                    ir::FloatPredicate::Ogt => FloatPredicate::OGT,
+                    ir::FloatPredicate::Oge => FloatPredicate::OGE,
+                    ir::FloatPredicate::Olt => FloatPredicate::OLT,
+                    ir::FloatPredicate::Ole => FloatPredicate::OLE,
+                };
+                b.build_float_compare(predicate, lhs, rhs, &name)?.into()
+            }
+            Instruction::ICmp { dest, predicate, ty, lhs, rhs } => {
+                let name = hint(dest).unwrap_or_default();
+                let lhs = self.operand(ty, lhs, locals);
+                let rhs = self.operand(ty, rhs, locals);
+                let predicate = match predicate {
+                    ir::IntPredicate::Eq => IntPredicate::EQ,
+                    ir::IntPredicate::Ne => IntPredicate::NE,
+                    ir::IntPredicate::Slt => IntPredicate::SLT,
+                    ir::IntPredicate::Sle => IntPredicate::SLE,
+                    ir::IntPredicate::Sgt => IntPredicate::SGT,
+                    ir::IntPredicate::Sge => IntPredicate::SGE,
+                };
+                match (lhs, rhs) {
+                    (BasicValueEnum::PointerValue(lhs), BasicValueEnum::PointerValue(rhs)) => {
+                        b.build_int_compare(predicate, lhs, rhs, &name)?.into()
+                    }
+                    _ => b.build_int_compare(predicate, lhs.into_int_value(), rhs.into_int_value(), &name)?.into(),
+                }
+            }
+            Instruction::Select { dest, condition, ty, then_value, else_value } => {
+                let name = hint(dest).unwrap_or_default();
+                let condition = self.operand(&Type::I1, condition, locals).into_int_value();
+                let then_value = self.operand(ty, then_value, locals);
+                let else_value = self.operand(ty, else_value, locals);
+                b.build_select(condition, then_value, else_value, &name)?
+            }
+            Instruction::GetElementPtr { dest, ty, ptr, indices } => {
+                let ptr = self.operand(&Type::Ptr, ptr, locals);
+                let mut indices: Vec<_> = indices
+                    .iter()
+                    .map(|(ty, index)| self.operand(ty, index, locals).as_value_ref())
+                    .collect();
+                let name = name(&hint(dest));
+                unsafe {
+                    let gep = LLVMBuildGEP2(
+                        b.as_mut_ptr(),
+                        self.basic_type(ty).as_type_ref(),
+                        ptr.as_value_ref(),
+                        indices.as_mut_ptr(),
+                        indices.len() as u32,
+                        name.as_ptr(),
+                    );
+                    BasicValueEnum::new(gep)
+                }
+            }
+            Instruction::PtrToInt { dest, value, ty } => {
+                let name = hint(dest).unwrap_or_default();
+                let value = self.operand(&Type::Ptr, value, locals).into_pointer_value();
+                b.build_ptr_to_int(value, self.basic_type(ty).into_int_type(), &name)?.into()
+            }
+            Instruction::Call { dest, ret, callee, args, variadic } => {
+                let params = match variadic {
+                    Some(fixed) => fixed.clone(),
+                    None => args.iter().map(|(ty, _)| ty.clone()).collect(),
+                };
+                let fn_type = self.fn_type(ret, &params, variadic.is_some());
+                let callee = self.operand(&Type::Ptr, callee, locals);
+                let mut args: Vec<_> = args
+                    .iter()
+                    .map(|(ty, arg)| self.operand(ty, arg, locals).as_value_ref())
+                    .collect();
+                // `void` calls cannot be named
+                let name = name(&dest.and_then(|dest| hint(&dest)));
+                let call = unsafe {
+                    LLVMBuildCall2(
+                        b.as_mut_ptr(),
+                        fn_type.as_type_ref(),
+                        callee.as_value_ref(),
+                        args.as_mut_ptr(),
+                        args.len() as u32,
+                        name.as_ptr(),
+                    )
+                };
+                if dest.is_none() {
+                    return Ok(None);
+                }
+                unsafe { BasicValueEnum::new(call) }
+            }
+            Instruction::DbgDeclare { address, variable } => {
+                let debug = self.debug.as_ref().expect("Debug variable in a module without debug info");
+                let info = self.ir.debug_info.as_ref().expect("Debug variable in a module without debug info");
+                let declared = &info.variables[variable.0];
+                let location = b.get_current_debug_location().unwrap_or_else(|| {
+                    let scope = debug.scopes[declared.scope.0];
+                    debug.builder.create_debug_location(self.context, declared.line as u32, 0, scope, None)
+                });
+                let address = self.operand(&Type::Ptr, address, locals).into_pointer_value();
+                let block = b.get_insert_block().expect("The builder is positioned in a block");
+                debug.builder.insert_declare_at_end(address, Some(debug.variables[variable.0]), None, location, block);
+                return Ok(None);
+            }
+        };
+        Ok(Some(value))
+    }
+
+    fn lower_terminator(
+        &self,
+        terminator: Option<&Terminator>,
+        blocks: &HashMap<ir::BlockId, BasicBlock<'ctx>>,
+        locals: &HashMap<ValueId, BasicValueEnum<'ctx>>,
+    ) -> Result<(), BuilderError> {
+        let b = &self.builder;
+        match terminator {
+            Some(Terminator::Br(target)) => {
+                b.build_unconditional_branch(blocks[target])?;
+            }
+            Some(Terminator::CondBr { condition, then_block, else_block }) => {
+                let condition = self.operand(&Type::I1, condition, locals).into_int_value();
+                b.build_conditional_branch(condition, blocks[then_block], blocks[else_block])?;
+            }
+            Some(Terminator::Ret(Some((ty, value)))) => {
+                b.build_return(Some(&self.operand(ty, value, locals)))?;
+            }
+            Some(Terminator::Ret(None)) => {
+                b.build_return(None)?;
+            }
+            Some(Terminator::Unreachable) | None => {
+                b.build_unreachable()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends to every verifier message quoting a located instruction the HULK
+    /// line the instruction was generated for.
+    fn locate(&self, message: &str, source: &str, missplacement: i32) -> String {
+        let located: Vec<(String, Span)> = self
+            .located
+            .iter()
+            .map(|(inst, span)| (inst.print_to_string().to_string().trim().to_string(), *span))
+            .collect();
+        let mut report = String::from("LLVM verification failed:");
+        for line in message.lines() {
+            report.push_str("\n  ");
+            report.push_str(line);
+            if let Some((_, span)) = located.iter().find(|(text, _)| text == line.trim()) {
+                let (line, col, line_str, _) = get_line_context(source, span.start, missplacement);
+                report.push_str(&format!("\n    generated for (line {line}, column {col}): {}", line_str.trim()));
+            }
+        }
+        report
+    }
+}
+
+/// The scopes, variables and types of a module's `DebugInfo`, created in the
+/// same order as the textual printer numbers them.
+struct DebugLowering<'ctx> {
+    builder: DebugInfoBuilder<'ctx>,
+    file: DIFile<'ctx>,
+    pointer_width: u32,
+    scopes: Vec<DIScope<'ctx>>,
+    subprograms: HashMap<ScopeId, DISubprogram<'ctx>>,
+    variables: Vec<DILocalVariable<'ctx>>,
+    types: HashMap<DebugType, DIType<'ctx>>,
+}
+
+impl<'ctx> DebugLowering<'ctx> {
+    fn new(context: &'ctx Context, module: &Module<'ctx>, info: &DebugInfo) -> Self {
+        let (builder, unit) = module.create_debug_info_builder(
+            true,
+            DWARFSourceLanguage::C,
+            &info.file,
+            &info.directory,
+            "hulk_compiler",
+            false,
+            "",
+            0,
+            "",
+            DWARFEmissionKind::Full,
+            0,
+            false,
+            false,
+            "",
+            "",
+        );
+        let dwarf_version = context.i32_type().const_int(4, false);
+        module.add_basic_value_flag("Dwarf Version", FlagBehavior::Warning, dwarf_version);
+        let debug_info_version = context.i32_type().const_int(3, false);
+        module.add_basic_value_flag("Debug Info Version", FlagBehavior::Warning, debug_info_version);
+
+        let mut debug = DebugLowering {
+            builder,
+            file: unit.get_file(),
+            pointer_width: info.pointer_width,
+            scopes: Vec::new(),
+            subprograms: HashMap::new(),
+            variables: Vec::new(),
+            types: HashMap::new(),
+        };
+        for (index, scope) in info.scopes.iter().enumerate() {
+            let scope = match scope {
+                Scope::Subprogram { name, line, ret, params } => {
+                    let ret = ret.as_ref().map(|ret| debug.debug_type(ret));
+                    let params: Vec<_> = params.iter().map(|param| debug.debug_type(param)).collect();
+                    let subroutine = debug.builder.create_subroutine_type(debug.file, ret, &params, DIFlags::ZERO);
+                    let subprogram = debug.builder.create_function(
+                        debug.file.as_debug_info_scope(),
+                        name,
+                        None,
+                        debug.file,
+                        *line as u32,
+                        subroutine,
+                        false,
+                        true,
+                        *line as u32,
+                        DIFlags::ZERO,
+                        false,
+                    );
+                    debug.subprograms.insert(ScopeId(index), subprogram);
+                    subprogram.as_debug_info_scope()
+                }
+                Scope::LexicalBlock { parent, line, column } => debug
+                    .builder
+                    .create_lexical_block(debug.scopes[parent.0], debug.file, *line as u32, *column as u32)
+                    .as_debug_info_scope(),
+            };
+            debug.scopes.push(scope);
+        }
+        for variable in &info.variables {
+            let ty = debug.debug_type(&variable.ty);
+            let scope = debug.scopes[variable.scope.0];
+            let line = variable.line as u32;
+            let declared = match variable.arg {
+                Some(arg) => debug
+                    .builder
+                    .create_parameter_variable(scope, &variable.name, arg, debug.file, line, ty, true, DIFlags::ZERO),
+                None => debug
+                    .builder
+                    .create_auto_variable(scope, &variable.name, debug.file, line, ty, true, DIFlags::ZERO, 0),
+            };
+            debug.variables.push(declared);
+        }
+        debug
+    }
+
+    fn debug_type(&mut self, ty: &DebugType) -> DIType<'ctx> {
+        if let Some(di_type) = self.types.get(ty) {
+            return *di_type;
+        }
+        // Encodings from the DWARF standard
+        const DW_ATE_BOOLEAN: u32 = 0x02;
+        const DW_ATE_FLOAT: u32 = 0x04;
+        const DW_ATE_SIGNED_CHAR: u32 = 0x06;
+        let basic = |name: &str, size: u64, encoding: u32| {
+            self.builder
+                .create_basic_type(name, size, encoding, DIFlags::ZERO)
+                .expect("Basic types are named")
+                .as_type()
+        };
+        let width = self.pointer_width as u64;
+        let di_type = match ty {
+            DebugType::Number => basic("Number", 64, DW_ATE_FLOAT),
+            DebugType::Boolean => basic("Boolean", 8, DW_ATE_BOOLEAN),
+            DebugType::String => {
+                let char_type = basic("char", 8, DW_ATE_SIGNED_CHAR);
+                self.builder
+                    .create_pointer_type("String", char_type, width, 0, AddressSpace::default())
+                    .as_type()
+            }
+            DebugType::Object(name) => {
+                let object = self.builder.create_struct_type(
+                    self.file.as_debug_info_scope(),
+                    name,
+                    self.file,
+                    0,
+                    0,
+                    0,
+                    DIFlags::FWD_DECL,
+                    None,
+                    &[],
+                    0,
+                    None,
+                    "",
+                );
+                self.builder
+                    .create_pointer_type("", object.as_type(), width, 0, AddressSpace::default())
+                    .as_type()
+            }
+        };
+        self.types.insert(ty.clone(), di_type);
+        di_type
+    }
+}
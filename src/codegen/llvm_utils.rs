@@ -1,75 +1,76 @@
 use super::context::CodeGenContext;
+use super::ir::{Constant, Global, Module, Type, Value};
+use super::target::Target;
 
-/// Emit the global string constants and the printf declaration.
-pub fn declare_printf(output: &mut Vec<String>,  context: &mut CodeGenContext) {
-    output.push("@PI = constant double 0x400921FB54442D18".into()); // π
-    output.push("@E = constant double 0x4005BF0A8B145769".into()); // e
-    context.add_global_constant("PI");
-    context.add_global_constant("E");
-    output.push(r#"@.str.f = private unnamed_addr constant [4 x i8] c"%f\0A\00", align 1"#.into());
-    output.push(r#"@.str.d = private unnamed_addr constant [4 x i8] c"%d\0A\00", align 1"#.into());
-    output.push(r#"@.str.s = private unnamed_addr constant [4 x i8] c"%s\0A\00", align 1"#.into());
-    output.push(r#"@.true_str = private  constant [6 x i8] c"true\0A\00", align 1"#.into());
-    output.push(r#"@.false_str = private constant [7 x i8] c"false\0A\00", align 1"#.into());
-    output.push(r#"@.newline = private unnamed_addr constant [2 x i8] c"\0A\00", align 1"#.into());
-    output.push("declare i32 @printf(ptr, ...)".into());
-    output.push("declare i32 @strlen( ptr )".into());
-    output.push("declare ptr @strcpy(ptr,ptr)".into());
-    output.push("declare ptr @strcat(ptr,ptr)".into());
-    output.push("declare i32 @strcmp(ptr ,ptr)".into());
-    output.push("declare i8* @malloc(i64)".into());
+/// Adds a private constant holding `text` followed by a NUL byte.
+fn add_c_string(module: &mut Module, name: &str, text: &str) {
+    let mut bytes = text.as_bytes().to_vec();
+    bytes.push(0);
+    module.add_global(Global {
+        name: name.to_string(),
+        ty: Type::Array(bytes.len(), Box::new(Type::I8)),
+        init: Constant::Bytes(bytes),
+        constant: true,
+        private: true,
+    });
 }
 
-/// Emit a call to printf with the given format and value.
-pub fn generate_printf(context: &mut CodeGenContext, value: &str, fmt: &str) {
-    let (global_name, arg_type) = match fmt {
-        "%f" => ("@.str.f", "double"),
-        "%d" => ("@.str.d", "i32"),
-        "%s" => ("@.str.s", "i8*"),
-        _ => panic!("Unsupported format string: {}", fmt),
-    };
-
-    context.add_line(format!(
-        "call i32 (i8*, ...) @printf(i8* getelementptr inbounds ([4 x i8], [4 x i8]* {}, i64 0, i64 0), {} {})",
-        global_name,
-        arg_type,
-        value
-    ));
-}
-
-/// Emit the module header—ModuleID, data layout, and target triple—dynamically
-/// obtained from environment variables set by build.rs.
-pub fn generate_header(output: &mut Vec<String>) {
-    output.push("; ModuleID = 'hulk'".into());
-    output.push("target datalayout = \"e-m:e-p270:32:32-p271:32:32-p272:64:64-i64:64-f80:128-n8:16:32:64-S128\"".into());
-    output.push("target triple = \"x86_64-pc-linux-gnu\"".into());
+/// Emit the global constants, the print formats and the C library declarations.
+pub fn declare_printf(context: &mut CodeGenContext) {
+    for (name, value) in [("PI", std::f64::consts::PI), ("E", std::f64::consts::E)] {
+        context.module.add_global(Global {
+            name: name.to_string(),
+            ty: Type::Double,
+            init: Constant::Value(Value::Double(value)),
+            constant: true,
+            private: false,
+        });
+        context.add_global_constant(name);
+    }
+    let size_type = context.target.size_type();
+    let module = &mut context.module;
+    add_c_string(module, ".str.d", "%d\n");
+    add_c_string(module, ".str.s", "%s\n");
+    add_c_string(module, ".true_str", "true\n");
+    add_c_string(module, ".false_str", "false\n");
+    module.declare("printf", Type::I32, vec![Type::Ptr], true);
+    module.declare("strlen", size_type, vec![Type::Ptr], false);
+    module.declare("strcmp", Type::I32, vec![Type::Ptr, Type::Ptr], false);
 }
 
-/// Emit the `main` wrapper around the generated body.
-pub fn generate_main_wrapper(output: &mut Vec<String>, body: &[String]) {
-    output.push("define i32 @main() {".into());
-    output.push("entry:".into());
-    for line in body {
-        output.push(format!("  {}", line));
-    }
-    output.push("  ret i32 0".into());
-    output.push("}".into());
+/// Emit the module header—data layout and target triple of `target`.
+pub fn generate_header(module: &mut Module, target: &Target) {
+    module.data_layout = target.data_layout.clone().unwrap_or_default();
+    module.target_triple = target.triple.clone();
 }
 
-/// Emit declarations for runtime helper functions (fmod, pow, concat).
-pub fn generate_runtime_declarations(output: &mut Vec<String>) {
-    output.push("".into());
-    output.push("; Runtime function declarations".into());
-    output.push("declare double @fmod(double, double)".into());
-    output.push("declare double @pow(double, double)".into());
-    output.push("declare i8* @concat(i8*, i8*)".into());
+/// Emit declarations for runtime helper functions (fmod, pow, concat,
+/// hulk_num_to_str), console input, the
+/// garbage collector interface and the runtime error reporting.
+pub fn generate_runtime_declarations(module: &mut Module) {
+    module.declare("fmod", Type::Double, vec![Type::Double, Type::Double], false);
+    module.declare("pow", Type::Double, vec![Type::Double, Type::Double], false);
+    module.declare("concat", Type::Ptr, vec![Type::Ptr, Type::Ptr], false);
+    module.declare("hulk_num_to_str", Type::Ptr, vec![Type::Double], false);
+    module.declare("hulk_read_line", Type::Ptr, vec![], false);
+    module.declare("hulk_read_number", Type::Double, vec![], false);
+    module.declare("hulk_eof", Type::I32, vec![], false);
+    module.declare("hulk_gc_alloc", Type::Ptr, vec![Type::I64, Type::Ptr], false);
+    module.declare("hulk_gc_push_root", Type::Void, vec![Type::Ptr], false);
+    module.declare("hulk_gc_root_count", Type::I64, vec![], false);
+    module.declare("hulk_gc_pop_roots", Type::Void, vec![Type::I64], false);
+    module.declare("hulk_trace_push", Type::Void, vec![Type::Ptr], false);
+    module.declare("hulk_trace_pop", Type::Void, vec![], false);
+    module.declare("hulk_panic", Type::Void, vec![Type::Ptr, Type::Ptr, Type::I64, Type::I64], false);
 }
 
-pub fn to_llvm_type(type_node: String) -> String {
+pub fn to_llvm_type(type_node: String) -> Type {
     match type_node.as_str() {
-        "Number" => "double".to_string(),
-        "Boolean" => "i1".to_string(),
-        "String" => "ptr".to_string(),
-        _ => "ptr".to_string(), // Default to pointer type for unknown types
+        "Number" => Type::Double,
+        "Boolean" => Type::I1,
+        "String" => Type::Ptr,
+        // The semantic analyzer rejects every program with an `Unknown`-typed expression
+        "Unknown" => unreachable!("`Unknown` type reached code generation"),
+        _ => Type::Ptr, // Objects are handled through pointers
     }
 }
@@ -1,7 +1,17 @@
 pub mod code_generator;
 pub mod context;
+pub mod debug_info;
+pub mod gc;
+pub mod ir;
+pub mod ir_builder;
+#[cfg(feature = "llvm")]
+pub mod llvm_backend;
 pub mod llvm_utils;
+pub mod runtime_errors;
+pub mod source_map;
 pub mod statements;
+pub mod string_methods;
+pub mod target;
 pub mod visitor_codegen;
 pub use code_generator::CodeGenerator;
 pub mod type_def;
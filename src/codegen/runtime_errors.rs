@@ -0,0 +1,74 @@
+//! Runtime error reporting.
+//!
+//! Generated functions keep the shadow call stack of the runtime up to date:
+//! they push their HULK name with `hulk_trace_push` when entered and pop it with
+//! `hulk_trace_pop` before returning. A failed runtime check calls `hulk_panic`
+//! with the location of the expression being evaluated, which prints it along
+//! with the stack and exits.
+
+use super::code_generator::CodeGenerator;
+use super::ir::{FloatPredicate, Type, Value};
+use super::source_map::SourceMap;
+use crate::tokens::Span;
+
+impl CodeGenerator {
+    /// Sets the program being compiled, read from `path`; `source` is the input
+    /// with the builtin code injected before the user's file.
+    pub fn set_source(&mut self, path: &str, source: &str, missplacement: i32) {
+        self.context.source = Some(SourceMap::new(path, source, missplacement));
+    }
+
+    /// Checks at runtime that no number is divided by zero.
+    pub fn enable_checks(&mut self) {
+        self.context.checked = true;
+    }
+
+    /// Records on the shadow call stack that the function `name` was entered.
+    pub(crate) fn trace_enter(&mut self, name: &str) {
+        let name = self.context.module.add_string(name);
+        self.context
+            .builder()
+            .call_void(Value::Global("hulk_trace_push".to_string()), vec![(Type::Ptr, name)]);
+    }
+
+    /// Pops the current function from the shadow call stack, before returning.
+    pub(crate) fn trace_leave(&mut self) {
+        self.context.builder().call_void(Value::Global("hulk_trace_pop".to_string()), vec![]);
+    }
+
+    /// Reports `message` at `span` and exits when the `i1` value `failed` is true.
+    /// Code generated afterwards runs only if the check passed.
+    pub(crate) fn runtime_check(&mut self, failed: Value, message: &str, span: Span) {
+        let (path, line, column) = match &self.context.source {
+            Some(source) => {
+                let (line, column) = source.line_col(span.start).unwrap_or((0, 0));
+                (source.path.clone(), line, column)
+            }
+            None => ("<unknown>".to_string(), 0, 0),
+        };
+        let message = self.context.module.add_string(message);
+        let path = self.context.module.add_string(&path);
+        let builder = self.context.builder();
+        let panic_block = builder.new_block("runtime_error");
+        let ok_block = builder.new_block("check_ok");
+        builder.cond_br(failed, panic_block, ok_block);
+        builder.position_at_end(panic_block);
+        builder.call_void(
+            Value::Global("hulk_panic".to_string()),
+            vec![
+                (Type::Ptr, message),
+                (Type::Ptr, path),
+                (Type::I64, Value::Int(line as i64)),
+                (Type::I64, Value::Int(column as i64)),
+            ],
+        );
+        builder.unreachable();
+        builder.position_at_end(ok_block);
+    }
+
+    /// Checks that the divisor of a `/` or `%` at `span` is not zero.
+    pub(crate) fn check_divisor(&mut self, divisor: &Value, span: Span) {
+        let is_zero = self.context.builder().fcmp(FloatPredicate::Oeq, divisor.clone(), Value::Double(0.0));
+        self.runtime_check(is_zero, "Division by zero", span);
+    }
+}
@@ -0,0 +1,41 @@
+//! Line table of the program being compiled, which turns the byte offsets of
+//! `Span`s into lines and columns of the user's file.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceMap {
+    /// Path of the user's file, as given on the command line.
+    pub path: String,
+    /// The input with the builtin code injected before the user's file.
+    source: String,
+    /// Byte offset of the start of every line of `source`.
+    line_starts: Vec<usize>,
+    /// Number of lines of builtin code injected before the user's file.
+    missplacement: usize,
+}
+
+impl SourceMap {
+    pub fn new(path: &str, source: &str, missplacement: i32) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        SourceMap {
+            path: path.to_string(),
+            source: source.to_string(),
+            line_starts,
+            missplacement: missplacement.max(0) as usize,
+        }
+    }
+
+    /// Line and column (both 1-based) of `offset` in the user's file, or `None`
+    /// when it points into the injected builtin code.
+    pub fn line_col(&self, offset: usize) -> Option<(usize, usize)> {
+        let index = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let line = index + 1;
+        if line <= self.missplacement {
+            return None;
+        }
+        let start = self.line_starts[index];
+        let column = self.source.get(start..offset).map_or(1, |text| text.chars().count() + 1);
+        Some((line - self.missplacement, column))
+    }
+}
@@ -0,0 +1,79 @@
+//! Calls to the methods of the builtin `String` type.
+//!
+//! Strings have no vtable: every method (see `STRING_METHODS`) is a runtime
+//! function taking the string and the arguments by value. Methods that can fail
+//! return a sentinel that the generated code turns into a runtime error at the
+//! location of the call.
+
+use super::code_generator::CodeGenerator;
+use super::ir::{BinaryOp, FloatPredicate, IntPredicate, Type, Value};
+use super::llvm_utils::to_llvm_type;
+use super::visitor_codegen::GeneratorResult;
+use crate::ast_nodes::type_member_access::TypeFunctionAccessNode;
+use crate::types_tree::types_tree::STRING_METHODS;
+
+/// Runtime function implementing the `String` method `name`.
+fn runtime_function(name: &str) -> &'static str {
+    match name {
+        "size" => "hulk_str_size",
+        "substr" => "hulk_str_substr",
+        "at" => "hulk_str_at",
+        "indexOf" => "hulk_str_index_of",
+        "toUpper" => "hulk_str_to_upper",
+        "toLower" => "hulk_str_to_lower",
+        "parse" => "hulk_str_parse",
+        _ => unreachable!("Unknown String method: {}", name),
+    }
+}
+
+impl CodeGenerator {
+    /// Declares the runtime functions of every `String` method.
+    pub(crate) fn declare_string_methods(&mut self) {
+        for (name, params, return_type) in STRING_METHODS {
+            let mut param_types = vec![Type::Ptr];
+            param_types.extend(params.iter().map(|(_, signature)| to_llvm_type(signature.to_string())));
+            let ret = to_llvm_type(return_type.to_string());
+            self.context.module.declare(runtime_function(name), ret, param_types, false);
+        }
+    }
+
+    /// Calls the method of `node` on `object`, a `String`.
+    pub(crate) fn generate_string_method(
+        &mut self,
+        object: GeneratorResult,
+        node: &mut TypeFunctionAccessNode,
+    ) -> GeneratorResult {
+        // The receiver and string arguments must survive the allocations of later arguments
+        let mut roots = None;
+        self.gc_spill(&object, &mut roots);
+        let mut args = vec![(Type::Ptr, object.value)];
+        for arg in node.member.arguments.iter_mut() {
+            let arg_val = self.generate_expression(arg);
+            self.gc_spill(&arg_val, &mut roots);
+            args.push((arg_val.llvm_type, arg_val.value));
+        }
+        let name = node.member.function_name.clone();
+        let return_type = node.node_type.clone().unwrap().type_name;
+        let return_llvm = to_llvm_type(return_type.clone());
+        let result = self
+            .context
+            .builder()
+            .call(return_llvm.clone(), Value::Global(runtime_function(&name).to_string()), args);
+        self.gc_release(roots);
+        match name.as_str() {
+            "at" => {
+                let out_of_range = self.context.builder().icmp(IntPredicate::Eq, Type::Ptr, result.clone(), Value::Null);
+                self.runtime_check(out_of_range, "String index out of range", node.span);
+            }
+            "parse" => {
+                // Only NaN is not equal to itself
+                let builder = self.context.builder();
+                let is_number = builder.fcmp(FloatPredicate::Oeq, result.clone(), result.clone());
+                let failed = builder.binary(BinaryOp::Xor, Type::I1, is_number, Value::Bool(true));
+                self.runtime_check(failed, "String is not a valid Number", node.span);
+            }
+            _ => {}
+        }
+        GeneratorResult::new(result, return_llvm, return_type)
+    }
+}
@@ -0,0 +1,74 @@
+//! Description of the machine the generated module is compiled for.
+
+use super::ir::Type;
+
+/// Triple of the machine the compiler itself was built for, set by `build.rs`.
+/// It is the target used when no `--target` option is given.
+pub const HOST_TRIPLE: &str = env!("HULK_HOST_TRIPLE");
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Target {
+    pub triple: String,
+    /// LLVM data layout, which fixes the pointer width and the layout of structs.
+    /// `None` when it is not known, in which case LLVM uses the default of the triple.
+    pub data_layout: Option<String>,
+    /// Width of a pointer (and of `size_t`) in bits.
+    pub pointer_width: u32,
+}
+
+impl Target {
+    /// The machine the compiler runs on. If its triple is not one `from_triple` knows,
+    /// the data layout is left to LLVM and pointers are as wide as the compiler's own.
+    pub fn host() -> Self {
+        Self::from_triple(HOST_TRIPLE).unwrap_or_else(|_| Target {
+            triple: HOST_TRIPLE.to_string(),
+            data_layout: None,
+            pointer_width: usize::BITS,
+        })
+    }
+
+    /// Describes the target named by `triple` (`arch-vendor-os[-env]`).
+    pub fn from_triple(triple: &str) -> Result<Self, String> {
+        let arch = triple.split('-').next().unwrap_or_default();
+        let windows = triple.contains("windows");
+        let apple = ["apple", "darwin", "macos", "ios"].iter().any(|os| triple.contains(os));
+        let mangling = if windows { "w" } else if apple { "o" } else { "e" };
+        let (data_layout, pointer_width) = match arch {
+            "x86_64" => (
+                format!("e-m:{}-p270:32:32-p271:32:32-p272:64:64-i64:64-f80:128-n8:16:32:64-S128", mangling),
+                64,
+            ),
+            "i386" | "i486" | "i586" | "i686" if mangling == "e" => (
+                "e-m:e-p:32:32-p270:32:32-p271:32:32-p272:64:64-f64:32:64-f80:32-n8:16:32-S128".to_string(),
+                32,
+            ),
+            "aarch64" | "arm64" if windows => {
+                ("e-m:w-p:64:64-i32:32-i64:64-i128:128-n32:64-S128".to_string(), 64)
+            }
+            "aarch64" | "arm64" if apple => ("e-m:o-i64:64-i128:128-n32:64-S128".to_string(), 64),
+            "aarch64" => ("e-m:e-i8:8:32-i16:16:32-i64:64-i128:128-n32:64-S128".to_string(), 64),
+            _ if arch.starts_with("armv7") && mangling == "e" => {
+                ("e-m:e-p:32:32-Fi8-i64:64-v128:64:128-a:0:32-n32-S64".to_string(), 32)
+            }
+            "wasm32" => ("e-m:e-p:32:32-p10:8:8-p20:8:8-i64:64-n32:64-S128-ni:1:10:20".to_string(), 32),
+            "wasm64" => ("e-m:e-p:64:64-p10:8:8-p20:8:8-i64:64-n32:64-S128-ni:1:10:20".to_string(), 64),
+            // `riscv64gc`, `riscv64imac`, ... only differ in the extensions they use
+            _ if arch.starts_with("riscv64") => ("e-m:e-p:64:64-i64:64-i128:128-n64-S128".to_string(), 64),
+            _ => return Err(format!("Unsupported target: {}", triple)),
+        };
+        Ok(Target { triple: triple.to_string(), data_layout: Some(data_layout), pointer_width })
+    }
+
+    pub fn is_host(&self) -> bool {
+        self.triple == HOST_TRIPLE
+    }
+
+    pub fn is_windows(&self) -> bool {
+        self.triple.contains("windows")
+    }
+
+    /// Integer type of C's `size_t` on the target.
+    pub fn size_type(&self) -> Type {
+        if self.pointer_width == 32 { Type::I32 } else { Type::I64 }
+    }
+}
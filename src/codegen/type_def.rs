@@ -1,6 +1,6 @@
 use std::{collections::HashMap};
 
-use crate::{ast_nodes::{program::{Program, Statement}, type_def::{TypeDefNode, TypeMember}}, codegen::{llvm_utils::to_llvm_type, CodeGenerator}, visitor::accept::Accept};
+use crate::{ast_nodes::{program::{Program, Statement}, type_def::{TypeDefNode, TypeMember}}, codegen::{ir::{Constant, Global, Type, Value}, llvm_utils::to_llvm_type, visitor_codegen::GeneratorResult, CodeGenerator}};
 
 impl CodeGenerator {
     pub fn init_all_type_methods_and_props(&mut self, node: &mut Program) {
@@ -92,24 +92,14 @@ impl CodeGenerator {
         self.context.types_members.insert(type_name.clone(), props_list);
         self.context.max_functions += count_functions;
 
-        let mut props_types = Vec::new();
+        // type (vtable , parent , props...)
+        let mut fields = vec![Type::I32, Type::Ptr];
         if let Some(props_list) = self.context.types_members.get(&type_name) {
             for (_prop_name, prop_type) in props_list {
-                props_types.push(to_llvm_type(prop_type.clone()));
+                fields.push(to_llvm_type(prop_type.clone()));
             }
         } 
-
-        let list_props_str = props_types
-            .iter()
-            .map(|llvm_name| format!("{}", llvm_name))
-            .collect::<Vec<_>>()
-            .join(", ");
-        // type (vtable , parent , props...)
-        if props_types.len() > 0 {
-            self.context.add_line(format!("%{}_type = type {{ i32, ptr, {} }}", type_name.clone(), list_props_str)); 
-        } else {
-            self.context.add_line(format!("%{}_type = type {{ i32, ptr }}", type_name.clone())); 
-        }
+        self.context.module.types.push((format!("{}_type", type_name), Type::Struct(fields)));
         
         // lll
         if let Some(parent_name) = &node.parent {
@@ -140,127 +130,175 @@ impl CodeGenerator {
 
     pub fn generate_type_constructor(&mut self, node: &mut TypeDefNode){
         let type_name = node.identifier.clone();
-        let type_reg = format!("%{}_type",type_name);
-        let mut params_list = Vec::new();
-        self.context.enter_scope();
-        for param in node.params.iter() {
-            let param_name  = format!("%{}.{}",param.name.clone(),self.context.get_scope());
-            params_list.push(format!("ptr {}",param_name.clone()));
-            self.context.add_variable(param_name.clone(), to_llvm_type(param.signature.clone()));
-        }
-        let params_str = params_list.join(", ");
+        let type_reg = Type::Named(format!("{}_type",type_name));
 
-    
         // Crea una lista del tamaño de max_functions, inicializada con "ptr null"
-        let mut method_list = vec!["ptr null".to_string(); self.context.max_functions as usize];
+        let mut method_list = vec![Constant::Value(Value::Null); self.context.max_functions as usize];
 
         // Llena la lista con el nombre de la función en el índice correspondiente
         if let Some(functions) = self.context.types_functions.get(&type_name) {
             for (index,(_, llvm_name))in functions.iter().enumerate() {
                 if index < self.context.max_functions as usize {
-                    method_list[index] = format!("ptr {}", llvm_name);
+                    method_list[index] = Constant::Value(Value::Global(llvm_name.trim_start_matches('@').to_string()));
                 }
             }
         }
 
-        // generate vtable instance 
-        let type_table_instance = format!("@{}_vtable", type_name);
-
         // Crea la instancia de la vtable usando method_list
-        self.context.add_line(format!("{} = constant %VTableType [ {} ]", type_table_instance, method_list.join(", ")));
+        self.context.module.add_global(Global {
+            name: format!("{}_vtable", type_name),
+            ty: Type::Named("VTableType".to_string()),
+            init: Constant::Array(Type::Ptr, method_list),
+            constant: true,
+            private: false,
+        });
+
+        self.generate_gc_map(&type_name);
         
         // build constructor
-        self.context.add_line(format!("define ptr @{}_new( {} ) {{",type_name.clone(),params_str.clone())); 
+        let constructor_name = format!("{}_new", type_name);
+        self.context.begin_function(&constructor_name, Type::Ptr);
+        self.context.builder().set_location(Some(node.span));
+        let param_types: Vec<String> = node.params.iter().map(|param| param.signature.clone()).collect();
+        self.debug_subprogram(&constructor_name, node.span, Some(&type_name), &param_types);
+        self.context.enter_scope();
+        for (index, param) in node.params.iter().enumerate() {
+            let param_type = to_llvm_type(param.signature.clone());
+            let slot = self.context.builder().add_param(Type::Ptr, &param.name);
+            self.debug_variable(&param.name, &param.signature, param.span, &slot, Some(index as u32 + 1));
+            self.context.add_variable(param.name.clone(), slot, param_type);
+        }
+        self.trace_enter(&format!("new {}", type_name));
 
-        let size_temp = self.context.new_temp("Number".to_string());
-        self.context.add_line(format!("{} = ptrtoint ptr getelementptr({}, ptr null, i32 1) to i64", size_temp, type_reg));
-        let mem_temp = self.context.new_temp(type_name.clone());
-        self.context.add_line(format!("{} = call ptr @malloc(i64 {})" , mem_temp , size_temp));
+        let builder = self.context.builder();
+        let size = builder.size_of(type_reg.clone());
+        let mem_temp = builder.call(Type::Ptr, Value::Global("hulk_gc_alloc".to_string()), vec![
+            (Type::I64, size),
+            (Type::Ptr, Value::Global(format!("{}_gc_map", type_name))),
+        ]);
+        // The new object is only referenced from a register while its members are initialized
+        let mut roots = None;
+        self.gc_spill(&GeneratorResult::new(mem_temp.clone(), Type::Ptr, type_name.clone()), &mut roots);
 
         // set type index on super_vtable
-        self.context.add_line(format!("%index_ptr = getelementptr {}, ptr {}, i32 0, i32 0", type_reg, mem_temp));
-        self.context.add_line(format!("store i32 {}, ptr %index_ptr", self.context.type_id.get(&type_name).expect("Type ID not found for type").clone()));
+        let type_id = *self.context.type_id.get(&type_name).expect("Type ID not found for type");
+        let builder = self.context.builder();
+        let index_ptr = builder.struct_gep(type_reg.clone(), mem_temp.clone(), 0);
+        builder.store(Type::I32, Value::Int(type_id as i64), index_ptr);
 
         if let Some(parent_name) = node.parent.clone() {
             let mut parent_args_values = Vec::new();
             for arg in node.parent_args.iter_mut() {
-                let arg_result = arg.accept(self);
-                let arg_reg = self.context.new_temp(arg_result.ast_type);
-                self.context.add_line(format!("{} = alloca {}", arg_reg.clone() , arg_result.llvm_type.clone()));
-                self.context.add_line(format!(
-                    "store {} {}, ptr {}",
-                    arg_result.llvm_type, arg_result.register, arg_reg.clone()
-                ));
-                parent_args_values.push(format!("ptr {}",arg_reg.clone()));
+                let arg_result = self.generate_expression(arg);
+                let builder = self.context.builder();
+                let arg_reg = builder.alloca(arg_result.llvm_type.clone(), None);
+                builder.store(arg_result.llvm_type.clone(), arg_result.value.clone(), arg_reg.clone());
+                self.gc_root(&arg_reg, &arg_result.llvm_type, &mut roots);
+                parent_args_values.push((Type::Ptr, arg_reg));
             }
-            let args_regs_str = parent_args_values.join(", ");
-            let parent_ptr = self.context.new_temp(parent_name.clone());
-            let parent_constructor_name = format!("@{}_new" , parent_name.clone()); 
-            self.context.add_line(format!(
-                "{} = call ptr {}({})",
-                parent_ptr.clone(), parent_constructor_name, args_regs_str
-            ));
-            self.context.add_line(format!("%parent_ptr = getelementptr {}, ptr {}, i32 0, i32 1", type_reg, mem_temp));
-            self.context.add_line(format!("store ptr {}, ptr %parent_ptr", parent_ptr.clone()));
-            if let Some(parent_members) = self.context.types_members.get(&parent_name) {
-                let parent_members_cloned = parent_members.clone();
-                for (index, (_member_name, member_type)) in parent_members_cloned.iter().enumerate() {
-                    let llvm_type = to_llvm_type(member_type.clone());
-                    let parent_type = format!("%{}_type", parent_name.clone());
-                    self.context.add_line(format!(
-                        "%src_{} = getelementptr {}, ptr {}, i32 0, i32 {}",
-                        index.clone(), parent_type.clone(), parent_ptr.clone(), index.clone() + 2
-                    ));
-                    self.context.add_line(format!(
-                        "%val_{} = load {}, ptr %src_{}",
-                        index.clone(), llvm_type, index.clone()
-                    ));
-                    self.context.add_line(format!(
-                        "%dst_{} = getelementptr {}, ptr {}, i32 0, i32 {}",
-                        index.clone(), type_reg, mem_temp, index.clone() + 2
-                    ));
-                    self.context.add_line(format!(
-                        "store {} %val_{}, ptr %dst_{}",
-                        llvm_type, index.clone(), index.clone()
-                    ));
-                }
+            let parent_type = Type::Named(format!("{}_type", parent_name));
+            let parent_members = self.context.types_members.get(&parent_name).cloned().unwrap_or_default();
+            let builder = self.context.builder();
+            let parent_ptr = builder.call(Type::Ptr, Value::Global(format!("{}_new", parent_name)), parent_args_values);
+            let parent_field = builder.struct_gep(type_reg.clone(), mem_temp.clone(), 1);
+            builder.store(Type::Ptr, parent_ptr.clone(), parent_field);
+            for (index, (_member_name, member_type)) in parent_members.iter().enumerate() {
+                let llvm_type = to_llvm_type(member_type.clone());
+                let src = builder.struct_gep(parent_type.clone(), parent_ptr.clone(), index as u32 + 2);
+                let val = builder.load(llvm_type.clone(), src);
+                let dst = builder.struct_gep(type_reg.clone(), mem_temp.clone(), index as u32 + 2);
+                builder.store(llvm_type, val, dst);
             }
         }
         
         // set properties values 
 
+        let previous_self = self.context.self_value.replace(mem_temp.clone());
         for member in node.members.iter() {
             match member {
                 TypeMember::Property(assign) => {
-                    let prop_reg = assign.expression.clone().accept(self);
-                    let result_reg = self.context.new_temp(prop_reg.ast_type.clone());
+                    let prop_reg = self.generate_expression(&mut assign.expression.clone());
                     let member_key = (type_name.clone(), assign.identifier.clone());
-                    let member_index = self.context.type_members_ids.get(&member_key)
+                    let member_index = *self.context.type_members_ids.get(&member_key)
                         .expect("Member index not found for type and param name");
-                    self.context.add_line(format!(
-                        "{} = getelementptr {}, ptr {}, i32 0, i32 {}",
-                        result_reg, type_reg, mem_temp, member_index
-                    ));
-                    self.context.add_line(format!("store {} {}, ptr {}", prop_reg.llvm_type, prop_reg.register, result_reg));
+                    let builder = self.context.builder();
+                    let result_reg = builder.struct_gep(type_reg.clone(), mem_temp.clone(), member_index as u32);
+                    builder.store(prop_reg.llvm_type, prop_reg.value, result_reg);
                 }
                 _ => continue 
             }
         }
+        self.context.self_value = previous_self;
 
-        self.context.add_line(format!("ret ptr {}", mem_temp));
-        self.context.add_line("}".to_string());
+        self.gc_release(roots);
+        self.trace_leave();
+        self.context.builder().ret(Some((Type::Ptr, mem_temp)));
+        self.context.exit_scope();
+        self.context.end_function();
+    }
 
+    /// Emits `@T_gc_map`, the byte offsets of the pointer fields of `%T_type`
+    /// (the parent instance and every `ptr` property) traced by the garbage collector.
+    fn generate_gc_map(&mut self, type_name: &str) {
+        let mut fields = vec![1];
+        if let Some(props) = self.context.types_members.get(type_name) {
+            for (index, (_, prop_type)) in props.iter().enumerate() {
+                if to_llvm_type(prop_type.clone()) == Type::Ptr {
+                    fields.push(index as u32 + 2);
+                }
+            }
+        }
+        let struct_type = Type::Named(format!("{}_type", type_name));
+        let offsets = fields
+            .iter()
+            .map(|field| Constant::FieldOffset { ty: struct_type.clone(), field: *field })
+            .collect::<Vec<_>>();
+        let offsets_type = Type::Array(fields.len(), Box::new(Type::I64));
+        self.context.module.add_global(Global {
+            name: format!("{}_gc_map", type_name),
+            ty: Type::Struct(vec![Type::I64, offsets_type.clone()]),
+            init: Constant::Struct(vec![
+                (Type::I64, Constant::Value(Value::Int(fields.len() as i64))),
+                (offsets_type, Constant::Array(Type::I64, offsets)),
+            ]),
+            constant: true,
+            private: false,
+        });
+    }
+
+    pub fn generate_super_vtable(&mut self) {
+        let vtables = self.context.types_vtables.iter()
+            .map(|vtable| Constant::Value(Value::Global(vtable.trim_start_matches('@').to_string())))
+            .collect();
+        self.context.module.add_global(Global {
+            name: "super_vtable".to_string(),
+            ty: Type::Array(self.context.count_types as usize, Box::new(Type::Ptr)),
+            init: Constant::Array(Type::Ptr, vtables),
+            constant: false,
+            private: false,
+        });
     }
 
     pub fn generate_get_vtable_method(&mut self) {
-        self.context.add_line("define ptr @get_vtable_method(i32 %type_id, i32 %method_id) {".to_string());
-        self.context.add_line(format!("%vtable_ptr_ptr = getelementptr [ {} x ptr ], ptr @super_vtable, i32 0, i32 %type_id", self.context.count_types));
-        self.context.add_line(format!("%vtable_ptr = load ptr , ptr %vtable_ptr_ptr"));
-        self.context.add_line(format!("%typed_vtable = bitcast ptr %vtable_ptr to ptr"));
-        self.context.add_line(format!("%method_ptr = getelementptr [ {} x ptr ], ptr %typed_vtable, i32 0, i32 %method_id", self.context.max_functions));
-        self.context.add_line(format!("%method = load ptr, ptr %method_ptr"));
-        self.context.add_line(format!("ret ptr %method"));
-        self.context.add_line("}".to_string());
+        let count_types = self.context.count_types as usize;
+        let max_functions = self.context.max_functions as usize;
+        self.context.begin_function("get_vtable_method", Type::Ptr);
+        let builder = self.context.builder();
+        let type_id = builder.add_param(Type::I32, "type_id");
+        let method_id = builder.add_param(Type::I32, "method_id");
+        let vtable_ptr_ptr = builder.gep(
+            Type::Array(count_types, Box::new(Type::Ptr)),
+            Value::Global("super_vtable".to_string()),
+            vec![(Type::I32, Value::Int(0)), (Type::I32, type_id)],
+        );
+        let vtable_ptr = builder.load(Type::Ptr, vtable_ptr_ptr);
+        let method_ptr = builder.gep(
+            Type::Array(max_functions, Box::new(Type::Ptr)),
+            vtable_ptr,
+            vec![(Type::I32, Value::Int(0)), (Type::I32, method_id)],
+        );
+        let method = builder.load(Type::Ptr, method_ptr);
+        builder.ret(Some((Type::Ptr, method)));
+        self.context.end_function();
     }
 }
-
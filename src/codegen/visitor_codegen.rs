@@ -1,5 +1,6 @@
 use super::code_generator::CodeGenerator;
-use super::llvm_utils::{to_llvm_type};
+use super::ir::{BinaryOp, FloatPredicate, IntPredicate, Type, Value};
+use super::llvm_utils::to_llvm_type;
 use crate::ast_nodes::binary_op::BinaryOpNode;
 use crate::ast_nodes::block::BlockNode;
 use crate::ast_nodes::destructive_assign::DestructiveAssignNode;
@@ -18,233 +19,189 @@ use crate::ast_nodes::type_member_access::{TypeFunctionAccessNode, TypePropAcces
 use crate::ast_nodes::unary_op::UnaryOpNode;
 use crate::ast_nodes::while_loop::WhileNode;
 use crate::ast_nodes::print::PrintNode;
+use crate::ast_nodes::read::{ReadKind, ReadNode};
 use crate::tokens::OperatorToken;
-use crate::visitor::accept::Accept;
 use crate::visitor::visitor_trait::Visitor;
 
 pub struct GeneratorResult {
-    pub register: String,   
-    pub llvm_type: String,
+    pub value: Value,
+    pub llvm_type: Type,
     pub ast_type : String,
 }
 impl GeneratorResult {
-    pub fn new(register: String, llvm_type: String, ast_type: String) -> Self {
-        GeneratorResult { register, llvm_type, ast_type }
+    pub fn new(value: Value, llvm_type: Type, ast_type: String) -> Self {
+        GeneratorResult { value, llvm_type, ast_type }
     }
 }
 
+fn global(name: &str) -> Value {
+    Value::Global(name.to_string())
+}
+
 impl Visitor<GeneratorResult> for CodeGenerator {
 
     fn visit_function_def(&mut self, node: &mut FunctionDefNode) -> GeneratorResult {
         let function_name = node.name.clone();
-        let params = node.params.clone();
         let return_type = node.return_type.clone();
-        let function_global_name = format!("@{}", function_name);
+        let return_llvm = to_llvm_type(return_type.clone());
+        self.context.begin_function(&function_name, return_llvm.clone());
+        self.context.builder().set_location(Some(node.span));
+        let param_types: Vec<String> = node.params.iter().map(|param| param.signature.clone()).collect();
+        self.debug_subprogram(&function_name, node.span, Some(&return_type), &param_types);
         self.context.enter_scope();
-        let mut llvm_args: Vec<String> = params.iter().map(|param| {
-            let llvm_type = to_llvm_type(param.signature.clone());
-            let register_name = format!("%{}.{}", param.name.clone(), self.context.get_scope());
-            self.context.add_variable(register_name.clone(), llvm_type.clone());
-            format!("ptr {}", register_name)
-        }).collect();
-        if let Some (type_name) = self.context.current_self.clone() {
-            self.context.add_variable(format!("%self.{}",self.context.get_scope()), type_name);
-            llvm_args.insert(0, format!("ptr %self.{}",self.context.get_scope()));
+        if self.context.current_self.is_some() {
+            let self_value = self.context.builder().add_param(Type::Ptr, "self");
+            self.context.self_value = Some(self_value);
         }
-        self.context.add_line(format!("define {} {}({}) {{", to_llvm_type(return_type.clone()), function_global_name, llvm_args.join(", ")));
-        let llvm_body = node.body.accept(self);
-        self.context.add_line(format!("ret {} {}", llvm_body.llvm_type, llvm_body.register));
-        self.context.add_line("}".to_string());
+        let first_arg = if self.context.current_self.is_some() { 2 } else { 1 };
+        for (index, param) in node.params.iter().enumerate() {
+            let slot = self.context.builder().add_param(Type::Ptr, &param.name);
+            self.debug_variable(&param.name, &param.signature, param.span, &slot, Some(first_arg + index as u32));
+            self.context.add_variable(param.name.clone(), slot, to_llvm_type(param.signature.clone()));
+        }
+        // Methods are named `T_method` in the module and `T.method` in stack traces
+        let trace_name = match &self.context.current_self {
+            Some(self_type) => {
+                let method = function_name.strip_prefix(&format!("{}_", self_type)).unwrap_or(&function_name);
+                format!("{}.{}", self_type, method)
+            }
+            None => function_name.clone(),
+        };
+        self.trace_enter(&trace_name);
+        let llvm_body = self.generate_expression(&mut node.body);
+        self.trace_leave();
+        self.context.builder().ret(Some((llvm_body.llvm_type, llvm_body.value)));
+        self.context.self_value = None;
         self.context.exit_scope();
-        GeneratorResult::new(function_global_name, to_llvm_type(return_type.clone()),return_type.clone())
+        self.context.end_function();
+        GeneratorResult::new(global(&function_name), return_llvm, return_type)
     }
 
     fn visit_literal_number(&mut self, node: &mut NumberLiteralNode) -> GeneratorResult {
-        let mut raw = node.value.to_string();
-        if !raw.contains('.') {
-            raw.push_str(".0");
-        }
-        let temp = self.context.new_temp("Number".to_string());
-        self.context
-            .add_line(format!("{} = fadd double 0.0, {}", temp, raw));
-        GeneratorResult::new(temp, "double".to_string(),"Number".to_string())
+        GeneratorResult::new(Value::Double(node.value), Type::Double, "Number".to_string())
     }
 
     fn visit_literal_boolean(&mut self, node: &mut BooleanLiteralNode) -> GeneratorResult {
-        let value = if node.value { "1" } else { "0" };
-        let temp = self.context.new_temp("Boolean".to_string());
-        self.context
-            .add_line(format!("{} = add i1 {}, 0", temp, value));
-        GeneratorResult::new(temp, "i1".to_string(), "Boolean".to_string())
+        GeneratorResult::new(Value::Bool(node.value), Type::I1, "Boolean".to_string())
     }
 
     fn visit_literal_string(&mut self, node: &mut StringLiteralNode) -> GeneratorResult {
-        let temp = self.context.new_temp("String".to_string());
-        let len = node.value.len();
-        let global_const = self.context.add_str_const(node.value.clone(), len.clone());
-        self.context.string_literals.insert(temp.clone(), node.value.clone());
-        self.context.add_line(format!("{} = getelementptr [{} x i8], ptr {}, i32 0, i32 0",temp,len + 1,global_const));
-        GeneratorResult::new(temp, "ptr".to_string(), "String".to_string())
+        let global_const = self.context.module.add_string(&node.value);
+        GeneratorResult::new(global_const, Type::Ptr, "String".to_string())
     }
 
     fn visit_identifier(&mut self, node: &mut IdentifierNode) -> GeneratorResult {
         let value = node.value.clone();
-        let llvm_type = to_llvm_type(node.node_type.clone().unwrap().type_name);
+        let type_name = node.node_type.clone().unwrap().type_name;
+        let llvm_type = to_llvm_type(type_name.clone());
         if self.context.is_global_constant(&value) {
-            let register = self.context.new_temp(node.node_type.clone().unwrap().type_name);
-            let global_ref = format!("@{}", value);
-            self.context.add_line(format!(
-                "{} = load {}, ptr {}",register, llvm_type, global_ref // Usar directamente el nombre (@PI, @E)
-            ));
-             GeneratorResult::new(register, llvm_type,node.node_type.clone().unwrap().type_name)
-        } 
-        else {
-            let register = self.context.new_temp(llvm_type.clone());
-            self.context.add_line(format!(
-                 "{} = load {}, ptr {}", register, llvm_type.clone(), self.context.get_variable(value)
-            ));
-            GeneratorResult::new(register, llvm_type.clone(),node.node_type.clone().unwrap().type_name)
+            let register = self.context.builder().load(llvm_type.clone(), global(&value));
+            GeneratorResult::new(register, llvm_type, type_name)
+        } else if value == "self" && self.context.self_value.is_some() {
+            GeneratorResult::new(self.context.self_value.clone().unwrap(), Type::Ptr, type_name)
+        } else {
+            let variable = self.context.get_variable(&value);
+            let register = self.context.builder().load(llvm_type.clone(), variable.slot);
+            GeneratorResult::new(register, llvm_type, type_name)
         }
     }
 
     fn visit_function_call(&mut self,  node: &mut FunctionCallNode) -> GeneratorResult {
         let name = node.function_name.clone();
-        let llvm_args: Vec<String> = node.arguments.iter().map(|arg| {
-            let arg_val = arg.clone().accept(self);
-            let arg_reg = self.context.new_temp(arg_val.llvm_type.clone());
-            self.context.add_line(format!("{} = alloca {}", arg_reg.clone(), arg_val.llvm_type));
-            self.context.add_line(format!(
-                "store {} {}, ptr {}",
-                arg_val.llvm_type, arg_val.register, arg_reg.clone()
-            ));
-            format!("ptr {}", arg_reg)
-        }).collect();
-        let node_type = to_llvm_type(node.node_type.clone().unwrap().type_name);
-        let temp = self.context.new_temp(node_type.clone());
-        self.context.add_line(format!(
-            "{} = call {} @{}({})",
-            temp, node_type, name, llvm_args.join(", ")
-        ));
-        GeneratorResult::new(temp, node_type,node.node_type.clone().unwrap().type_name)
-
+        let mut roots = None;
+        let mut llvm_args = Vec::new();
+        for arg in node.arguments.iter_mut() {
+            let arg_val = self.generate_expression(arg);
+            let builder = self.context.builder();
+            let arg_reg = builder.alloca(arg_val.llvm_type.clone(), None);
+            builder.store(arg_val.llvm_type.clone(), arg_val.value, arg_reg.clone());
+            self.gc_root(&arg_reg, &arg_val.llvm_type, &mut roots);
+            llvm_args.push((Type::Ptr, arg_reg));
+        }
+        let type_name = node.node_type.clone().unwrap().type_name;
+        let node_type = to_llvm_type(type_name.clone());
+        let temp = self.context.builder().call(node_type.clone(), global(&name), llvm_args);
+        self.gc_release(roots);
+        GeneratorResult::new(temp, node_type, type_name)
     }
 
     fn visit_while_loop(&mut self, node: &mut WhileNode) -> GeneratorResult {
-        let id_cond = self.context.new_id();
-        let id_loop = self.context.new_id();
-        let cond_label = format!("while_cond.{}",id_cond);
-        let loop_label = format!("while_loop.{}",id_loop);
-        let id_exit = self.context.new_id();
-        let exit_label = format!("while_exit.{}",id_exit);
-        let node_type = to_llvm_type(node.node_type.clone().unwrap().type_name);
-
-        let result_reg = self.context.new_temp(node_type.clone());
-        self.context.add_line(format!("{} = alloca {}", result_reg.clone() ,node_type.clone()));
-        
-        self.context.add_line(format!("br label %{}\n\n", cond_label));
-
-        self.context.add_line(format!("{}:", cond_label));
-        let cond_register = node.condition.accept(self);
-        self.context.add_line(format!(
-            "br i1 {}, label %{}, label %{}\n\n",
-            cond_register.register, loop_label, exit_label
-        ));
-        self.context.add_line(format!("{}:", loop_label));
-        let body_register = node.body.accept(self);
-        self.context.add_line(format!(
-            "store {} {}, ptr {}\n\n",
-            node_type.clone(), body_register.register, result_reg.clone()
-        ));
-        self.context.add_line(format!("br label %{}\n\n", cond_label));
-
-        self.context.add_line(format!("{}:", exit_label));
-        let return_reg = self.context.new_temp(node_type.clone());
-        self.context.add_line(format!(
-            "{} = load {}, ptr {}\n",
-            return_reg.clone(), node_type.clone(), result_reg.clone()
-        ));
-        GeneratorResult::new(return_reg, node_type,node.node_type.clone().unwrap().type_name)
+        let type_name = node.node_type.clone().unwrap().type_name;
+        let node_type = to_llvm_type(type_name.clone());
+
+        let builder = self.context.builder();
+        let result_reg = builder.alloca(node_type.clone(), None);
+        // The result is read again after the condition, which may allocate
+        builder.store(node_type.clone(), Value::default_of(&node_type), result_reg.clone());
+        let mut roots = None;
+        self.gc_root(&result_reg, &node_type, &mut roots);
+
+        let builder = self.context.builder();
+        let cond_label = builder.new_block("while_cond");
+        let loop_label = builder.new_block("while_loop");
+        let exit_label = builder.new_block("while_exit");
+        builder.br(cond_label);
+
+        builder.position_at_end(cond_label);
+        let cond_register = self.generate_expression(&mut node.condition);
+        let builder = self.context.builder();
+        builder.cond_br(cond_register.value, loop_label, exit_label);
+
+        builder.position_at_end(loop_label);
+        let body_register = self.generate_expression(&mut node.body);
+        let body_value = self.branch_value(&body_register, &node_type);
+        let builder = self.context.builder();
+        builder.store(node_type.clone(), body_value, result_reg.clone());
+        builder.br(cond_label);
+
+        builder.position_at_end(exit_label);
+        let return_reg = builder.load(node_type.clone(), result_reg);
+        self.gc_release(roots);
+        GeneratorResult::new(return_reg, node_type, type_name)
     }
 
     fn visit_for_loop(&mut self, node: &mut ForNode) -> GeneratorResult {
-        let start_reg = node.start.accept(self);
-        let end_reg = node.end.accept(self);
+        let start_reg = self.generate_expression(&mut node.start);
+        let end_reg = self.generate_expression(&mut node.end);
         self.context.enter_scope();
-        let for_condition_id = self.context.new_id();
-        let for_condition_label = format!("for_condition.{}", for_condition_id); 
-        let for_body_id = self.context.new_id();
-        let for_body_label = format!("for_body.{}", for_body_id);
-        let for_exit_id = self.context.new_id();
-        let for_exit_label = format!("for_exit.{}", for_exit_id);
-        let index_reg = format!("%{}.{}", node.variable, self.context.get_scope());
-        let comp_reg = self.context.new_temp("Number".to_string());
-        self.context.add_line(format!(
-            "{} = fcmp ole double {}, {}",
-            comp_reg, start_reg.register, end_reg.register
-        ));
-        let step_reg = self.context.new_temp("Number".to_string());
-        self.context.add_line(format!(
-            "{} = select i1 {}, double 1.0, double -1.0",
-            step_reg, comp_reg
-        ));
-        self.context.add_variable(index_reg.clone(), start_reg.llvm_type.clone());
-        self.context.add_line(format!(
-            "{} = alloca {}",
-            index_reg, start_reg.llvm_type
-        )); 
-        self.context.add_line(format!(
-            "store {} {}, ptr {}",
-            start_reg.llvm_type, start_reg.register, index_reg
-        ));
-
-        self.context.add_line(format!("br label %{}\n\n", for_condition_label));
-        self.context.add_line(format!("{}:", for_condition_label));
-        let curr = self.context.new_temp("Number".to_string());
-        self.context.add_line(format!(
-            "{} = load {}, ptr {}\n",
-            curr, start_reg.llvm_type, index_reg
-        ));
-        let comp_up = self.context.new_temp("Number".to_string());
-        self.context.add_line(format!(
-            "{} = fcmp ole double {}, {}",
-            comp_up, curr, end_reg.register
-        ));
-        let comp_down = self.context.new_temp("Number".to_string());
-        self.context.add_line(format!(
-            "{} = fcmp oge double {}, {}",
-            comp_down, curr, end_reg.register
-        ));
-        let condition = self.context.new_temp("Boolean".to_string());
-        self.context.add_line(format!(
-            "{} = select i1 {}, i1 {}, i1 {}",
-            condition, comp_reg ,comp_up, comp_down
-        ));
-        self.context.add_line(format!(
-            "br i1 {}, label %{}, label %{}\n\n",
-            condition, for_body_label, for_exit_label
-        ));
-        self.context.add_line(format!("{}:", for_body_label));
-        let body_result = node.body.accept(self);
-        let step_val = self.context.new_temp("Number".to_string());
-        self.context.add_line(format!(
-            "{} = fadd double {}, {}\n",
-            step_val, curr, step_reg
-        ));
-        self.context.add_line(format!(
-            "store double {}, ptr {}\n\n",
-            step_val, index_reg
-        ));
-        self.context.add_line(format!("br label %{}\n\n", for_condition_label));
-        self.context.add_line(format!("{}:", for_exit_label));
+        let builder = self.context.builder();
+        let for_condition_label = builder.new_block("for_condition");
+        let for_body_label = builder.new_block("for_body");
+        let for_exit_label = builder.new_block("for_exit");
+        let comp_reg = builder.fcmp(FloatPredicate::Ole, start_reg.value.clone(), end_reg.value.clone());
+        let step_reg = builder.select(comp_reg.clone(), Type::Double, Value::Double(1.0), Value::Double(-1.0));
+        let index_reg = builder.alloca(start_reg.llvm_type.clone(), Some(&node.variable));
+        builder.store(start_reg.llvm_type.clone(), start_reg.value.clone(), index_reg.clone());
+        self.debug_variable(&node.variable, "Number", node.span, &index_reg, None);
+        let builder = self.context.builder();
+        builder.br(for_condition_label);
+
+        builder.position_at_end(for_condition_label);
+        let curr = builder.load(start_reg.llvm_type.clone(), index_reg.clone());
+        let comp_up = builder.fcmp(FloatPredicate::Ole, curr.clone(), end_reg.value.clone());
+        let comp_down = builder.fcmp(FloatPredicate::Oge, curr.clone(), end_reg.value.clone());
+        let condition = builder.select(comp_reg, Type::I1, comp_up, comp_down);
+        builder.cond_br(condition, for_body_label, for_exit_label);
+
+        builder.position_at_end(for_body_label);
+        self.context.add_variable(node.variable.clone(), index_reg.clone(), start_reg.llvm_type.clone());
+        let body_result = self.generate_expression(&mut node.body);
+        let builder = self.context.builder();
+        let step_val = builder.binary(BinaryOp::FAdd, Type::Double, curr, step_reg);
+        builder.store(Type::Double, step_val, index_reg);
+        builder.br(for_condition_label);
+
+        builder.position_at_end(for_exit_label);
         self.context.exit_scope();
-        GeneratorResult::new(body_result.register, body_result.llvm_type, body_result.ast_type)
+        GeneratorResult::new(body_result.value, body_result.llvm_type, body_result.ast_type)
     }
 
     fn visit_code_block(&mut self, node: &mut BlockNode) -> GeneratorResult {
         self.context.enter_scope();
-        let mut result = GeneratorResult::new("".to_string(), "".to_string(),"".to_string());
+        // An empty block evaluates to the default `Object` value
+        let mut result = GeneratorResult::new(Value::Null, Type::Ptr, "Object".to_string());
         for expr in node.expression_list.expressions.iter_mut() {
-            let current = expr.accept(self);
+            let current = self.generate_expression(expr);
             result = current;
         }
         self.context.exit_scope();
@@ -252,53 +209,47 @@ impl Visitor<GeneratorResult> for CodeGenerator {
     }
 
     fn visit_binary_op(&mut self, node: &mut BinaryOpNode) -> GeneratorResult {
-        let left_val = node.left.accept(self);
-        let right_val = node.right.accept(self);
+        let mut left_val = self.generate_expression(&mut node.left);
+        let mut roots = None;
+        let concat = matches!(node.operator, OperatorToken::CONCAT | OperatorToken::DCONCAT);
+        if concat {
+            // The left string must survive the allocations of the right operand
+            left_val = self.string_of(left_val);
+            self.gc_spill(&left_val, &mut roots);
+        }
+        let mut right_val = self.generate_expression(&mut node.right);
+        if concat {
+            right_val = self.string_of(right_val);
+        }
         let op = node.operator.clone();
         match op {
             OperatorToken::PLUS | OperatorToken::MINUS | OperatorToken::MUL | OperatorToken::DIV => {
                 let opcode = match op {
-                    OperatorToken::PLUS => "fadd",
-                    OperatorToken::MINUS => "fsub",
-                    OperatorToken::MUL => "fmul",
-                    OperatorToken::DIV => "fdiv",
+                    OperatorToken::PLUS => BinaryOp::FAdd,
+                    OperatorToken::MINUS => BinaryOp::FSub,
+                    OperatorToken::MUL => BinaryOp::FMul,
+                    OperatorToken::DIV => BinaryOp::FDiv,
                     _ => unreachable!(),
                 };
-
-                let temp = self.context.new_temp("Number".to_string());
-                self.context.add_line(format!(
-                    "{} = {} double {}, {}",
-                    temp, opcode, left_val.register, right_val.register
-                ));
-                GeneratorResult::new(temp, "double".to_string(),"Number".to_string())
-            }
-
-            OperatorToken::MOD => {
-                if !self.context.runtime_functions.contains("fmod") {
-                    self.context.add_global_declaration("declare double @fmod(double, double)".to_string());
-                    self.context.runtime_functions.insert("fmod".to_string());
+                if op == OperatorToken::DIV && self.context.checked {
+                    self.check_divisor(&right_val.value, node.span);
                 }
 
-                let temp = self.context.new_temp("Number".to_string());
-                self.context.add_line(format!(
-                    "{} = call double @fmod(double {}, double {})",
-                    temp, left_val.register, right_val.register
-                ));
-                GeneratorResult::new(temp, "double".to_string(),"Number".to_string())
+                let temp = self.context.builder().binary(opcode, Type::Double, left_val.value, right_val.value);
+                GeneratorResult::new(temp, Type::Double,"Number".to_string())
             }
 
-            OperatorToken::POW => {
-                if !self.context.runtime_functions.contains("pow") {
-                    self.context.add_global_declaration("declare double @pow(double, double)".to_string());
-                    self.context.runtime_functions.insert("pow".to_string());
+            OperatorToken::MOD | OperatorToken::POW => {
+                let function = if op == OperatorToken::MOD { "fmod" } else { "pow" };
+                if op == OperatorToken::MOD && self.context.checked {
+                    self.check_divisor(&right_val.value, node.span);
                 }
-
-                let temp = self.context.new_temp("Number".to_string());
-                self.context.add_line(format!(
-                    "{} = call double @pow(double {}, double {})",
-                    temp, left_val.register, right_val.register
-                ));
-                GeneratorResult::new(temp, "double".to_string(),"Number".to_string())
+                let temp = self.context.builder().call(
+                    Type::Double,
+                    global(function),
+                    vec![(Type::Double, left_val.value), (Type::Double, right_val.value)],
+                );
+                GeneratorResult::new(temp, Type::Double,"Number".to_string())
             }
 
             OperatorToken::EQ
@@ -307,80 +258,76 @@ impl Visitor<GeneratorResult> for CodeGenerator {
             | OperatorToken::GTE
             | OperatorToken::LT
             | OperatorToken::LTE => {
-                if self.context.get_type(&left_val.register) == "Boolean"
-                    && self.context.get_type(&right_val.register) == "Boolean"
-                {
+                if left_val.llvm_type == Type::I1 && right_val.llvm_type == Type::I1 {
                     let cmp_op = match op {
-                        OperatorToken::EQ => "eq",
-                        OperatorToken::NEQ => "ne",
+                        OperatorToken::EQ => IntPredicate::Eq,
+                        OperatorToken::NEQ => IntPredicate::Ne,
                         _ => panic!("Invalid comparison operator for booleans: {:?}", op),
                     };
 
-                    let temp = self.context.new_temp("Boolean".to_string());
-                    self.context.add_line(format!(
-                        "{} = icmp {} i1 {}, {}",
-                        temp, cmp_op, left_val.register, right_val.register
-                    ));
-                    GeneratorResult::new(temp, "i1".to_string(),"Boolean".to_string())
+                    let temp = self.context.builder().icmp(cmp_op, Type::I1, left_val.value, right_val.value);
+                    GeneratorResult::new(temp, Type::I1,"Boolean".to_string())
+                } else if left_val.llvm_type == Type::Ptr && right_val.llvm_type == Type::Ptr {
+                    // Strings are compared lexicographically by `strcmp`
+                    let cmp_op = match op {
+                        OperatorToken::EQ => IntPredicate::Eq,
+                        OperatorToken::NEQ => IntPredicate::Ne,
+                        OperatorToken::GT => IntPredicate::Sgt,
+                        OperatorToken::GTE => IntPredicate::Sge,
+                        OperatorToken::LT => IntPredicate::Slt,
+                        OperatorToken::LTE => IntPredicate::Sle,
+                        _ => unreachable!(),
+                    };
+
+                    let builder = self.context.builder();
+                    let order = builder.call(
+                        Type::I32,
+                        global("strcmp"),
+                        vec![(Type::Ptr, left_val.value), (Type::Ptr, right_val.value)],
+                    );
+                    let temp = builder.icmp(cmp_op, Type::I32, order, Value::Int(0));
+                    GeneratorResult::new(temp, Type::I1,"Boolean".to_string())
                 } else {
                     let cmp_op = match op {
-                        OperatorToken::EQ => "oeq",
-                        OperatorToken::NEQ => "one",
-                        OperatorToken::GT => "ogt",
-                        OperatorToken::GTE => "oge",
-                        OperatorToken::LT => "olt",
-                        OperatorToken::LTE => "ole",
+                        OperatorToken::EQ => FloatPredicate::Oeq,
+                        OperatorToken::NEQ => FloatPredicate::One,
+                        OperatorToken::GT => FloatPredicate::Ogt,
+                        OperatorToken::GTE => FloatPredicate::Oge,
+                        OperatorToken::LT => FloatPredicate::Olt,
+                        OperatorToken::LTE => FloatPredicate::Ole,
                         _ => unreachable!(),
                     };
 
-                    let temp = self.context.new_temp("Boolean".to_string());
-                    self.context.add_line(format!(
-                        "{} = fcmp {} double {}, {}",
-                        temp, cmp_op, left_val.register, right_val.register
-                    ));
-                    GeneratorResult::new(temp, "i1".to_string(),"Boolean".to_string())
+                    let temp = self.context.builder().fcmp(cmp_op, left_val.value, right_val.value);
+                    GeneratorResult::new(temp, Type::I1,"Boolean".to_string())
                 }
             }
 
             OperatorToken::AND | OperatorToken::OR => {
                 let opcode = match op {
-                    OperatorToken::AND => "and",
-                    OperatorToken::OR => "or",
+                    OperatorToken::AND => BinaryOp::And,
+                    OperatorToken::OR => BinaryOp::Or,
                     _ => unreachable!(),
                 };
 
-                let temp = self.context.new_temp("Boolean".to_string());
-                self.context.add_line(format!(
-                    "{} = {} i1 {}, {}",
-                    temp, opcode, left_val.register, right_val.register
-                ));
-                GeneratorResult::new(temp, "i1".to_string(),"Boolean".to_string())
+                let temp = self.context.builder().binary(opcode, Type::I1, left_val.value, right_val.value);
+                GeneratorResult::new(temp, Type::I1,"Boolean".to_string())
             }
 
-            OperatorToken::CONCAT => {
-                let len1 = self.context.new_temp("Number".to_string());
-                let len2 = self.context.new_temp("Number".to_string());
-                let total_len = self.context.new_temp("Number".to_string());
-                let total_len_plus_one = self.context.new_temp("Number".to_string());
-                let result_ptr = self.context.new_temp("String".to_string());
-                let copy_reg = self.context.new_temp("ptr".to_string());
-                let result = self.context.new_temp("String".to_string());
-                self.context.add_line(
-                    format!(
-                        "{} = call i32 @strlen(ptr {})\n
-                        {} = call i32 @strlen(ptr {})\n
-                        {} = add i32 {}, {}\n
-                        {} = add i32 {}, 1\n
-                        {} = call ptr @malloc(i32 {})\n
-                        {} = call ptr @strcpy(ptr {}, ptr {})\n 
-                        {} = call ptr @strcat(ptr {}, ptr {})\n
-                        ",len1,left_val.register,len2, right_val.register,
-                        total_len,len1,len2,total_len_plus_one,total_len,
-                        result_ptr,total_len_plus_one,copy_reg,result_ptr,left_val.register,
-                        result,result_ptr,right_val.register
-                    )
+            OperatorToken::CONCAT | OperatorToken::DCONCAT => {
+                let mut right = right_val.value;
+                if op == OperatorToken::DCONCAT {
+                    // `a @@ b` is `a @ (" " @ b)`, the left operand is already rooted
+                    let space = self.context.module.add_string(" ");
+                    right = self.context.builder().call(Type::Ptr, global("concat"), vec![(Type::Ptr, space), (Type::Ptr, right)]);
+                }
+                let result = self.context.builder().call(
+                    Type::Ptr,
+                    global("concat"),
+                    vec![(Type::Ptr, left_val.value), (Type::Ptr, right)],
                 );
-                GeneratorResult::new(result, "ptr".to_string(),"String".to_string())
+                self.gc_release(roots);
+                GeneratorResult::new(result, Type::Ptr,"String".to_string())
             }
 
             _ => panic!("Unsupported binary operator: {:?}", op),
@@ -388,18 +335,16 @@ impl Visitor<GeneratorResult> for CodeGenerator {
     }
 
     fn visit_unary_op(&mut self, node: &mut UnaryOpNode) -> GeneratorResult {
-        let operand_val = node.operand.accept(self);
+        let operand_val = self.generate_expression(&mut node.operand);
         let op = node.operator.clone();
         match op {
             OperatorToken::NEG => {
-                let temp = self.context.new_temp("Number".to_string());
-                self.context.add_line(format!("{} = fsub double 0.0, {}", temp, operand_val.register));
-                GeneratorResult::new(temp, "double".to_string(),"Number".to_string())
+                let temp = self.context.builder().binary(BinaryOp::FSub, Type::Double, Value::Double(0.0), operand_val.value);
+                GeneratorResult::new(temp, Type::Double,"Number".to_string())
             }
             OperatorToken::NOT => {
-                let temp = self.context.new_temp("Boolean".to_string());
-                self.context.add_line(format!("{} = xor i1 {}, true", temp, operand_val.register));
-                GeneratorResult::new(temp, "i1".to_string(),"Boolean".to_string())
+                let temp = self.context.builder().binary(BinaryOp::Xor, Type::I1, operand_val.value, Value::Bool(true));
+                GeneratorResult::new(temp, Type::I1,"Boolean".to_string())
             }
             _ => panic!("Unsupported unary operator: {:?}", op),
         }
@@ -408,119 +353,95 @@ impl Visitor<GeneratorResult> for CodeGenerator {
     fn visit_if_else(&mut self, node: &mut IfElseNode) -> GeneratorResult {
         let node_type = node.node_type.clone().unwrap().type_name;
         let node_type_llvm = to_llvm_type(node_type.clone());
-        let result_reg = self.context.new_temp(node_type.clone());
-        let exit_id = self.context.new_id();
-        let exit_label = format!("if_else_exit.{}", exit_id);
-        self.context.add_line(format!("{} = alloca {}", result_reg, node_type_llvm));
-        let cond_reg = node.condition.accept(self);
-        let if_id = self.context.new_id();
-        let if_true_label = format!("if_true.{}", if_id);
-        let if_false_label = format!("if_false.{}", if_id);
-        self.context.add_line(format!(
-            "br i1 {}, label %{}, label %{}",
-            cond_reg.register, if_true_label, if_false_label
-        ));
-        self.context.add_line(format!("{}:", if_true_label));
-        let if_expr = node.if_expression.accept(self);
-        self.context.add_line(format!(
-            "store {} {}, ptr {}\n",
-            node_type_llvm, if_expr.register, result_reg
-        ));
-        self.context.add_line(format!("br label %{}\n\n", exit_label));
-        self.context.add_line(format!("{}:", if_false_label));
-        if node.elifs.len() > 0 {
-            for (cond, expr) in node.elifs.iter_mut() {
-                let elif_id = self.context.new_id();
-                let elif_label = format!("elif_true.{}", elif_id);
-                let elif_false_label = format!("elif_false.{}", elif_id);
-                let elif_cond_reg = if let Some(cond_expr) = cond {
-                    cond_expr.accept(self)
-                } else {
-                    let else_reg = self.context.new_temp("Boolean".to_string());
-                    self.context.add_line(format!(
-                        "{} = add i1 0, 1", else_reg
-                    ));
-                    GeneratorResult::new(else_reg, "i1".to_string(),"Boolean".to_string())
-                };
-                if let Some(_) = cond {
-                    self.context.add_line(format!(
-                        "br i1 {}, label %{}, label %{}",
-                        elif_cond_reg.register, elif_label, elif_false_label
-                    ));
-                } else {
-                    self.context.add_line(format!("br label %{}\n\n", elif_label));
-                }
-                self.context.add_line(format!("{}:", elif_label));
-                let elif_expr = expr.clone().accept(self);
-                self.context.add_line(format!(
-                    "store {} {}, ptr {}\n",
-                    node_type_llvm, elif_expr.register, result_reg
-                ));
-                if let Some(_) = cond {
-                    self.context.add_line(format!("br label %{}\n\n", exit_label));
-                }
-                
-                if let Some(_) = cond {
-                    self.context.add_line(format!("{}:", elif_false_label));
-                } else {
-                    self.context.add_line(format!("br label %{}\n\n", exit_label));
-                }
+        let builder = self.context.builder();
+        let result_reg = builder.alloca(node_type_llvm.clone(), None);
+        let exit_label = builder.new_block("if_else_exit");
+        let cond_reg = self.generate_expression(&mut node.condition);
+        let builder = self.context.builder();
+        let if_true_label = builder.new_block("if_true");
+        let if_false_label = builder.new_block("if_false");
+        builder.cond_br(cond_reg.value, if_true_label, if_false_label);
+        builder.position_at_end(if_true_label);
+        let if_expr = self.generate_expression(&mut node.if_expression);
+        let if_value = self.branch_value(&if_expr, &node_type_llvm);
+        let builder = self.context.builder();
+        builder.store(node_type_llvm.clone(), if_value, result_reg.clone());
+        builder.br(exit_label);
+        builder.position_at_end(if_false_label);
+        let has_else = matches!(node.elifs.last(), Some((None, _)));
+        for (cond, expr) in node.elifs.iter_mut() {
+            if let Some(cond_expr) = cond {
+                let elif_cond_reg = self.generate_expression(cond_expr);
+                let builder = self.context.builder();
+                let elif_label = builder.new_block("elif_true");
+                let elif_false_label = builder.new_block("elif_false");
+                builder.cond_br(elif_cond_reg.value, elif_label, elif_false_label);
+                builder.position_at_end(elif_label);
+                let elif_expr = self.generate_expression(expr);
+                let elif_value = self.branch_value(&elif_expr, &node_type_llvm);
+                let builder = self.context.builder();
+                builder.store(node_type_llvm.clone(), elif_value, result_reg.clone());
+                builder.br(exit_label);
+                builder.position_at_end(elif_false_label);
+            } else {
+                let else_expr = self.generate_expression(expr);
+                let else_value = self.branch_value(&else_expr, &node_type_llvm);
+                let builder = self.context.builder();
+                builder.store(node_type_llvm.clone(), else_value, result_reg.clone());
+                builder.br(exit_label);
             }
-        } else {
-            self.context.add_line(format!("br label %{}\n\n", exit_label));
         }
-        self.context.add_line(format!("{}:", exit_label));
-        let final_result = self.context.new_temp(node_type.clone());
-        self.context.add_line(format!(
-            "{} = load {}, ptr {}\n",
-            final_result, node_type_llvm, result_reg
-        ));
+        let builder = self.context.builder();
+        if !has_else {
+            // No branch was taken: the expression evaluates to the default value of its type
+            builder.store(node_type_llvm.clone(), Value::default_of(&node_type_llvm), result_reg.clone());
+            builder.br(exit_label);
+        }
+        builder.position_at_end(exit_label);
+        let final_result = builder.load(node_type_llvm.clone(), result_reg);
         GeneratorResult::new(final_result, node_type_llvm,node_type)
     }
 
     fn visit_let_in(&mut self, node: &mut LetInNode) -> GeneratorResult {
         self.context.enter_scope();
-        for assig in node.assignments.clone().iter_mut() {
+        let parent_scope = self.debug_enter_block(node.span);
+        let mut roots = None;
+        for assig in node.assignments.iter_mut() {
             let identifier = assig.identifier.clone();
-            let body = assig.expression.accept(self);
+            let body = self.generate_expression(&mut assig.expression);
             let llvm_type = to_llvm_type(body.ast_type.clone());
-            let register_name = format!("%{}.{}", identifier, self.context.get_scope());
-            self.context.temp_types.insert(register_name.clone(), assig.node_type.clone().unwrap().type_name.clone());
-            self.context.add_variable(register_name.clone(), llvm_type.clone());
-            self.context.add_line(format!(
-                "{} = alloca {}",
-                register_name.clone(), llvm_type
-            ));
-            self.context.add_line(format!(
-                "store {} {}, ptr {}",
-                llvm_type, body.register, register_name
-            ));
+            let builder = self.context.builder();
+            let register_name = builder.alloca(llvm_type.clone(), Some(&identifier));
+            builder.store(llvm_type.clone(), body.value, register_name.clone());
+            self.debug_variable(&identifier, &body.ast_type, assig.span, &register_name, None);
+            self.context.add_variable(identifier, register_name.clone(), llvm_type.clone());
+            self.gc_root(&register_name, &llvm_type, &mut roots);
         }
-        let body_result = node.body.accept(self);
+        let body_result = self.generate_expression(&mut node.body);
+        self.gc_release(roots);
+        self.debug_exit_block(parent_scope);
         self.context.exit_scope();
-        GeneratorResult::new(body_result.register, body_result.llvm_type, body_result.ast_type)
+        GeneratorResult::new(body_result.value, body_result.llvm_type, body_result.ast_type)
     }
 
     fn visit_destructive_assign(&mut self, node: &mut DestructiveAssignNode) -> GeneratorResult {
-        let expr_result = node.expression.accept(self);
+        let expr_result = self.generate_expression(&mut node.expression);
         match *node.identifier.clone() {
             Expression::Identifier(id) => {
-                let identifier_register = self.context.get_variable(id.value.clone());
-                self.context.add_line(format!(
-                    "store {} {}, ptr {}",
-                    expr_result.llvm_type, expr_result.register, identifier_register
-                ));
+                let variable = self.context.get_variable(&id.value);
+                self.context.builder().store(expr_result.llvm_type.clone(), expr_result.value.clone(), variable.slot);
             }
             Expression::TypePropAccess(obj) => {
                 let obj_type = self.context.current_self.clone().unwrap();
-                let prop_reg = self.context.new_temp(obj_type.clone());
-                let prop_index = self.context.type_members_ids.get(&(obj_type.clone(), (*obj.member).clone())).unwrap();
-                self.context.add_line(format!("{} = getelementptr %{}_type ,ptr %self.{}, i32 0 , i32 {}", prop_reg,obj_type.clone(),self.context.get_scope(),prop_index.clone()));
-                self.context.add_line(format!("store {} {}, ptr {}" , expr_result.llvm_type, expr_result.register, prop_reg));
+                let prop_index = *self.context.type_members_ids.get(&(obj_type.clone(), (*obj.member).clone())).unwrap();
+                let self_value = self.context.self_value.clone().expect("Property assignment outside a method");
+                let builder = self.context.builder();
+                let prop_reg = builder.struct_gep(Type::Named(format!("{}_type", obj_type)), self_value, prop_index as u32);
+                builder.store(expr_result.llvm_type.clone(), expr_result.value.clone(), prop_reg);
             }
             _ => panic!("Error: assigment not possible")
         }
-        GeneratorResult::new(expr_result.register, expr_result.llvm_type, expr_result.ast_type)
+        GeneratorResult::new(expr_result.value, expr_result.llvm_type, expr_result.ast_type)
     }
 
     fn visit_type_def(&mut self, node: &mut TypeDefNode) -> GeneratorResult {
@@ -539,117 +460,143 @@ impl Visitor<GeneratorResult> for CodeGenerator {
             }
         }
         self.context.current_self = None;
-        GeneratorResult::new(format!("%{}_type",type_name.clone()), format!("%{}_type",type_name.clone()), type_name.clone())
+        GeneratorResult::new(global(&format!("{}_new", type_name)), Type::Named(format!("{}_type",type_name.clone())), type_name.clone())
 
     }
 
     fn visit_type_instance(&mut self, node: &mut TypeInstanceNode) -> GeneratorResult { 
-        let type_constructor = format!("@{}_new", node.type_name);
-        let llvm_args: Vec<String> = node.arguments.iter().map(|arg| {
-            let arg_val = arg.clone().accept(self);
-            let arg_reg = self.context.new_temp(arg_val.llvm_type.clone());
-            self.context.add_line(format!("{} = alloca {}", arg_reg.clone(), arg_val.llvm_type));
-            self.context.add_line(format!(
-                "store {} {}, ptr {}",
-                arg_val.llvm_type, arg_val.register, arg_reg.clone()
-            ));
-            format!("ptr {}", arg_reg)
-        }).collect();
-        let args_str = llvm_args.join(", ");
-        let result = self.context.new_temp(node.node_type.clone().unwrap().type_name);
-        self.context.add_line(format!(
-            "{} = call ptr {}({})",
-            result.clone(), type_constructor, args_str
-        ));
-        GeneratorResult::new(result.clone(), "ptr".to_string(),node.type_name.clone())
+        let type_constructor = format!("{}_new", node.type_name);
+        let mut roots = None;
+        let mut llvm_args = Vec::new();
+        for arg in node.arguments.iter_mut() {
+            let arg_val = self.generate_expression(arg);
+            let builder = self.context.builder();
+            let arg_reg = builder.alloca(arg_val.llvm_type.clone(), None);
+            builder.store(arg_val.llvm_type.clone(), arg_val.value, arg_reg.clone());
+            self.gc_root(&arg_reg, &arg_val.llvm_type, &mut roots);
+            llvm_args.push((Type::Ptr, arg_reg));
+        }
+        let result = self.context.builder().call(Type::Ptr, global(&type_constructor), llvm_args);
+        self.gc_release(roots);
+        GeneratorResult::new(result, Type::Ptr,node.type_name.clone())
     }
 
     fn visit_type_function_access(&mut self, node: &mut TypeFunctionAccessNode) -> GeneratorResult {
-        let object = node.object.accept(self); 
+        let object = self.generate_expression(&mut node.object); 
+        if object.ast_type == "String" {
+            return self.generate_string_method(object, node);
+        }
 
         let mut curr_object_type = object.ast_type.clone();
         let function_name = node.member.function_name.clone();
-        let mut curr_type_reg_ptr = object.register.clone();
+        let mut curr_type_reg_ptr = object.value.clone();
 
         while ! self.context.type_functions_ids.contains_key(&(curr_object_type.clone(),function_name.clone())) {
             let parent_opt = {
                 self.context.inherits.get(&curr_object_type.clone()).cloned()
             };
             if let Some(parent) = parent_opt {
-                let parent_ptr_ptr = self.context.new_temp("ptr".to_string());
-                self.context.add_line(format!("{} = getelementptr %{}_type, ptr {}, i32 0, i32 1", parent_ptr_ptr.clone(),curr_object_type.clone(), curr_type_reg_ptr.clone()));
-                let parent_ptr = self.context.new_temp("ptr".to_string());
-                self.context.add_line(format!("{} = load ptr, ptr {}", parent_ptr.clone(), parent_ptr_ptr.clone()));
+                let builder = self.context.builder();
+                let parent_ptr_ptr = builder.struct_gep(Type::Named(format!("{}_type", curr_object_type)), curr_type_reg_ptr.clone(), 1);
+                let parent_ptr = builder.load(Type::Ptr, parent_ptr_ptr);
                 curr_object_type = parent; 
-                curr_type_reg_ptr = parent_ptr.clone();
+                curr_type_reg_ptr = parent_ptr;
             } else {
                 panic!("Method not found.")
             }
         }
 
         let function_index = *self.context.type_functions_ids.get(&(curr_object_type.clone(), node.member.function_name.clone())).unwrap();
-        let type_id_ptr = self.context.new_temp("ptr".to_string());
-        self.context.add_line(format!("{} = getelementptr %{}_type, ptr {}, i32 0, i32 0", type_id_ptr, curr_object_type.clone(), curr_type_reg_ptr.clone()));
-        let type_id = self.context.new_temp("i32".to_string());
-        self.context.add_line(format!("{} = load i32, ptr {}", type_id, type_id_ptr));
-        let func_ptr = self.context.new_temp("ptr".to_string());
-        self.context.add_line(format!("{} = call ptr @get_vtable_method(i32 {},i32 {})", func_ptr, type_id, function_index));
-        
+        let builder = self.context.builder();
+        let type_id_ptr = builder.struct_gep(Type::Named(format!("{}_type", curr_object_type)), curr_type_reg_ptr.clone(), 0);
+        let type_id = builder.load(Type::I32, type_id_ptr);
+        let func_ptr = builder.call(
+            Type::Ptr,
+            global("get_vtable_method"),
+            vec![(Type::I32, type_id), (Type::I32, Value::Int(function_index as i64))],
+        );
+        let missing = builder.icmp(IntPredicate::Eq, Type::Ptr, func_ptr.clone(), Value::Null);
+        let message = format!("Method '{}' is not defined for type '{}'", function_name, curr_object_type);
+        self.runtime_check(missing, &message, node.span);
+
         let return_type = node.node_type.clone().unwrap().type_name;
         let return_llvm = to_llvm_type(return_type.clone());
-        let mut llvm_args: Vec<String> = Vec::new();
+        let mut llvm_args = vec![(Type::Ptr, curr_type_reg_ptr.clone())];
+        // The receiver must survive the allocations made while evaluating the arguments
+        let mut roots = None;
+        self.gc_spill(&GeneratorResult::new(curr_type_reg_ptr, Type::Ptr, curr_object_type), &mut roots);
         for arg in node.member.arguments.iter_mut() {
-            let arg_val = arg.accept(self);
-            let arg_reg = self.context.new_temp(arg_val.llvm_type.clone());
-            self.context.add_line(format!("{} = alloca {}", arg_reg.clone(), arg_val.llvm_type));
-            self.context.add_line(format!(
-                "store {} {}, ptr {}",
-                arg_val.llvm_type, arg_val.register, arg_reg.clone()
-            ));
-            llvm_args.push(format!("ptr {}", arg_reg));
+            let arg_val = self.generate_expression(arg);
+            let builder = self.context.builder();
+            let arg_reg = builder.alloca(arg_val.llvm_type.clone(), None);
+            builder.store(arg_val.llvm_type.clone(), arg_val.value, arg_reg.clone());
+            self.gc_root(&arg_reg, &arg_val.llvm_type, &mut roots);
+            llvm_args.push((Type::Ptr, arg_reg));
         }
-        llvm_args.insert(0, format!("ptr {}", curr_type_reg_ptr.clone()));
-        let temp = self.context.new_temp(return_type.clone());
-        self.context.add_line(format!(
-            "{} = call {} {}({})",
-            temp.clone(), return_llvm, func_ptr, llvm_args.join(", ")
-        ));
-        GeneratorResult::new(temp, to_llvm_type(node.member.node_type.clone().unwrap().type_name), node.member.node_type.clone().unwrap().type_name)
+        let temp = self.context.builder().call(return_llvm, func_ptr, llvm_args);
+        self.gc_release(roots);
+        let member_type = node.member.node_type.clone().unwrap().type_name;
+        GeneratorResult::new(temp, to_llvm_type(member_type.clone()), member_type)
     }
 
     fn visit_type_prop_access(&mut self, node: &mut TypePropAccessNode) -> GeneratorResult {
-        let object = node.object.accept(self);
-        let member_index = self.context.type_members_ids.get(&(object.ast_type.clone(), (*node.member).clone())).unwrap().clone();
-        let node_type = to_llvm_type(node.node_type.clone().unwrap().type_name);
-        let ptr_temp = self.context.new_temp(to_llvm_type(node_type.clone()));
-        self.context.add_line(format!("{} = getelementptr %{}_type, ptr %self.{}, i32 0 , i32 {}", ptr_temp, object.ast_type.clone(), self.context.get_scope(), member_index));
-        let result = self.context.new_temp(node_type.clone());
-        self.context.add_line(format!("{} = load {}, ptr {}", result.clone(), node_type.clone(), ptr_temp.clone()));
-        GeneratorResult::new(result, node_type.clone(), node.node_type.clone().unwrap().type_name)
+        let object = self.generate_expression(&mut node.object);
+        let member_index = *self.context.type_members_ids.get(&(object.ast_type.clone(), (*node.member).clone())).unwrap();
+        let type_name = node.node_type.clone().unwrap().type_name;
+        let node_type = to_llvm_type(type_name.clone());
+        let builder = self.context.builder();
+        let ptr_temp = builder.struct_gep(Type::Named(format!("{}_type", object.ast_type)), object.value, member_index as u32);
+        let result = builder.load(node_type.clone(), ptr_temp);
+        GeneratorResult::new(result, node_type, type_name)
     }
     
     fn visit_print(&mut self, node: &mut PrintNode) -> GeneratorResult {
-        let arg = node.expression.accept(self);
-        let call_reg = self.context.new_temp("i32".to_string());
-        let new_line_reg = self.context.new_temp("ptr".to_string());
-        let call_new_line = self.context.new_temp("ptr".to_string());
-        let id = self.context.new_id();
-        let new_line = format!("{} = getelementptr [2 x i8], ptr @.newline, i32 0, i32 0", new_line_reg);
-        let new_line2 = format!("{} = call i32 (ptr, ...) @printf(ptr {})", call_new_line, new_line_reg);
-        if arg.llvm_type == "i1" {
-            self.context.add_line(format!("%bool_ptr{} = select i1 {}, ptr @.true_str, ptr @.false_str", id ,arg.register));
-            self.context.add_line(format!("{} = call i32 (ptr, ...) @printf(ptr %bool_ptr{})", call_reg,id));
-        } else if arg.llvm_type == "double" {
-            self.context.add_line(format!("%fmt_dbl_ptr{} = getelementptr [4 x i8], ptr @.str.f, i32 0, i32 0", id)); 
-            self.context.add_line(format!("{} = call i32 (ptr, ...) @printf(ptr %fmt_dbl_ptr{}, double {})", call_reg, id, arg.register));
-        } else if arg.llvm_type == "ptr" {
-            self.context.add_line(format!("{} = call i32 (ptr, ...) @printf(ptr {})", call_reg, arg.register));
-            self.context.add_line(new_line);
-            self.context.add_line(new_line2);
-        } else {
-            panic!("Unsupported expression type for print: {:?}", node.expression);
+        let arg = self.generate_expression(&mut node.expression);
+        let builder = self.context.builder();
+        let printf = global("printf");
+        match arg.llvm_type {
+            Type::I1 => {
+                let bool_ptr = builder.select(arg.value.clone(), Type::Ptr, global(".true_str"), global(".false_str"));
+                builder.call_variadic(Type::I32, printf, vec![Type::Ptr], vec![(Type::Ptr, bool_ptr)]);
+            }
+            Type::Double => {
+                let text = builder.call(Type::Ptr, global("hulk_num_to_str"), vec![(Type::Double, arg.value.clone())]);
+                builder.call_variadic(Type::I32, printf, vec![Type::Ptr], vec![
+                    (Type::Ptr, global(".str.s")),
+                    (Type::Ptr, text),
+                ]);
+            }
+            Type::Ptr => {
+                // The string is an argument, not the format, so `%` is printed as is
+                builder.call_variadic(Type::I32, printf, vec![Type::Ptr], vec![
+                    (Type::Ptr, global(".str.s")),
+                    (Type::Ptr, arg.value.clone()),
+                ]);
+            }
+            _ => panic!("Unsupported expression type for print: {:?}", node.expression),
         }
-        GeneratorResult::new(arg.register, arg.llvm_type, node.node_type.clone().unwrap().type_name)
+        GeneratorResult::new(arg.value, arg.llvm_type, node.node_type.clone().unwrap().type_name)
+    }
+
+    fn visit_read(&mut self, node: &mut ReadNode) -> GeneratorResult {
+        let type_name = node.node_type.clone().unwrap().type_name;
+        let builder = self.context.builder();
+        let value = match node.kind {
+            ReadKind::Line => builder.call(Type::Ptr, global("hulk_read_line"), vec![]),
+            ReadKind::Number => {
+                let number = builder.call(Type::Double, global("hulk_read_number"), vec![]);
+                // Only NaN is not equal to itself
+                let is_number = builder.fcmp(FloatPredicate::Oeq, number.clone(), number.clone());
+                let failed = builder.binary(BinaryOp::Xor, Type::I1, is_number, Value::Bool(true));
+                self.runtime_check(failed, "Input is not a valid Number", node.span);
+                number
+            }
+            ReadKind::Eof => {
+                let eof = builder.call(Type::I32, global("hulk_eof"), vec![]);
+                builder.icmp(IntPredicate::Ne, Type::I32, eof, Value::Int(0))
+            }
+        };
+        GeneratorResult::new(value, to_llvm_type(type_name.clone()), type_name)
     }
 
 }
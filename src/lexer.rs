@@ -0,0 +1,137 @@
+//! HULK lexer, generated by the in-tree `lexer_generator` crate.
+//!
+//! The token set is listed in `lexer.tokens`, which the build script compiles
+//! into static DFA tables, so nothing is built at startup. The lexemes are
+//! handed to the LALRPOP parser, which declares them as `extern` tokens in
+//! `parser.lalrpop`. Comments are kept apart, as trivia.
+
+use std::fmt;
+
+use crate::tokens::{LexicalError, Span};
+
+/// The lexer generated from `lexer.tokens` by the build script: the token
+/// kinds, and the tables of the minimal DFA with the functions that read them.
+mod generated {
+    #![allow(dead_code)]
+    include!(concat!(env!("OUT_DIR"), "/lexer.rs"));
+}
+
+pub use generated::TokenKind;
+
+/// A token of the input, the text it was read from included.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Token<'input> {
+    pub kind: TokenKind,
+    pub text: &'input str,
+}
+
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// A token with its byte span, or the lexical error found in its place.
+pub type Spanned<'input> = Result<(usize, Token<'input>, usize), LexicalError>;
+
+/// A comment of the input, kept as trivia so tools such as a formatter can
+/// put it back.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comment<'input> {
+    /// The whole comment, delimiters included.
+    pub text: &'input str,
+    pub span: Span,
+}
+
+/// The result of lexing an input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tokens<'input> {
+    /// Tokens for the parser, ending at the first unterminated comment.
+    pub tokens: Vec<Spanned<'input>>,
+    pub comments: Vec<Comment<'input>>,
+}
+
+/// Splits HULK source code into the tokens of `parser.lalrpop`.
+pub struct HulkLexer;
+
+impl HulkLexer {
+    pub fn new() -> Self {
+        HulkLexer
+    }
+
+    /// Tokens of `input`, in the form expected by the generated parser, and
+    /// its comments.
+    ///
+    /// Comments are not tokens: `//` runs to the end of the line and `/* */`
+    /// may be nested, which no regex can describe, so they are skipped here
+    /// before asking the DFA for the next token.
+    pub fn tokenize<'input>(&self, input: &'input str) -> Tokens<'input> {
+        let mut tokens = Tokens { tokens: Vec::new(), comments: Vec::new() };
+        let mut offset = 0;
+        while offset < input.len() {
+            let rest = &input[offset..];
+            if rest.starts_with("//") {
+                let end = rest.find('\n').map_or(input.len(), |index| offset + index);
+                tokens.comments.push(Comment { text: &input[offset..end], span: Span::new(offset, end) });
+                offset = end;
+                continue;
+            }
+            if rest.starts_with("/*") {
+                let Some(end) = block_comment_end(rest).map(|length| offset + length) else {
+                    tokens.tokens.push(Err(LexicalError {
+                        message: "Unterminated block comment".to_string(),
+                        offset,
+                    }));
+                    break;
+                };
+                tokens.comments.push(Comment { text: &input[offset..end], span: Span::new(offset, end) });
+                offset = end;
+                continue;
+            }
+            let (end, kind, ignore) = match generated::longest_match(0, input, offset) {
+                Some((end, rule)) => (end, rule.kind, rule.ignore),
+                // Unreachable while the error token matches any character
+                None => (offset + rest.chars().next().unwrap().len_utf8(), TokenKind::Error, false),
+            };
+            let token = Token { kind, text: &input[offset..end] };
+            match kind {
+                _ if ignore => {}
+                // A lone quote starts a string literal that never ends
+                TokenKind::Error if token.text == "\"" => tokens.tokens.push(Err(LexicalError {
+                    message: "Unterminated string literal".to_string(),
+                    offset,
+                })),
+                TokenKind::Error => tokens.tokens.push(Err(LexicalError {
+                    message: format!("Unexpected character '{}'", token.text),
+                    offset,
+                })),
+                _ => tokens.tokens.push(Ok((offset, token, end))),
+            }
+            offset = end;
+        }
+        tokens
+    }
+}
+
+/// Length of the block comment at the start of `text`, closing delimiter
+/// included, or `None` if it is not closed.
+fn block_comment_end(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut index = 0;
+    while index < text.len() {
+        let rest = &text[index..];
+        if rest.starts_with("/*") {
+            depth += 1;
+            index += 2;
+        } else if rest.starts_with("*/") {
+            depth -= 1;
+            index += 2;
+            if depth == 0 {
+                return Some(index);
+            }
+        } else {
+            index += rest.chars().next().unwrap().len_utf8();
+        }
+    }
+    None
+}
@@ -0,0 +1,149 @@
+//! Scans a large HULK file with a lexer for the HULK token set.
+//!
+//! Run with `cargo bench --bench scan`. The input is the concatenation of the
+//! example programs of the compiler, repeated until it reaches a few megabytes.
+//!
+//! As a baseline, the same DFA is also scanned through per-state maps of sorted
+//! character ranges, the way transitions were stored before the dense class table.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use lexer_generator::RegexParser;
+use lexer_generator::automata::{LexerDFA, LexerNFA, NfaBuild};
+use lexer_generator::lexer::{Lexer, TokenSpec};
+
+const KEYWORDS: &[&str] = &[
+    "function", "type", "inherits", "new", "let", "in", "if", "elif", "else", "while", "for", "range", "true",
+    "false", "print",
+];
+
+const SYMBOLS: &[&str] = &[
+    r"\+", r"\-", r"\*", "/", "%", r"\^", "@", "@@", "==", "!=", ">", ">=", "<", "<=", "!", "&", r"\|", "=",
+    ":=", r"\.", "=>", ",", ":", ";", r"\(", r"\)", r"\{", r"\}",
+];
+
+fn specs() -> Vec<TokenSpec<String>> {
+    let mut specs = KEYWORDS
+        .iter()
+        .chain(SYMBOLS)
+        .map(|pattern| TokenSpec::build(pattern.to_string(), *pattern))
+        .collect::<Vec<_>>();
+    specs.push(TokenSpec::build("IDENTIFIER".to_string(), r"[a-zA-Z]\w*"));
+    specs.push(TokenSpec::build("NUMBER".to_string(), r"\d+(\.\d+)?"));
+    specs.push(TokenSpec::build("STRING".to_string(), r#""[^"]*""#));
+    specs.push(TokenSpec::build_ignorable("WHITESPACE".to_string(), r"\s+"));
+    specs.push(TokenSpec::build("ERROR".to_string(), "."));
+    specs
+}
+
+fn input() -> String {
+    let examples = concat!(env!("CARGO_MANIFEST_DIR"), "/../examples");
+    let mut paths = std::fs::read_dir(examples)
+        .expect("Failed to read the examples")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "hulk"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    let programs = paths
+        .iter()
+        .map(|path| std::fs::read_to_string(path).unwrap())
+        .collect::<Vec<_>>()
+        .join("\n");
+    programs.repeat((4 << 20) / programs.len() + 1)
+}
+
+/// The transitions of a DFA as sorted character ranges per state, searched by bisection.
+struct RangeMaps<'d> {
+    dfa: &'d LexerDFA<String>,
+    transitions: HashMap<usize, Vec<(char, char, usize)>>,
+}
+
+impl<'d> RangeMaps<'d> {
+    fn new(dfa: &'d LexerDFA<String>) -> Self {
+        let transitions = (0..dfa.state_count()).map(|state| (state, dfa.transitions_from(state))).collect();
+        RangeMaps { dfa, transitions }
+    }
+
+    fn next_state(&self, state: usize, ch: char) -> Option<usize> {
+        let ranges = self.transitions.get(&state)?;
+        let index = ranges.partition_point(|&(_, end, _)| end < ch);
+        ranges.get(index).filter(|&&(start, _, _)| start <= ch).map(|&(_, _, target)| target)
+    }
+
+    fn longest_match(&self, input: &str, start: usize) -> Option<(usize, &String)> {
+        let mut state = self.dfa.start_state;
+        let mut last_match = None;
+        for (index, ch) in input[start..].char_indices() {
+            let Some(next_state) = self.next_state(state, ch) else {
+                break;
+            };
+            state = next_state;
+            if let Some(kind) = &self.dfa.accepting_states[state] {
+                last_match = Some((start + index + ch.len_utf8(), kind));
+            }
+        }
+        last_match
+    }
+}
+
+/// Counts the lexemes of `input` found by `longest_match`, skipping unexpected characters.
+fn count_lexemes<'k>(input: &str, longest_match: impl Fn(&str, usize) -> Option<(usize, &'k String)>) -> usize {
+    let mut count = 0;
+    let mut offset = 0;
+    while offset < input.len() {
+        offset = match longest_match(input, offset) {
+            Some((end, _)) => {
+                count += 1;
+                end
+            }
+            None => offset + input[offset..].chars().next().unwrap().len_utf8(),
+        };
+    }
+    count
+}
+
+/// Runs `scan` a few times over `input`, prints its throughput and returns its mean time.
+fn measure(name: &str, input: &str, mut scan: impl FnMut() -> usize) -> Duration {
+    let iterations = 5;
+    let mut tokens = 0;
+    let start = Instant::now();
+    for _ in 0..iterations {
+        tokens = scan();
+    }
+    let elapsed = start.elapsed() / iterations;
+    println!(
+        "{}: scanned {:.1} MiB ({} tokens) in {:.2?}: {:.1} MiB/s",
+        name,
+        input.len() as f64 / (1 << 20) as f64,
+        tokens,
+        elapsed,
+        input.len() as f64 / (1 << 20) as f64 / elapsed.as_secs_f64()
+    );
+    elapsed
+}
+
+fn main() {
+    let start = Instant::now();
+    let lexer = Lexer::new(specs()).unwrap();
+    println!("Built the lexer in {:.2?}", start.elapsed());
+
+    let parser = RegexParser::new();
+    let tagged_automata = specs()
+        .iter()
+        .map(|spec| (NfaBuild::new().build_from_regex(&parser.parse(&spec.patt).unwrap()), spec.kind.clone()))
+        .collect::<Vec<_>>();
+    let dfa = LexerDFA::new(&LexerNFA::new(&tagged_automata));
+    println!("{} states, {} character classes", dfa.state_count(), dfa.class_count);
+    let range_maps = RangeMaps::new(&dfa);
+
+    let input = input();
+    measure("Lexer::split", &input, || lexer.split(&input).expect("The examples should lex").len());
+    let dense = measure("Dense class table", &input, || {
+        count_lexemes(&input, |input, start| dfa.longest_match(input, start))
+    });
+    let ranges = measure("Range maps (baseline)", &input, || {
+        count_lexemes(&input, |input, start| range_maps.longest_match(input, start))
+    });
+    println!("The dense class table scans {:.1}x as fast", ranges.as_secs_f64() / dense.as_secs_f64());
+}
@@ -33,6 +33,80 @@ impl CharSet {
         range.sort();
         CharSet { range, neg }
     }
+
+    /// The digits `[0-9]`, written `\d`.
+    pub fn digit() -> Self {
+        CharSet::new(vec![('0', '9')], false)
+    }
+
+    /// The word characters `[0-9A-Z_a-z]`, written `\w`.
+    pub fn word() -> Self {
+        CharSet::new(vec![('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')], false)
+    }
+
+    /// The ASCII whitespace characters (space, `\t`, `\n`, vertical tab, form feed
+    /// and `\r`), written `\s`.
+    pub fn space() -> Self {
+        CharSet::new(vec![(' ', ' '), ('\t', '\r')], false)
+    }
+
+    /// The set of the characters that do not match this one, as `\D` is to `\d`.
+    pub fn negated(self) -> Self {
+        CharSet { neg: !self.neg, ..self }
+    }
+
+    /// Returns the sorted, disjoint and non-adjacent ranges of the characters that
+    /// match the set, taking the negation flag into account.
+    pub fn ranges(&self) -> Vec<(char, char)> {
+        let mut ranges = self
+            .range
+            .iter()
+            .map(|&(start, end)| (start.min(end), start.max(end)))
+            .collect::<Vec<_>>();
+        ranges.sort();
+
+        let mut merged: Vec<(char, char)> = Vec::new();
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some(last) if next_char(last.1).is_none_or(|next| start <= next) => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        if !self.neg {
+            return merged;
+        }
+
+        let mut complement = Vec::new();
+        let mut next = Some('\0');
+        for (start, end) in merged {
+            if let Some(from) = next.filter(|from| *from < start) {
+                complement.push((from, prev_char(start).unwrap()));
+            }
+            next = next_char(end);
+        }
+        if let Some(from) = next {
+            complement.push((from, char::MAX));
+        }
+        complement
+    }
+}
+
+/// The character that follows `c`, skipping the surrogate code points.
+pub fn next_char(c: char) -> Option<char> {
+    match c {
+        '\u{D7FF}' => Some('\u{E000}'),
+        char::MAX => None,
+        _ => char::from_u32(c as u32 + 1),
+    }
+}
+
+/// The character that precedes `c`, skipping the surrogate code points.
+pub fn prev_char(c: char) -> Option<char> {
+    match c {
+        '\u{E000}' => Some('\u{D7FF}'),
+        '\0' => None,
+        _ => char::from_u32(c as u32 - 1),
+    }
 }
 
 impl PartialEq<char> for CharSet {
@@ -57,8 +131,10 @@ impl Display for CharSet {
         }
         for &(start, end) in &self.range {
             repr.push(start);
-            repr.push('-');
-            repr.push(end);
+            if start != end {
+                repr.push('-');
+                repr.push(end);
+            }
         }
         repr.push(']');
         write!(f, "{}", repr)
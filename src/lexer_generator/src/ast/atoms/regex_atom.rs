@@ -7,6 +7,8 @@ use crate::ast::atoms::CharSet;
 pub enum RegexAtom {
     /// A single character.
     Char(char),
+    /// Any character of an inclusive range.
+    Range(char, char),
     /// The epsilon (empty string) symbol.
     Epsilon,
 }
@@ -20,6 +22,15 @@ impl RegexAtom {
             None
         }
     }
+
+    /// Checks whether the atom consumes the character `c`.
+    pub fn contains(&self, c: char) -> bool {
+        match self {
+            RegexAtom::Char(atom) => *atom == c,
+            RegexAtom::Range(start, end) => *start <= c && c <= *end,
+            RegexAtom::Epsilon => false,
+        }
+    }
 }
 
 impl From<char> for RegexAtom {
@@ -34,6 +45,7 @@ impl Display for RegexAtom {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             RegexAtom::Char(c) => write!(f, "{}", c),
+            RegexAtom::Range(start, end) => write!(f, "[{}-{}]", start, end),
             RegexAtom::Epsilon => write!(f, "\\epsilon"),
         }
     }
@@ -42,10 +54,7 @@ impl Display for RegexAtom {
 impl PartialEq<char> for RegexAtom {
     /// Allows comparing a `RegexAtom` to a `char`.
     fn eq(&self, other: &char) -> bool {
-        match self {
-            RegexAtom::Char(c) => c == other,
-            RegexAtom::Epsilon => false,
-        }
+        self.contains(*other)
     }
 }
 
@@ -67,6 +76,14 @@ impl AtomSet {
             None
         }
     }
+
+    /// Returns the sorted, disjoint and non-adjacent ranges of the characters in the set.
+    pub fn ranges(&self) -> Vec<(char, char)> {
+        match self {
+            AtomSet::CharSet(cs) => cs.ranges(),
+            AtomSet::Wildcard => vec![('\0', char::MAX)],
+        }
+    }
 }
 
 impl Display for AtomSet {
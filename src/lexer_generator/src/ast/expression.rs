@@ -7,7 +7,7 @@ use crate::ast::{ atoms::regex_atom::MatchableAtom, bin_op::BinOp, un_op::UnOp }
 /// An `Expression` can be one of:
 /// - a single atom (character, epsilon, wildcard, or charset),
 /// - a binary operation (concatenation or union),
-/// - a unary operation (Kleene star, plus, or question mark).
+/// - a unary operation (Kleene star, plus, question mark, or bounded repetition).
 pub enum Expression {
     /// A single atomic unit of the regular expression (e.g., `a`, `.`, `[a-z]`, `ε`).
     Atom(MatchableAtom),
@@ -15,7 +15,7 @@ pub enum Expression {
     /// A binary operation combining two expressions (e.g., `ab`, `a|b`).
     BinOp(BinOp),
 
-    /// A unary operation applied to a single expression (e.g., `a*`, `a+`, `a?`).
+    /// A unary operation applied to a single expression (e.g., `a*`, `a+`, `a?`, `a{2,3}`).
     UnOp(UnOp),
 }
 
@@ -2,12 +2,17 @@ use std::fmt::Display;
 
 use crate::ast::expression::Expression;
 
+/// The largest count a repetition may have. Each repetition builds a copy of its
+/// operand, so larger counts would make the automata impractically big.
+pub const MAX_REPETITION: usize = 1000;
+
 /// Represents a unary operator in a regular expression AST.
 ///
 /// Supported unary operators include:
 /// - `KleeneStar`: Matches zero or more repetitions (e.g., `a*`)
 /// - `Plus`: Matches one or more repetitions (e.g., `a+`)
 /// - `QuestionMark`: Matches zero or one occurrence (e.g., `a?`)
+/// - `Repeat`: Matches a bounded number of repetitions (e.g., `a{2,3}`)
 pub enum UnaryOperator {
     /// The Kleene star operator (`*`)
     KleeneStar,
@@ -15,6 +20,8 @@ pub enum UnaryOperator {
     Plus,
     /// The question mark operator (`?`)
     QuestionMark,
+    /// The repetition operator (`{min}`, `{min,}` or `{min,max}`), unbounded when `max` is `None`
+    Repeat { min: usize, max: Option<usize> },
 }
 
 impl From<char> for UnaryOperator {
@@ -49,12 +56,15 @@ impl Display for UnOp {
     /// Formats the unary operation as a string representation of the regular expression.
     ///
     /// Examples:
-    /// - `a*`, `b+`, or `c?` depending on the operator applied.
+    /// - `a*`, `b+`, `c?` or `d{2,3}` depending on the operator applied.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.op {
             UnaryOperator::KleeneStar => write!(f, "{}*", self.operand),
             UnaryOperator::Plus => write!(f, "{}+", self.operand),
             UnaryOperator::QuestionMark => write!(f, "{}?", self.operand),
+            UnaryOperator::Repeat { min, max: Some(max) } if min == max => write!(f, "{}{{{}}}", self.operand, min),
+            UnaryOperator::Repeat { min, max: Some(max) } => write!(f, "{}{{{},{}}}", self.operand, min, max),
+            UnaryOperator::Repeat { min, max: None } => write!(f, "{}{{{},}}", self.operand, min),
         }
     }
 }
\ No newline at end of file
@@ -46,7 +46,8 @@ impl NfaBuild {
     ///
     /// # Returns
     ///
-    /// An NFA that accepts exactly the given symbol.
+    /// An NFA that accepts exactly the given symbol. Character sets and wildcards
+    /// get one transition per range of characters, so they span all of Unicode.
     fn build_symbol(&mut self, symbol: &MatchableAtom) -> NFA {
         let start_state = self.state_counter;
         let accept_state = self.state_counter + 1;
@@ -56,11 +57,8 @@ impl NfaBuild {
 
         match symbol {
             MatchableAtom::AtomSet(char_set) => {
-                for byte in 0u8..=255u8 {
-                    let character = byte as char;
-                    if *char_set == character {
-                        transitions.insert((start_state, RegexAtom::Char(character)), HashSet::from([accept_state]));
-                    }
+                for (start, end) in char_set.ranges() {
+                    transitions.insert((start_state, RegexAtom::Range(start, end)), HashSet::from([accept_state]));
                 }
             }
             MatchableAtom::Atom(sym) => {
@@ -90,7 +88,7 @@ impl NfaBuild {
         }
     }
 
-    /// Builds an NFA for a unary operation (Kleene star, plus, or question mark).
+    /// Builds an NFA for a unary operation (Kleene star, plus, question mark, or repetition).
     ///
     /// # Arguments
     ///
@@ -100,6 +98,9 @@ impl NfaBuild {
     ///
     /// An NFA representing the unary operation applied to its operand.
     fn build_unary_op(&mut self, unary_op: &UnOp) -> NFA {
+        if let UnaryOperator::Repeat { min, max } = unary_op.op {
+            return self.repeat(&unary_op.operand, min, max);
+        }
         let operator = &unary_op.op;
         let operand_nfa = self.build_from_regex(&unary_op.operand);
 
@@ -107,7 +108,46 @@ impl NfaBuild {
             UnaryOperator::KleeneStar => self.kleene_star(&operand_nfa),
             UnaryOperator::Plus => self.one_or_more(&operand_nfa),
             UnaryOperator::QuestionMark => self.optional(&operand_nfa),
+            UnaryOperator::Repeat { .. } => unreachable!(),
+        }
+    }
+
+    /// Builds the repetition `{min,max}` of an expression.
+    ///
+    /// Every copy of the operand needs states of its own, so the operand is built once
+    /// per copy: `min` required copies followed by `max - min` optional ones, or by a
+    /// Kleene star when there is no upper bound.
+    ///
+    /// # Arguments
+    ///
+    /// * `operand` - The repeated expression.
+    /// * `min` - The least number of repetitions.
+    /// * `max` - The greatest number of repetitions, if bounded.
+    ///
+    /// # Returns
+    ///
+    /// A new NFA representing the repetition.
+    fn repeat(&mut self, operand: &Expression, min: usize, max: Option<usize>) -> NFA {
+        let mut nfa = self.build_symbol(&MatchableAtom::Atom(RegexAtom::Epsilon));
+        for _ in 0..min {
+            let copy = self.build_from_regex(operand);
+            self.append(&mut nfa, &copy);
+        }
+        match max {
+            Some(max) => {
+                for _ in min..max {
+                    let copy = self.build_from_regex(operand);
+                    let optional = self.optional(&copy);
+                    self.append(&mut nfa, &optional);
+                }
+            }
+            None => {
+                let copy = self.build_from_regex(operand);
+                let star = self.kleene_star(&copy);
+                self.append(&mut nfa, &star);
+            }
         }
+        nfa
     }
 
     /// Builds the concatenation of two NFAs.
@@ -124,19 +164,26 @@ impl NfaBuild {
     ///
     /// A new NFA representing the concatenation.
     fn concat(&mut self, nfa1: &NFA, nfa2: &NFA) -> NFA {
-        let start_state = nfa1.start_state;
-        let accept_state = nfa2.accept_state;
+        let mut nfa = NFA {
+            start_state: nfa1.start_state,
+            accept_state: nfa1.accept_state,
+            transitions: nfa1.transitions.clone(),
+        };
+        self.append(&mut nfa, nfa2);
+        nfa
+    }
 
-        let mut transitions = nfa1.transitions.clone();
-        for ((state, symbol), targets) in nfa2.transitions.iter() {
-            if *state == nfa2.start_state {
-                transitions.insert((nfa1.accept_state, symbol.clone()), targets.clone());
-            } else {
-                transitions.insert((*state, symbol.clone()), targets.clone());
-            }
+    /// Concatenates `next` to `nfa` in place, moving the transitions of its start state
+    /// to the accept state of `nfa`.
+    ///
+    /// Unlike [`NfaBuild::concat`], the transitions of `nfa` are not copied, so a chain
+    /// of appends takes time linear in the size of the result.
+    fn append(&mut self, nfa: &mut NFA, next: &NFA) {
+        for ((state, symbol), targets) in next.transitions.iter() {
+            let state = if *state == next.start_state { nfa.accept_state } else { *state };
+            nfa.transitions.insert((state, symbol.clone()), targets.clone());
         }
-
-        NFA { start_state, accept_state, transitions }
+        nfa.accept_state = next.accept_state;
     }
 
     /// Builds the union (alternation) of two NFAs.
@@ -0,0 +1,139 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use crate::ast::atoms::charset::CharSet;
+use crate::ast::atoms::regex_atom::RegexAtom;
+
+/// A drawing of an automaton in the DOT language of Graphviz.
+///
+/// The moves between two states are drawn as a single edge, labelled with their
+/// characters as a regex class: `a`, `[a-z_]`, or `[^"]` when the complement is shorter.
+/// Epsilon moves get an edge of their own, labelled `ε`.
+pub(crate) struct DotGraph {
+    name: &'static str,
+    start: usize,
+    states: BTreeSet<usize>,
+    accepting: BTreeMap<usize, String>,
+    ranges: BTreeMap<(usize, usize), Vec<(char, char)>>,
+    epsilon: BTreeSet<(usize, usize)>,
+}
+
+impl DotGraph {
+    /// Creates an empty drawing of the graph `name`, whose start state is `start`.
+    pub(crate) fn new(name: &'static str, start: usize) -> Self {
+        DotGraph {
+            name,
+            start,
+            states: BTreeSet::from([start]),
+            accepting: BTreeMap::new(),
+            ranges: BTreeMap::new(),
+            epsilon: BTreeSet::new(),
+        }
+    }
+
+    /// Marks `state` as accepting, labelled with `label` (e.g. its token kind).
+    pub(crate) fn accept(&mut self, state: usize, label: String) {
+        self.states.insert(state);
+        self.accepting.insert(state, label);
+    }
+
+    /// Adds a move from `from` to `to` that consumes `atom`.
+    pub(crate) fn add_move(&mut self, from: usize, atom: &RegexAtom, to: usize) {
+        match atom {
+            RegexAtom::Char(c) => self.add_range(from, *c, *c, to),
+            RegexAtom::Range(start, end) => self.add_range(from, *start, *end, to),
+            RegexAtom::Epsilon => {
+                self.states.extend([from, to]);
+                self.epsilon.insert((from, to));
+            }
+        }
+    }
+
+    /// Adds a move from `from` to `to` that consumes any character of `start..=end`.
+    pub(crate) fn add_range(&mut self, from: usize, start: char, end: char, to: usize) {
+        self.states.extend([from, to]);
+        self.ranges.entry((from, to)).or_default().push((start, end));
+    }
+
+    /// Returns the drawing in the DOT language, states and edges sorted by number.
+    pub(crate) fn render(&self) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph {} {{", self.name).unwrap();
+        writeln!(dot, "    rankdir=LR;").unwrap();
+        writeln!(dot, "    node [shape=circle];").unwrap();
+        writeln!(dot, "    start [shape=point];").unwrap();
+        writeln!(dot, "    start -> {};", self.start).unwrap();
+        for state in &self.states {
+            match self.accepting.get(state) {
+                Some(label) => {
+                    writeln!(dot, "    {} [shape=doublecircle, label=\"{}\\n{}\"];", state, state, escape(label)).unwrap()
+                }
+                None => writeln!(dot, "    {};", state).unwrap(),
+            }
+        }
+        let mut edges = self
+            .ranges
+            .iter()
+            .map(|(&(from, to), ranges)| (from, to, ranges_label(ranges)))
+            .chain(self.epsilon.iter().map(|&(from, to)| (from, to, "ε".to_string())))
+            .collect::<Vec<_>>();
+        edges.sort();
+        for (from, to, label) in edges {
+            writeln!(dot, "    {} -> {} [label=\"{}\"];", from, to, escape(&label)).unwrap();
+        }
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+}
+
+/// Writes a set of characters as a regex class, or as a single character.
+fn ranges_label(ranges: &[(char, char)]) -> String {
+    let included = CharSet::new(ranges.to_vec(), false).ranges();
+    let excluded = CharSet::new(ranges.to_vec(), true).ranges();
+    if excluded.is_empty() {
+        return "any".to_string();
+    }
+    if let [(start, end)] = included[..]
+        && start == end
+    {
+        return printable(start);
+    }
+
+    let (negation, ranges) = if excluded.len() < included.len() { ("^", excluded) } else { ("", included) };
+    let mut label = format!("[{}", negation);
+    for (start, end) in ranges {
+        label.push_str(&class_char(start));
+        if start != end {
+            label.push('-');
+            label.push_str(&class_char(end));
+        }
+    }
+    label.push(']');
+    label
+}
+
+/// Writes a character of a class, escaping those that have a meaning in it.
+fn class_char(c: char) -> String {
+    match c {
+        '\\' | ']' | '[' | '-' | '^' => format!("\\{}", c),
+        _ => printable(c),
+    }
+}
+
+/// Writes a character so that it can be seen: whitespace and control characters by
+/// their escapes, and the space as `␣`.
+fn printable(c: char) -> String {
+    match c {
+        ' ' => "␣".to_string(),
+        '\n' => "\\n".to_string(),
+        '\t' => "\\t".to_string(),
+        '\r' => "\\r".to_string(),
+        _ if c.is_control() || c == char::MAX => format!("\\u{{{:x}}}", c as u32),
+        _ => c.to_string(),
+    }
+}
+
+/// Escapes `text` for a quoted DOT string.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
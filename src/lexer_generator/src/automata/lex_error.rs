@@ -0,0 +1,91 @@
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+
+use super::lexeme::{Lexeme, Position};
+use crate::lexer::DEFAULT_MODE;
+
+/// The reason of a lexical error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LexErrorKind<T> {
+    /// No token starts with this character.
+    UnexpectedCharacter(char),
+    /// A token of this kind pops the mode stack while in the default mode.
+    LeftDefaultMode(T),
+}
+
+/// A lexical error, with the span of the input that was skipped to recover from it.
+///
+/// # Type Parameters
+///
+/// * `T`: The token kind type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LexError<'a, T> {
+    /// The reason of the error.
+    pub kind: LexErrorKind<T>,
+
+    /// The skipped text, which starts where the error was found.
+    pub fragment: &'a str,
+
+    /// The line number where the error was found (1-based).
+    pub line: usize,
+
+    /// The column where the error was found (1-based, counted in characters).
+    pub column: usize,
+
+    /// The start index (byte offset) of the skipped text.
+    pub start: usize,
+
+    /// The end index (byte offset) of the skipped text.
+    pub end: usize,
+}
+
+impl<'a, T> LexError<'a, T>
+where
+    T: Eq + PartialEq + Clone + Hash,
+{
+    /// Creates a new `LexError` for the skipped text `fragment`, found at `position`.
+    pub fn with(kind: LexErrorKind<T>, fragment: &'a str, position: Position, start: usize, end: usize) -> Self {
+        Self {
+            kind,
+            fragment,
+            line: position.line,
+            column: position.column,
+            start,
+            end,
+        }
+    }
+
+    /// Returns a lexeme of kind `kind` that covers the skipped text, so that a parser
+    /// can see where the error was and keep going.
+    pub fn to_lexeme(&self, kind: T) -> Lexeme<'a, T> {
+        Lexeme {
+            kind,
+            fragment: self.fragment,
+            line: self.line,
+            column: self.column,
+            start: self.start,
+            end: self.end,
+        }
+    }
+}
+
+impl<T> Display for LexError<'_, T>
+where
+    T: Debug,
+{
+    /// Formats the error as a message for the user.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            LexErrorKind::UnexpectedCharacter(ch) => write!(
+                f,
+                "Lexical Error!: Unexpected character '{}' at line: {}, column: {}",
+                ch, self.line, self.column
+            ),
+            LexErrorKind::LeftDefaultMode(kind) => write!(
+                f,
+                "Lexical Error!: Token {:?} leaves the {} mode at line: {}, column: {}",
+                kind, DEFAULT_MODE, self.line, self.column
+            ),
+        }
+    }
+}
@@ -20,6 +20,9 @@ where
     /// The line number where the lexeme was found (1-based).
     pub line: usize,
 
+    /// The column where the lexeme starts (1-based, counted in characters).
+    pub column: usize,
+
     /// The start index (byte offset) of the lexeme in the source text.
     pub start: usize,
 
@@ -31,24 +34,25 @@ impl<'a, T> Lexeme<'a, T>
 where
     T: Eq + PartialEq + Clone + Hash,
 {
-    /// Creates a new `Lexeme` with the specified kind, fragment, line, and start/end positions.
+    /// Creates a new `Lexeme` with the specified kind, fragment, position, and start/end offsets.
     ///
     /// # Arguments
     ///
     /// * `kind` - The token kind or category.
     /// * `fragment` - The matched substring slice.
-    /// * `line` - The line number where the lexeme was found.
+    /// * `position` - The line and column where the lexeme starts.
     /// * `start` - The starting byte index of the lexeme.
     /// * `end` - The ending byte index of the lexeme.
     ///
     /// # Returns
     ///
     /// A new instance of `Lexeme`.
-    pub fn with(kind: T, fragment: &'a str, line: usize, start: usize, end: usize) -> Self {
+    pub fn with(kind: T, fragment: &'a str, position: Position, start: usize, end: usize) -> Self {
         Self {
             kind,
             fragment,
-            line,
+            line: position.line,
+            column: position.column,
             start,
             end,
         }
@@ -63,4 +67,31 @@ where
     pub fn is_blank(&self) -> bool {
         self.start == self.end
     }
-}
\ No newline at end of file
+}
+
+/// A line and column of the input, both 1-based. Columns are counted in characters,
+/// so that they match what an editor shows whatever the encoding of the text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    /// The position of the first character of the input.
+    pub fn start() -> Self {
+        Position { line: 1, column: 1 }
+    }
+
+    /// Moves the position past `text`.
+    pub fn advance(&mut self, text: &str) {
+        for ch in text.chars() {
+            if ch == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+    }
+}
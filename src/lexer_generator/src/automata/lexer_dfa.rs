@@ -4,15 +4,23 @@ use std::{
     hash::Hash,
 };
 
-use super::lexeme::Lexeme;
+use super::dot::DotGraph;
+use super::lexeme::{Lexeme, Position};
+use super::lex_error::{LexError, LexErrorKind};
 use super::lexer_nfa::LexerNFA;
 use super::ndt::NDT;
+use super::minimization::{minimize, DEAD_STATE};
 use super::tracker::VisitTracker;
-use super::utils::{to_set, to_str};
+use crate::ast::atoms::charset::{next_char, prev_char};
+use crate::ast::atoms::regex_atom::RegexAtom;
 
 /// A deterministic finite automaton (DFA) lexer that tokenizes input strings
 /// based on a provided nondeterministic finite automaton (NFA) with tagged tokens.
 ///
+/// The DFA is minimal, and its transitions are stored in a dense table indexed by
+/// character equivalence classes: characters that every state treats the same way
+/// share a class, so the table has one column per class instead of per character.
+///
 /// # Type Parameters
 ///
 /// * `T`: The token kind type, which must implement `Clone`, `PartialEq`, `Eq`, `Hash`, and `Debug`.
@@ -20,15 +28,24 @@ use super::utils::{to_set, to_str};
 /// # Fields
 ///
 /// * `start_state`: The initial state of the DFA.
-/// * `accepting_states`: A map from DFA states to token kinds indicating accepting states.
-/// * `transitions`: A map representing DFA transitions from `(state, input_char)` to next state.
+/// * `accepting_states`: The token kind accepted by each state, if any.
+/// * `interval_starts`: The first character of each interval of the alphabet, sorted.
+/// * `interval_classes`: The equivalence class of the characters of each interval.
+/// * `class_count`: The number of equivalence classes.
+/// * `table`: The transitions, `table[state * class_count + class]` being the next state
+///   or `DEAD_STATE`.
 pub struct LexerDFA<T>
 where
     T: Clone + PartialEq,
 {
     pub start_state: usize,
-    pub accepting_states: HashMap<usize, T>,
-    pub transitions: HashMap<(usize, char), usize>,
+    pub accepting_states: Vec<Option<T>>,
+    pub interval_starts: Vec<char>,
+    pub interval_classes: Vec<usize>,
+    pub class_count: usize,
+    pub table: Vec<usize>,
+    /// Equivalence class of each ASCII character, the common case when scanning.
+    ascii_classes: [usize; 128],
 }
 
 impl<T> LexerDFA<T>
@@ -48,11 +65,73 @@ where
         LexerDFA::from(nfa)
     }
 
+    /// Returns the equivalence class of `ch`.
+    pub fn class_of(&self, ch: char) -> usize {
+        if ch.is_ascii() {
+            return self.ascii_classes[ch as usize];
+        }
+        let interval = self.interval_starts.partition_point(|&start| start <= ch) - 1;
+        self.interval_classes[interval]
+    }
+
+    /// Returns the state reached from `state` by consuming `ch`, if any.
+    pub fn next_state(&self, state: usize, ch: char) -> Option<usize> {
+        match self.table[state * self.class_count + self.class_of(ch)] {
+            DEAD_STATE => None,
+            next => Some(next),
+        }
+    }
+
+    /// Returns the transitions leaving `state` as sorted, disjoint and inclusive character
+    /// ranges with their target state, merging adjacent ranges with the same target.
+    pub fn transitions_from(&self, state: usize) -> Vec<(char, char, usize)> {
+        let mut ranges: Vec<(char, char, usize)> = Vec::new();
+        for (index, (&start, &class)) in self.interval_starts.iter().zip(&self.interval_classes).enumerate() {
+            let end = match self.interval_starts.get(index + 1) {
+                Some(&next) => prev_char(next).unwrap(),
+                None => char::MAX,
+            };
+            let target = self.table[state * self.class_count + class];
+            match ranges.last_mut() {
+                _ if target == DEAD_STATE => {}
+                Some(last) if last.2 == target && next_char(last.1) == Some(start) => last.1 = end,
+                _ => ranges.push((start, end, target)),
+            }
+        }
+        ranges
+    }
+
+    /// Draws the DFA in the DOT language of Graphviz, each accepting state labelled
+    /// with its token kind. The dead state is left out.
+    ///
+    /// # Returns
+    ///
+    /// The DOT source of the drawing, for `dot -Tsvg` or any other Graphviz tool.
+    pub fn to_dot(&self) -> String {
+        let mut graph = DotGraph::new("LexerDFA", self.start_state);
+        for (state, kind) in self.accepting_states.iter().enumerate() {
+            if let Some(kind) = kind {
+                graph.accept(state, format!("{:?}", kind));
+            }
+        }
+        for state in 0..self.state_count() {
+            for (start, end, target) in self.transitions_from(state) {
+                graph.add_range(state, start, end, target);
+            }
+        }
+        graph.render()
+    }
+
+    /// Returns the number of states of the DFA.
+    pub fn state_count(&self) -> usize {
+        self.accepting_states.len()
+    }
+
     /// Scans the input string and produces a vector of lexemes (tokens).
     ///
     /// Attempts to match the longest possible lexemes according to the DFA transitions.
     /// Returns `Ok` with the lexemes if scanning is successful for the entire input,
-    /// or `Err` with a list of lexical error messages if unrecognized characters are found.
+    /// or `Err` with a [`LexError`] for each unrecognized character, which is skipped.
     ///
     /// # Arguments
     ///
@@ -60,61 +139,35 @@ where
     ///
     /// # Returns
     ///
-    /// `Result<Vec<Lexeme<T>>, Vec<String>>` where the `Ok` variant contains the list of recognized tokens,
-    /// and the `Err` variant contains lexical error messages.
-    pub fn scan<'a>(&self, input: &'a str) -> Result<Vec<Lexeme<'a, T>>, Vec<String>> {
+    /// `Result<Vec<Lexeme<T>>, Vec<LexError<T>>>` where the `Ok` variant contains the list of recognized tokens,
+    /// and the `Err` variant contains the lexical errors.
+    pub fn scan<'a>(&self, input: &'a str) -> Result<Vec<Lexeme<'a, T>>, Vec<LexError<'a, T>>> {
         let mut results = Vec::new();
         let mut issues = Vec::new();
-        let source_chars: Vec<char> = input.chars().collect();
-        let mut index = 0;
-        let mut line_info = (0, 0);
-        let total = source_chars.len();
-
-        while index < total {
-            let mut state = self.start_state;
-            let mut last_match: Option<(usize, &T)> = None;
-            let mut line_snapshot = line_info;
-            let mut offset = index;
-
-            while offset < total {
-                let ch = source_chars[offset];
-                if ch == '\n' {
-                    line_snapshot.0 += 1;
-                    line_snapshot.1 = offset;
-                }
+        let mut position = Position::start();
+        let mut offset = 0;
 
-                if let Some(&next_state) = self.transitions.get(&(state, ch)) {
-                    state = next_state;
-                    if let Some(kind) = self.accepting_states.get(&state) {
-                        last_match = Some((offset + 1, kind));
-                    }
-                    offset += 1;
-                } else {
-                    break;
+        while offset < input.len() {
+            let end = match self.longest_match(input, offset) {
+                Some((end, kind)) => {
+                    results.push(Lexeme::with(kind.clone(), &input[offset..end], position, offset, end));
+                    end
                 }
-            }
-
-            if let Some((end, kind)) = last_match {
-                let fragment = &input[index..end];
-                results.push(Lexeme::with(
-                    kind.clone(),
-                    fragment,
-                    line_info.0,
-                    index,
-                    end,
-                ));
-                line_info = line_snapshot;
-                index = end;
-            } else {
-                issues.push(format!(
-                    "Lexical Error!: Unexpected character '{}' at line: {}, column: {}",
-                    source_chars[index],
-                    line_info.0,
-                    index - line_info.1
-                ));
-                line_info = line_snapshot;
-                index += 1;
-            }
+                None => {
+                    let ch = input[offset..].chars().next().unwrap();
+                    let end = offset + ch.len_utf8();
+                    issues.push(LexError::with(
+                        LexErrorKind::UnexpectedCharacter(ch),
+                        &input[offset..end],
+                        position,
+                        offset,
+                        end,
+                    ));
+                    end
+                }
+            };
+            position.advance(&input[offset..end]);
+            offset = end;
         }
 
         if issues.is_empty() {
@@ -123,21 +176,64 @@ where
             Err(issues)
         }
     }
+
+    /// Finds the longest lexeme of `input` that starts at byte offset `start`.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input string slice being tokenized.
+    /// * `start` - Byte offset where the lexeme starts, at a char boundary.
+    ///
+    /// # Returns
+    ///
+    /// `Some((end, kind))` with the byte offset where the lexeme ends and its token kind,
+    /// or `None` if no token starts at `start`.
+    pub fn longest_match(&self, input: &str, start: usize) -> Option<(usize, &T)> {
+        self.longest_match_with_stop(input, start).0
+    }
+
+    /// Finds the longest lexeme of `input` that starts at byte offset `start`, like
+    /// [`LexerDFA::longest_match`], and tells how far the DFA read to find it.
+    ///
+    /// # Returns
+    ///
+    /// The match, and the byte offset where the DFA stopped: that of the first character
+    /// it could not consume, or the length of `input` if it read it all. The match only
+    /// depends on the input up to this offset, this character included.
+    pub fn longest_match_with_stop(&self, input: &str, start: usize) -> (Option<(usize, &T)>, usize) {
+        let mut state = self.start_state;
+        let mut last_match = None;
+        for (index, ch) in input[start..].char_indices() {
+            let Some(next_state) = self.next_state(state, ch) else {
+                return (last_match, start + index);
+            };
+            state = next_state;
+            if let Some(kind) = &self.accepting_states[state] {
+                last_match = Some((start + index + ch.len_utf8(), kind));
+            }
+        }
+        (last_match, input.len())
+    }
 }
 
 impl<T> From<&LexerNFA<T>> for LexerDFA<T>
 where
     T: Clone + PartialEq + Eq + Hash,
 {
-    /// Converts a `LexerNFA` into an equivalent deterministic `LexerDFA`
-    /// using the subset construction algorithm.
+    /// Converts a `LexerNFA` into an equivalent minimal `LexerDFA`.
     ///
-    /// The states of the DFA correspond to sets of NFA states,
-    /// with transitions computed via epsilon-closure and moves.
+    /// The subset construction builds a DFA whose states correspond to sets of NFA
+    /// states, with transitions computed via epsilon-closure and moves. Instead of
+    /// trying every character, the ranges leaving a set of NFA states are split into
+    /// disjoint intervals on which every character moves to the same states, so the
+    /// DFA covers all of Unicode.
     ///
     /// The accepting states are determined by the presence of any NFA accepting states
     /// in the corresponding subset, selecting the token with the highest priority (lowest number).
     ///
+    /// The DFA is then minimised with Hopcroft's algorithm and its transitions are laid
+    /// out in a dense table over character equivalence classes.
+    ///
     /// # Arguments
     ///
     /// * `nfa` - A reference to the nondeterministic lexer NFA.
@@ -146,48 +242,185 @@ where
     ///
     /// An equivalent deterministic lexer DFA.
     fn from(nfa: &LexerNFA<T>) -> Self {
-        let start_state = 0;
-        let mut transitions = HashMap::new();
-        let mut tracker = VisitTracker::new();
-
-        let initial = nfa.e_closure(&HashSet::from([nfa.start_state]));
-        let id = to_str(&initial);
-        tracker.add_unseen(id.clone());
-
-        while let Some(state_id) = tracker.pop_unseen() {
-            let state_set = to_set(&state_id);
-            for byte in 0..=255u8 {
-                let ch = char::from(byte);
-                let target_set = nfa.e_closure(&nfa.move_to(&state_set, &ch.into()));
-
-                if target_set.is_empty() {
-                    continue;
-                }
+        let (ranges, accepting) = subset_construction(nfa);
 
-                let target_id = to_str(&target_set);
-                if !tracker.is_seen(&target_id) {
-                    tracker.add_unseen(target_id.clone());
+        // Every bound of a range starts an interval of characters that all states treat
+        // the same way
+        let mut interval_starts = ranges
+            .iter()
+            .flatten()
+            .flat_map(|&(start, end, _)| [Some(start), next_char(end)])
+            .flatten()
+            .chain(['\0'])
+            .collect::<Vec<_>>();
+        interval_starts.sort();
+        interval_starts.dedup();
+
+        let interval_count = interval_starts.len();
+        let mut table = vec![DEAD_STATE; ranges.len() * interval_count];
+        for (state, state_ranges) in ranges.iter().enumerate() {
+            for &(start, end, target) in state_ranges {
+                let first = interval_starts.partition_point(|&bound| bound < start);
+                let last = interval_starts.partition_point(|&bound| bound <= end);
+                for interval in first..last {
+                    table[state * interval_count + interval] = target;
                 }
+            }
+        }
 
-                transitions.insert((tracker[&state_id], ch), tracker[&target_id]);
+        let minimal = minimize(&table, interval_count, &accepting, 0);
+        let state_count = minimal.iter().filter(|&&state| state != DEAD_STATE).max().map_or(1, |max| max + 1);
+        let mut accepting_states = vec![None; state_count];
+        let mut minimal_table = vec![DEAD_STATE; state_count * interval_count];
+        for (state, &new_state) in minimal.iter().enumerate() {
+            if new_state == DEAD_STATE {
+                continue;
+            }
+            accepting_states[new_state] = accepting[state].clone();
+            for interval in 0..interval_count {
+                let target = table[state * interval_count + interval];
+                minimal_table[new_state * interval_count + interval] =
+                    if target == DEAD_STATE { DEAD_STATE } else { minimal[target] };
             }
         }
 
-        let accepting_states = tracker
-            .iter()
-            .filter_map(|sid| {
-                let set = to_set(sid);
-                set.iter()
-                    .filter_map(|q| nfa.accepting_states.get(q).cloned())
-                    .min_by_key(|(_, priority)| *priority)
-                    .map(|(kind, _)| (tracker[sid], kind))
+        // Intervals whose columns are equal form one equivalence class
+        let mut classes: HashMap<Vec<usize>, usize> = HashMap::new();
+        let interval_classes = (0..interval_count)
+            .map(|interval| {
+                let column = (0..state_count)
+                    .map(|state| minimal_table[state * interval_count + interval])
+                    .collect::<Vec<_>>();
+                let class_count = classes.len();
+                *classes.entry(column).or_insert(class_count)
             })
-            .collect();
+            .collect::<Vec<_>>();
+        let class_count = classes.len();
+        let mut table = vec![DEAD_STATE; state_count * class_count];
+        for (column, class) in classes {
+            for (state, target) in column.into_iter().enumerate() {
+                table[state * class_count + class] = target;
+            }
+        }
 
-        LexerDFA {
-            start_state,
+        let mut dfa = LexerDFA {
+            start_state: 0,
             accepting_states,
-            transitions,
+            interval_starts,
+            interval_classes,
+            class_count,
+            table,
+            ascii_classes: [0; 128],
+        };
+        for byte in 0..128u8 {
+            dfa.ascii_classes[byte as usize] = {
+                let interval = dfa.interval_starts.partition_point(|&start| start <= byte as char) - 1;
+                dfa.interval_classes[interval]
+            };
         }
+        dfa
     }
-}
\ No newline at end of file
+}
+
+/// Builds the DFA of `nfa` with the subset construction, identifying each DFA state by
+/// the sorted NFA states it contains.
+///
+/// # Returns
+///
+/// The transitions of each DFA state as sorted, disjoint and inclusive character ranges
+/// with their target state, and the token kind accepted by each state. State 0 is the
+/// start state.
+fn subset_construction<T>(nfa: &LexerNFA<T>) -> (Vec<RangeTargets>, Vec<Option<T>>)
+where
+    T: Clone + PartialEq,
+{
+    let mut outgoing: HashMap<usize, Vec<RangeMove>> = HashMap::new();
+    for ((state, symbol), targets) in &nfa.transitions {
+        let range = match symbol {
+            RegexAtom::Char(c) => (*c, *c),
+            RegexAtom::Range(start, end) => (*start, *end),
+            RegexAtom::Epsilon => continue,
+        };
+        outgoing.entry(*state).or_default().push((range.0, range.1, targets));
+    }
+
+    let mut tracker: VisitTracker<Vec<usize>> = VisitTracker::new();
+    let mut transitions: HashMap<usize, Vec<(char, char, usize)>> = HashMap::new();
+    tracker.add_unseen(sorted(nfa.e_closure(&HashSet::from([nfa.start_state]))));
+
+    while let Some(state_id) = tracker.pop_unseen() {
+        let moves = state_id
+            .iter()
+            .filter_map(|state| outgoing.get(state))
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+
+        let mut ranges: Vec<(char, char, usize)> = Vec::new();
+        for (start, end, targets) in partition(&moves) {
+            let target_id = sorted(nfa.e_closure(&targets));
+            tracker.add_unseen(target_id.clone());
+
+            let target = tracker[&target_id];
+            match ranges.last_mut() {
+                Some(last) if last.2 == target && next_char(last.1) == Some(start) => last.1 = end,
+                _ => ranges.push((start, end, target)),
+            }
+        }
+        transitions.insert(tracker[&state_id], ranges);
+    }
+
+    let mut accepting = vec![None; tracker.iter().count()];
+    for state_id in &tracker {
+        accepting[tracker[state_id]] = state_id
+            .iter()
+            .filter_map(|q| nfa.accepting_states.get(q).cloned())
+            .min_by_key(|(_, priority)| *priority)
+            .map(|(kind, _)| kind);
+    }
+    let ranges = (0..accepting.len())
+        .map(|state| transitions.remove(&state).unwrap_or_default())
+        .collect();
+    (ranges, accepting)
+}
+
+fn sorted(set: HashSet<usize>) -> Vec<usize> {
+    let mut states = set.into_iter().collect::<Vec<_>>();
+    states.sort_unstable();
+    states
+}
+
+/// The NFA states reached from some state by consuming a character of an inclusive range.
+type RangeMove<'a> = (char, char, &'a HashSet<usize>);
+
+/// The transitions of a DFA state as inclusive character ranges with their target state.
+type RangeTargets = Vec<(char, char, usize)>;
+
+/// Splits the ranges of `moves` into sorted, disjoint intervals, each with the union
+/// of the targets of the moves that contain it. Characters outside every range are skipped.
+fn partition(moves: &[RangeMove]) -> Vec<(char, char, HashSet<usize>)> {
+    let mut bounds = moves
+        .iter()
+        .flat_map(|&(start, end, _)| [Some(start), next_char(end)])
+        .flatten()
+        .collect::<Vec<_>>();
+    bounds.sort();
+    bounds.dedup();
+
+    let mut intervals = Vec::new();
+    for (index, &start) in bounds.iter().enumerate() {
+        let end = match bounds.get(index + 1) {
+            Some(&bound) => prev_char(bound).unwrap(),
+            None => char::MAX,
+        };
+        let targets = moves
+            .iter()
+            .filter(|&&(from, to, _)| from <= start && start <= to)
+            .flat_map(|&(_, _, targets)| targets.iter().copied())
+            .collect::<HashSet<_>>();
+        if !targets.is_empty() {
+            intervals.push((start, end, targets));
+        }
+    }
+    intervals
+}
@@ -3,6 +3,7 @@ use std::{
     collections::{HashMap, HashSet},
     fmt::Debug,
 };
+use super::dot::DotGraph;
 use super::ndt::NDT;
 use crate::ast::atoms::regex_atom::RegexAtom;
 use super::nfa::NFA;
@@ -87,6 +88,25 @@ where
             transitions,
         }
     }
+
+    /// Draws the NFA in the DOT language of Graphviz, each accepting state labelled
+    /// with its token kind.
+    ///
+    /// # Returns
+    ///
+    /// The DOT source of the drawing, for `dot -Tsvg` or any other Graphviz tool.
+    pub fn to_dot(&self) -> String {
+        let mut graph = DotGraph::new("LexerNFA", self.start_state);
+        for (state, (kind, _)) in &self.accepting_states {
+            graph.accept(*state, format!("{:?}", kind));
+        }
+        for ((state, symbol), targets) in &self.transitions {
+            for target in targets {
+                graph.add_move(*state, symbol, *target);
+            }
+        }
+        graph.render()
+    }
 }
 
 impl<T> NDT for LexerNFA<T>
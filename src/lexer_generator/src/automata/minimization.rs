@@ -0,0 +1,129 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// Marks a missing transition in a dense transition table.
+pub const DEAD_STATE: usize = usize::MAX;
+
+/// Minimises a DFA with Hopcroft's partition refinement algorithm.
+///
+/// The DFA is given as a dense table where `table[state * class_count + class]` is the
+/// state reached by consuming a character of the equivalence class `class`, or
+/// [`DEAD_STATE`]. Accepting states are only merged with states that accept the same
+/// token kind, so the minimal DFA reports the same tokens as the original one.
+///
+/// # Arguments
+///
+/// * `table` - The dense transition table.
+/// * `class_count` - The number of character equivalence classes (columns of `table`).
+/// * `accepting` - The token kind accepted by each state, if any.
+/// * `start` - The start state.
+///
+/// # Returns
+///
+/// The state of the minimal DFA that each original state becomes, or [`DEAD_STATE`] if it
+/// can never reach an accepting state. States are numbered in breadth-first order from
+/// the start state, which is state 0.
+pub fn minimize<K>(table: &[usize], class_count: usize, accepting: &[Option<K>], start: usize) -> Vec<usize>
+where
+    K: Eq + Hash,
+{
+    // The dead state is made explicit so that partial transitions can be refined too
+    let dead = accepting.len();
+    let target = |state: usize, class: usize| -> usize {
+        if state == dead {
+            return dead;
+        }
+        match table[state * class_count + class] {
+            DEAD_STATE => dead,
+            next => next,
+        }
+    };
+
+    let mut inverse: Vec<HashMap<usize, Vec<usize>>> = vec![HashMap::new(); class_count];
+    for state in 0..=dead {
+        for (class, sources) in inverse.iter_mut().enumerate() {
+            sources.entry(target(state, class)).or_default().push(state);
+        }
+    }
+
+    // Initial partition: non accepting states, and one block per token kind
+    let mut blocks: Vec<Vec<usize>> = Vec::new();
+    let mut block_of = vec![0; dead + 1];
+    let mut initial: HashMap<Option<&K>, usize> = HashMap::new();
+    for (state, state_block) in block_of.iter_mut().enumerate() {
+        let kind = accepting.get(state).and_then(|kind| kind.as_ref());
+        let block = *initial.entry(kind).or_insert_with(|| {
+            blocks.push(Vec::new());
+            blocks.len() - 1
+        });
+        blocks[block].push(state);
+        *state_block = block;
+    }
+
+    let mut worklist: Vec<usize> = (0..blocks.len()).collect();
+    let mut in_worklist = vec![true; blocks.len()];
+    let mut marked = vec![false; dead + 1];
+
+    while let Some(splitter) = worklist.pop() {
+        in_worklist[splitter] = false;
+        let members = blocks[splitter].clone();
+        for sources in &inverse {
+            // States that move into the splitter, grouped by their block
+            let mut touched: HashMap<usize, Vec<usize>> = HashMap::new();
+            for member in &members {
+                for &source in sources.get(member).into_iter().flatten() {
+                    touched.entry(block_of[source]).or_default().push(source);
+                }
+            }
+
+            for (block, moved) in touched {
+                if moved.len() == blocks[block].len() {
+                    continue;
+                }
+                for &state in &moved {
+                    marked[state] = true;
+                }
+                blocks[block].retain(|state| !marked[*state]);
+                for &state in &moved {
+                    marked[state] = false;
+                }
+
+                let new_block = blocks.len();
+                for &state in &moved {
+                    block_of[state] = new_block;
+                }
+                blocks.push(moved);
+                in_worklist.push(false);
+
+                if in_worklist[block] || blocks[new_block].len() <= blocks[block].len() {
+                    worklist.push(new_block);
+                    in_worklist[new_block] = true;
+                } else {
+                    worklist.push(block);
+                    in_worklist[block] = true;
+                }
+            }
+        }
+    }
+
+    // Number the live blocks in breadth-first order from the start state
+    let dead_block = block_of[dead];
+    let mut number = vec![DEAD_STATE; blocks.len()];
+    let mut queue = std::collections::VecDeque::new();
+    if block_of[start] != dead_block {
+        number[block_of[start]] = 0;
+        queue.push_back(start);
+    }
+    let mut count = 1;
+    while let Some(state) = queue.pop_front() {
+        for class in 0..class_count {
+            let block = block_of[target(state, class)];
+            if block != dead_block && number[block] == DEAD_STATE {
+                number[block] = count;
+                count += 1;
+                queue.push_back(blocks[block][0]);
+            }
+        }
+    }
+
+    (0..dead).map(|state| number[block_of[state]]).collect()
+}
@@ -1,17 +1,22 @@
 pub mod bob_construye_nfa;
+mod dot;
 pub mod nfa;
 pub mod ndt;
 pub mod lexeme;
+pub mod lex_error;
 pub mod lexer_nfa;
 pub mod tracker;
 pub mod utils;
 pub mod lexer_dfa;
+pub mod minimization;
 
 pub use bob_construye_nfa::NfaBuild;
 pub use nfa::NFA;
 pub use ndt::NDT;   
-pub use lexeme::Lexeme;
+pub use lexeme::{Lexeme, Position};
+pub use lex_error::{LexError, LexErrorKind};
 pub use lexer_nfa::LexerNFA;
 pub use tracker::VisitTracker;
 pub use utils::{to_set, to_str};
 pub use lexer_dfa::LexerDFA;
+pub use minimization::{minimize, DEAD_STATE};
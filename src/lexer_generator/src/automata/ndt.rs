@@ -46,7 +46,8 @@ pub trait NDT {
 
     /// Computes the set of states reachable from the given state set via a specific symbol.
     ///
-    /// This corresponds to the classical `move` operation in automata theory.
+    /// This corresponds to the classical `move` operation in automata theory. A character
+    /// symbol also follows the range transitions that contain it.
     ///
     /// # Arguments
     ///
@@ -59,8 +60,17 @@ pub trait NDT {
     fn move_to(&self, state_set: &HashSet<usize>, symbol: &RegexAtom) -> HashSet<usize> {
         let mut reachable = HashSet::new();
 
-        for &state in state_set {
-            if let Some(next_states) = self.get_transitions().get(&(state, symbol.clone())) {
+        let Some(&ch) = symbol.as_char() else {
+            for &state in state_set {
+                if let Some(next_states) = self.get_transitions().get(&(state, symbol.clone())) {
+                    reachable.extend(next_states);
+                }
+            }
+            return reachable;
+        };
+
+        for ((state, atom), next_states) in self.get_transitions() {
+            if state_set.contains(state) && atom.contains(ch) {
                 reachable.extend(next_states);
             }
         }
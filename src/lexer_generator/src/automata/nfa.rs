@@ -1,4 +1,5 @@
 use std::collections::{HashMap, HashSet};
+use super::dot::DotGraph;
 use super::ndt::NDT;
 use crate::ast::atoms::regex_atom::RegexAtom;
 
@@ -66,6 +67,22 @@ impl NFA {
 
         current_states.contains(&self.accept_state)
     }
+
+    /// Draws the NFA in the DOT language of Graphviz, the accept state labelled `accept`.
+    ///
+    /// # Returns
+    ///
+    /// The DOT source of the drawing, for `dot -Tsvg` or any other Graphviz tool.
+    pub fn to_dot(&self) -> String {
+        let mut graph = DotGraph::new("NFA", self.start_state);
+        graph.accept(self.accept_state, "accept".to_string());
+        for ((state, symbol), targets) in &self.transitions {
+            for target in targets {
+                graph.add_move(*state, symbol, *target);
+            }
+        }
+        graph.render()
+    }
 }
 
 impl NDT for NFA {
@@ -77,7 +77,7 @@ where
 
     /// Returns an iterator over all seen items.
     ///
-    pub fn iter(&self) -> std::collections::hash_set::Iter<T> {
+    pub fn iter(&self) -> std::collections::hash_set::Iter<'_, T> {
         self.seen.iter()
     }
 }
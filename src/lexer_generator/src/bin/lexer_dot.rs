@@ -0,0 +1,117 @@
+//! Draws the automata of a spec file (see `codegen::SpecFile`) in the DOT language.
+//!
+//! ```text
+//! lexer_dot <spec-file> [--nfa | --token <Kind>] [--mode <MODE>] [-o <output>]
+//! ```
+//!
+//! By default it draws the minimal DFA of the mode, with `--nfa` the NFA that joins the
+//! tokens of the mode, and with `--token` the NFA of a single token. The mode is the
+//! default one unless `--mode` names another. The output goes to stdout unless `-o`
+//! names a file, e.g. `lexer_dot hulk.tokens | dot -Tsvg > hulk.svg`.
+
+use std::fmt;
+use std::process::exit;
+
+use lexer_generator::RegexParser;
+use lexer_generator::automata::{LexerDFA, LexerNFA, NfaBuild, NFA};
+use lexer_generator::codegen::SpecFile;
+use lexer_generator::lexer::DEFAULT_MODE;
+
+/// A token kind, shown by its bare name on the accepting states.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Kind(String);
+
+impl fmt::Debug for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+enum Drawing {
+    Dfa,
+    Nfa,
+    Token(String),
+}
+
+struct Options {
+    spec_path: String,
+    drawing: Drawing,
+    mode: String,
+    output: Option<String>,
+}
+
+const USAGE: &str = "Usage: lexer_dot <spec-file> [--nfa | --token <Kind>] [--mode <MODE>] [-o <output>]";
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut spec_path = None;
+    let mut drawing = Drawing::Dfa;
+    let mut mode = DEFAULT_MODE.to_string();
+    let mut output = None;
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("Missing value for {}", name));
+        match arg.as_str() {
+            "--nfa" => drawing = Drawing::Nfa,
+            "--token" => drawing = Drawing::Token(value("--token")?),
+            "--mode" => mode = value("--mode")?,
+            "-o" => output = Some(value("-o")?),
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ if arg.starts_with('-') => return Err(format!("Unknown option {}\n{}", arg, USAGE)),
+            _ if spec_path.is_none() => spec_path = Some(arg),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    let spec_path = spec_path.ok_or(USAGE.to_string())?;
+    Ok(Options { spec_path, drawing, mode, output })
+}
+
+fn draw(options: &Options) -> Result<String, Vec<String>> {
+    let source = std::fs::read_to_string(&options.spec_path)
+        .map_err(|error| vec![format!("Cannot read {}: {}", options.spec_path, error)])?;
+    let spec_file = SpecFile::parse(&source)?;
+
+    let parser = RegexParser::new();
+    let mut tagged_automata: Vec<(NFA, Kind)> = Vec::new();
+    let mut errors = Vec::new();
+    for spec in spec_file.specs.iter().filter(|spec| spec.mode == options.mode) {
+        match parser.parse(&spec.patt) {
+            Ok(pattern) => tagged_automata.push((NfaBuild::new().build_from_regex(&pattern), Kind(spec.kind.clone()))),
+            Err(error) => errors.push(format!("Invalid pattern `{}` for token {}: {}", spec.patt, spec.kind, error)),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    if tagged_automata.is_empty() {
+        return Err(vec![format!("The mode {} has no tokens", options.mode)]);
+    }
+
+    match &options.drawing {
+        Drawing::Token(kind) => tagged_automata
+            .iter()
+            .find(|(_, tagged)| tagged.0 == *kind)
+            .map(|(nfa, _)| nfa.to_dot())
+            .ok_or(vec![format!("The mode {} has no token {}", options.mode, kind)]),
+        Drawing::Nfa => Ok(LexerNFA::new(&tagged_automata).to_dot()),
+        Drawing::Dfa => Ok(LexerDFA::new(&LexerNFA::new(&tagged_automata)).to_dot()),
+    }
+}
+
+fn main() {
+    let options = parse_options(std::env::args().skip(1)).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        exit(2);
+    });
+    let dot = draw(&options).unwrap_or_else(|errors| {
+        for error in errors {
+            eprintln!("{}", error);
+        }
+        exit(1);
+    });
+    match &options.output {
+        Some(path) => std::fs::write(path, dot).unwrap_or_else(|error| {
+            eprintln!("Cannot write {}: {}", path, error);
+            exit(1);
+        }),
+        None => print!("{}", dot),
+    }
+}
@@ -0,0 +1,164 @@
+//! Generation of standalone Rust lexers, for use from build scripts.
+//!
+//! The generated source holds the minimal DFA of each lexer mode as static tables,
+//! a `longest_match` function and a `Scanner` with the recovery strategies and error
+//! token of [`crate::lexer::Lexer`], and depends on nothing but the type of the token
+//! kinds, which must be `Copy`, `PartialEq` and `Debug`. Since it may not use
+//! every item, it is best included in a module of its own:
+//!
+//! ```ignore
+//! mod tokens {
+//!     #![allow(dead_code)]
+//!     include!(concat!(env!("OUT_DIR"), "/tokens.rs"));
+//! }
+//! ```
+
+pub mod spec_file;
+pub use spec_file::SpecFile;
+
+use std::{
+    error::Error,
+    fmt::{Debug, Display, Write},
+    hash::Hash,
+    path::Path,
+};
+
+use crate::automata::{LexerDFA, DEAD_STATE};
+use crate::lexer::{lexer::compile_modes, ModeAction, TokenSpec};
+
+/// The functions of the generated lexer, which read the tables.
+const SCANNER: &str = include_str!("scanner.rs.in");
+
+/// Generates the source of a lexer for `specs`.
+///
+/// # Arguments
+///
+/// * `specs` - The token specifications, as given to [`crate::lexer::Lexer::new`].
+/// * `kind_type` - The path of the token kind type in the generated code. Each kind
+///   is written as `{kind_type}::{kind}`.
+///
+/// # Returns
+///
+/// * `Ok(String)` with the Rust source of the lexer.
+/// * `Err(Vec<String>)` with a message for each invalid pattern or undefined mode.
+pub fn generate<T>(specs: &[TokenSpec<T>], kind_type: &str) -> Result<String, Vec<String>>
+where
+    T: Clone + PartialEq + Eq + Hash + Debug + Display,
+{
+    // Accepting states report the index of their spec, so each one keeps its actions
+    let modes = compile_modes(specs, |index, _| index)?;
+
+    let mut source = String::new();
+    writeln!(source, "// Generated by lexer_generator. Do not edit.\n").unwrap();
+    writeln!(source, "type Kind = {};\n", kind_type).unwrap();
+
+    writeln!(source, "/// The lexer modes, the default one first.").unwrap();
+    writeln!(source, "pub static MODES: [&str; {}] = [", modes.len()).unwrap();
+    for (mode, _) in &modes {
+        writeln!(source, "    {:?},", mode).unwrap();
+    }
+    writeln!(source, "];\n").unwrap();
+
+    writeln!(source, "/// The token specifications, in order of precedence.").unwrap();
+    writeln!(source, "pub static RULES: [Rule; {}] = [", specs.len()).unwrap();
+    for spec in specs {
+        let action = match &spec.action {
+            Some(ModeAction::Push(target)) => {
+                format!("ModeAction::Push({})", modes.iter().position(|(mode, _)| mode == target).unwrap())
+            }
+            Some(ModeAction::Pop) => "ModeAction::Pop".to_string(),
+            None => "ModeAction::None".to_string(),
+        };
+        writeln!(
+            source,
+            "    Rule {{ kind: {}::{}, ignore: {}, action: {} }},",
+            kind_type, spec.kind, spec.ignore, action
+        )
+        .unwrap();
+    }
+    writeln!(source, "];\n").unwrap();
+
+    writeln!(source, "static DFAS: [Dfa; {}] = [", modes.len()).unwrap();
+    for (_, dfa) in &modes {
+        write_dfa(&mut source, dfa);
+    }
+    writeln!(source, "];\n").unwrap();
+
+    source.push_str(SCANNER);
+    Ok(source)
+}
+
+/// Generates the source of a lexer from the source of a spec file, see [`SpecFile`].
+///
+/// The generated source starts with the enum of the token kinds.
+pub fn generate_from_spec_file(spec_source: &str) -> Result<String, Vec<String>> {
+    let spec_file = SpecFile::parse(spec_source)?;
+
+    let mut source = String::new();
+    writeln!(source, "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]").unwrap();
+    writeln!(source, "pub enum {} {{", spec_file.kind_type).unwrap();
+    for kind in spec_file.kinds() {
+        writeln!(source, "    {},", kind).unwrap();
+    }
+    writeln!(source, "}}\n").unwrap();
+
+    source.push_str(&generate(&spec_file.specs, &spec_file.kind_type)?);
+    Ok(source)
+}
+
+/// Generates the lexer of the spec file at `path` into `OUT_DIR`, from a build script.
+///
+/// The lexer is written to a file named after the spec file with the `rs` extension,
+/// and Cargo is told to run the build script again when the spec file changes.
+pub fn process_spec_file(path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+    let path = path.as_ref();
+    println!("cargo:rerun-if-changed={}", path.display());
+
+    let spec_source = std::fs::read_to_string(path)?;
+    let source = generate_from_spec_file(&spec_source)
+        .map_err(|errors| format!("{}: {}", path.display(), errors.join("\n")))?;
+    let file_name = path.with_extension("rs");
+    let out_path = Path::new(&std::env::var("OUT_DIR")?).join(file_name.file_name().ok_or("Invalid spec file path")?);
+    std::fs::write(out_path, source)?;
+    Ok(())
+}
+
+/// Writes the tables of `dfa` as a `Dfa` literal, one row of the transition table per state.
+fn write_dfa(source: &mut String, dfa: &LexerDFA<usize>) {
+    let entry = |value: usize| if value == DEAD_STATE { "X".to_string() } else { value.to_string() };
+    let list = |values: &mut dyn Iterator<Item = String>| values.collect::<Vec<_>>().join(", ");
+
+    writeln!(source, "    Dfa {{").unwrap();
+    writeln!(source, "        start: {},", dfa.start_state).unwrap();
+    writeln!(source, "        class_count: {},", dfa.class_count).unwrap();
+    writeln!(
+        source,
+        "        ascii_classes: [{}],",
+        list(&mut (0..128u8).map(|byte| dfa.class_of(byte as char).to_string()))
+    )
+    .unwrap();
+    writeln!(
+        source,
+        "        interval_starts: &[{}],",
+        list(&mut dfa.interval_starts.iter().map(|start| format!("'\\u{{{:x}}}'", *start as u32)))
+    )
+    .unwrap();
+    writeln!(
+        source,
+        "        interval_classes: &[{}],",
+        list(&mut dfa.interval_classes.iter().map(|class| class.to_string()))
+    )
+    .unwrap();
+    writeln!(source, "        table: &[").unwrap();
+    for row in dfa.table.chunks(dfa.class_count.max(1)) {
+        writeln!(source, "            {},", list(&mut row.iter().map(|&next| entry(next)))).unwrap();
+    }
+    writeln!(source, "        ],").unwrap();
+    writeln!(
+        source,
+        "        accepting: &[{}],",
+        list(&mut dfa.accepting_states.iter().map(|rule| rule.map_or("X".to_string(), |rule| rule.to_string())))
+    )
+    .unwrap();
+    writeln!(source, "    }},").unwrap();
+}
@@ -0,0 +1,112 @@
+use crate::lexer::TokenSpec;
+
+/// The token specifications of a spec file and the name of the enum of their kinds.
+///
+/// A spec file has one token per line, `<attributes> <Kind> = <pattern>`, where the
+/// pattern is the rest of the line with the surrounding whitespace removed. The
+/// attributes are `ignore`, `in <MODE>`, `push <MODE>` and `pop`. Empty lines and
+/// lines starting with `#` are skipped, and an `enum <Name>` line names the enum of
+/// the token kinds, `TokenKind` by default.
+///
+/// ```text
+/// enum TokenKind
+///
+/// Let = let
+/// Identifier = [a-zA-Z]\w*
+/// ignore Whitespace = \s+
+/// push STRING Quote = "
+/// in STRING Text = [^"]+
+/// in STRING pop Quote = "
+/// ```
+pub struct SpecFile {
+    pub kind_type: String,
+    pub specs: Vec<TokenSpec<String>>,
+}
+
+impl SpecFile {
+    /// Parses the source of a spec file.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SpecFile)` if every line is well formed.
+    /// * `Err(Vec<String>)` with a message for each line that is not.
+    pub fn parse(source: &str) -> Result<Self, Vec<String>> {
+        let mut spec_file = SpecFile { kind_type: "TokenKind".to_string(), specs: Vec::new() };
+        let mut errors = Vec::new();
+        for (index, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let result = match line.strip_prefix("enum ") {
+                Some(name) => parse_identifier(name.trim()).map(|name| spec_file.kind_type = name),
+                None => parse_spec(line).map(|spec| spec_file.specs.push(spec)),
+            };
+            if let Err(error) = result {
+                errors.push(format!("Line {}: {}", index + 1, error));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(spec_file)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Returns the token kinds in order of first appearance, without repetitions.
+    pub fn kinds(&self) -> Vec<&str> {
+        let mut kinds: Vec<&str> = Vec::new();
+        for spec in &self.specs {
+            if !kinds.contains(&spec.kind.as_str()) {
+                kinds.push(&spec.kind);
+            }
+        }
+        kinds
+    }
+}
+
+/// Parses a `<attributes> <Kind> = <pattern>` line.
+fn parse_spec(line: &str) -> Result<TokenSpec<String>, String> {
+    let Some((head, pattern)) = line.split_once('=') else {
+        return Err("Expected `<Kind> = <pattern>`".to_string());
+    };
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        return Err("Missing pattern".to_string());
+    }
+
+    let mut words = head.split_whitespace().collect::<Vec<_>>();
+    let Some(kind) = words.pop() else {
+        return Err("Missing token kind".to_string());
+    };
+    let mut spec = TokenSpec::build(parse_identifier(kind)?, pattern);
+    let mut words = words.into_iter();
+    while let Some(word) = words.next() {
+        spec = match word {
+            "ignore" => TokenSpec { ignore: true, ..spec },
+            "pop" => spec.pop_mode(),
+            "in" | "push" => {
+                let Some(mode) = words.next() else {
+                    return Err(format!("Missing mode after `{}`", word));
+                };
+                let mode = parse_identifier(mode)?;
+                if word == "in" { spec.in_mode(mode) } else { spec.push_mode(mode) }
+            }
+            _ => return Err(format!("Unknown attribute `{}`", word)),
+        };
+    }
+    Ok(spec)
+}
+
+/// Checks that `name` can be used as a Rust identifier.
+fn parse_identifier(name: &str) -> Result<String, String> {
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(name.to_string())
+    } else {
+        Err(format!("`{}` is not a valid name", name))
+    }
+}
@@ -4,10 +4,13 @@ use crate::RegexParser;
 use crate::automata::{
     bob_construye_nfa::NfaBuild,
     lexeme::Lexeme,
+    lex_error::LexError,
     lexer_dfa::LexerDFA,
     lexer_nfa::LexerNFA,
 };
-use super::token_spec::TokenSpec;
+use super::recovery::Recovery;
+use super::token_spec::{ModeAction, TokenSpec, DEFAULT_MODE};
+use super::token_stream::{Checkpoint, TokenStream};
 
 /// A generic lexer that uses a set of token specifications to tokenize input strings.
 /// 
@@ -20,12 +23,22 @@ use super::token_spec::TokenSpec;
 /// The `Lexer` builds a composite nondeterministic finite automaton (NFA) from
 /// token regex patterns, then determinizes it into a deterministic finite automaton (DFA)
 /// for efficient token scanning. It supports ignoring tokens such as whitespace.
+///
+/// Each lexer mode gets a DFA of its own, built from the specs of that mode. The lexer
+/// starts in [`DEFAULT_MODE`] and keeps a stack of modes that the tokens push and pop.
+///
+/// After an unexpected character, the lexer skips the input following its [`Recovery`]
+/// strategy and goes on lexing.
 pub struct Lexer<T>
 where
     T: Clone + PartialEq + Eq + Hash + Debug,
 {
-    ruleset: HashMap<T, TokenSpec<T>>,
-    matcher: LexerDFA<T>,
+    specs: Vec<TokenSpec<T>>,
+    /// The DFA of each mode, whose accepting states report the index of their spec,
+    /// so that each spec keeps its own flags and actions even if others share its kind.
+    modes: HashMap<String, LexerDFA<usize>>,
+    recovery: Recovery<T>,
+    error_kind: Option<T>,
 }
 
 impl<T> Lexer<T>
@@ -37,30 +50,29 @@ where
     /// # Arguments
     ///
     /// * `specs` - A vector of `TokenSpec`s describing token patterns and kinds.
+    ///   Earlier specs take precedence over later ones of the same mode.
     ///
-    /// # Panics
+    /// # Returns
     ///
-    /// Panics if any regex pattern fails to parse.
-    pub fn new(specs: Vec<TokenSpec<T>>) -> Self {
-        let parser = RegexParser::new();
-        let tagged_automata = specs
-            .iter()
-            .map(|spec| {
-                let pattern = parser.parse(&spec.patt).unwrap();
-                let mut builder = NfaBuild::new();
-                let nfa = builder.build_from_regex(&pattern);
-                (nfa, spec.kind.clone())
-            })
-            .collect::<Vec<_>>();
+    /// * `Ok(Lexer)` if every pattern is a valid regex and every pushed mode has specs.
+    /// * `Err(Vec<String>)` with a message for each invalid pattern or undefined mode.
+    pub fn new(specs: Vec<TokenSpec<T>>) -> Result<Self, Vec<String>> {
+        let modes = compile_modes(&specs, |index, _| index)?.into_iter().collect();
+
+        Ok(Lexer { specs, modes, recovery: Recovery::SkipChar, error_kind: None })
+    }
 
-        let composite_nfa = LexerNFA::new(&tagged_automata);
-        let matcher = LexerDFA::new(&composite_nfa);
-        let ruleset = specs
-            .into_iter()
-            .map(|spec| (spec.kind.clone(), spec))
-            .collect::<HashMap<_, _>>();
+    /// Sets how the lexer skips the input after an unexpected character,
+    /// [`Recovery::SkipChar`] by default.
+    pub fn with_recovery(self, recovery: Recovery<T>) -> Self {
+        Lexer { recovery, ..self }
+    }
 
-        Lexer { ruleset, matcher }
+    /// Makes [`Lexer::split`] put a lexeme of kind `kind` in place of each lexical error,
+    /// covering the skipped text, and succeed, so that a parser can report the errors and
+    /// keep going.
+    pub fn with_error_token(self, kind: T) -> Self {
+        Lexer { error_kind: Some(kind), ..self }
     }
 
     /// Splits the input string into a sequence of lexemes (tokens).
@@ -71,25 +83,200 @@ where
     ///
     /// # Returns
     ///
-    /// * `Ok(Vec<Lexeme<T>>)` containing the tokens if lexing is successful.
-    /// * `Err(Vec<String>)` containing error messages if lexing fails.
+    /// * `Ok(Vec<Lexeme<T>>)` containing the tokens if lexing is successful, or if the lexer
+    ///   has an error token, see [`Lexer::with_error_token`].
+    /// * `Err(Vec<LexError<T>>)` containing the lexical errors otherwise.
     ///
     /// Tokens flagged as `ignore` in their `TokenSpec` will be omitted from the output.
-    pub fn split<'a>(&self, input: &'a str) -> Result<Vec<Lexeme<'a, T>>, Vec<String>> {
-        let scan_result = self.matcher.scan(input);
-        let Ok(captures) = scan_result else {
-            return Err(scan_result.err().unwrap());
-        };
-
-        let filtered = captures
-            .into_iter()
-            .filter(|lexeme| {
-                self.ruleset
-                    .get(&lexeme.kind)
-                    .map_or(false, |spec| !spec.ignore)
-            })
-            .collect();
-
-        Ok(filtered)
-    }
-}
\ No newline at end of file
+    pub fn split<'a>(&self, input: &'a str) -> Result<Vec<Lexeme<'a, T>>, Vec<LexError<'a, T>>> {
+        let (results, issues) = self.split_with_errors(input);
+        if issues.is_empty() || self.error_kind.is_some() {
+            Ok(results)
+        } else {
+            Err(issues)
+        }
+    }
+
+    /// Splits the input string into lexemes like [`Lexer::split`], and returns them along
+    /// with every lexical error found.
+    ///
+    /// If the lexer has an error token, the lexemes include one in place of each error.
+    pub fn split_with_errors<'a>(&self, input: &'a str) -> (Vec<Lexeme<'a, T>>, Vec<LexError<'a, T>>) {
+        let mut results = Vec::new();
+        let mut issues = Vec::new();
+        for token in self.tokens(input) {
+            match token {
+                Ok(lexeme) => results.push(lexeme),
+                Err(issue) => {
+                    if let Some(kind) = &self.error_kind {
+                        results.push(issue.to_lexeme(kind.clone()));
+                    }
+                    issues.push(issue);
+                }
+            }
+        }
+        (results, issues)
+    }
+
+    /// Lexes the input string lazily.
+    ///
+    /// Each lexeme is the longest match of the DFA of the current mode, and the mode
+    /// actions of its spec are applied before matching the next one.
+    ///
+    /// # Returns
+    ///
+    /// A [`TokenStream`] over the lexemes of `input`, with their 1-based line and column
+    /// and their byte span.
+    pub fn tokens<'a>(&self, input: &'a str) -> TokenStream<'a, '_, T> {
+        self.resume(input, &Checkpoint::start())
+    }
+
+    /// Lexes the input string lazily from a checkpoint taken by an earlier [`TokenStream`].
+    pub fn resume<'a>(&self, input: &'a str, checkpoint: &Checkpoint) -> TokenStream<'a, '_, T> {
+        TokenStream::new(self, input, checkpoint)
+    }
+
+    /// Lexes an edited input again from the nearest checkpoint that the edit leaves valid.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input after the edit.
+    /// * `checkpoints` - Checkpoints taken while lexing the input before the edit, sorted by offset.
+    /// * `edit` - The byte offset of the first byte that the edit changed.
+    ///
+    /// # Returns
+    ///
+    /// A [`TokenStream`] that starts at the last checkpoint whose previous tokens were lexed
+    /// without reading `edit` or anything after it, or at the start of `input` if there is
+    /// none. The caller may stop once the new lexemes line up with the old ones again.
+    pub fn relex<'a>(&self, input: &'a str, checkpoints: &[Checkpoint], edit: usize) -> TokenStream<'a, '_, T> {
+        let start = Checkpoint::start();
+        let checkpoint = checkpoints
+            .iter()
+            .rev()
+            .find(|checkpoint| checkpoint.scanned <= edit)
+            .unwrap_or(&start);
+        self.resume(input, checkpoint)
+    }
+
+    /// Finds the longest lexeme of `input` that starts at byte offset `start` in `mode`.
+    ///
+    /// # Returns
+    ///
+    /// The end of the lexeme and the spec of its token, if any, and the offset where the
+    /// DFA stopped reading, as in [`LexerDFA::longest_match_with_stop`].
+    pub(crate) fn match_in(&self, mode: &str, input: &str, start: usize) -> (Option<(usize, &TokenSpec<T>)>, usize) {
+        let (found, stop) = self.modes[mode].longest_match_with_stop(input, start);
+        (found.map(|(end, &index)| (end, &self.specs[index])), stop)
+    }
+
+    /// Finds where lexing goes on after the unexpected character at byte offset `start`,
+    /// in `mode`, following the recovery strategy of the lexer.
+    ///
+    /// # Returns
+    ///
+    /// The end of the skipped text, and the offset of the last character read to find it,
+    /// or the length of `input` if it read it all.
+    pub(crate) fn recover(&self, mode: &str, input: &str, start: usize) -> (usize, usize) {
+        let next = start + input[start..].chars().next().unwrap().len_utf8();
+        match &self.recovery {
+            Recovery::SkipChar => (next, start),
+            Recovery::SkipToWhitespace => {
+                let end = input[next..].find(char::is_whitespace).map_or(input.len(), |index| next + index);
+                (end, end)
+            }
+            Recovery::SkipToToken(kinds) => {
+                let mut stop = start;
+                for (index, _) in input[next..].char_indices() {
+                    let (found, match_stop) = self.match_in(mode, input, next + index);
+                    stop = stop.max(match_stop);
+                    if found.is_some_and(|(_, spec)| kinds.contains(&spec.kind)) {
+                        return (next + index, stop);
+                    }
+                }
+                (input.len(), input.len())
+            }
+        }
+    }
+
+    /// Returns the name of `mode` as stored by the lexer, if it has such a mode.
+    pub(crate) fn mode_name(&self, mode: &str) -> Option<&str> {
+        self.modes.get_key_value(mode).map(|(name, _)| name.as_str())
+    }
+
+    /// Finds the longest lexeme of `input` that starts at byte offset `start` in the
+    /// default mode, for callers that drive the scan themselves.
+    ///
+    /// # Returns
+    ///
+    /// `Some((end, kind))` with the byte offset where the lexeme ends and its token kind,
+    /// or `None` if no token starts at `start`.
+    pub fn longest_match(&self, input: &str, start: usize) -> Option<(usize, &T)> {
+        self.modes[DEFAULT_MODE]
+            .longest_match(input, start)
+            .map(|(end, &index)| (end, &self.specs[index].kind))
+    }
+
+    /// Checks whether tokens of `kind` are flagged as `ignore` in their `TokenSpec`.
+    pub fn is_ignored(&self, kind: &T) -> bool {
+        self.specs.iter().any(|spec| spec.kind == *kind && spec.ignore)
+    }
+}
+
+/// Builds the DFA of each lexer mode from the specs of that mode.
+///
+/// # Arguments
+///
+/// * `specs` - The token specifications, earlier ones taking precedence.
+/// * `tag` - The value that the accepting states of a spec report, given its index and the spec.
+///
+/// # Returns
+///
+/// * `Ok(Vec<(String, LexerDFA<K>)>)` with the DFA of each mode, the default mode first and
+///   the others in order of appearance.
+/// * `Err(Vec<String>)` with a message for each invalid pattern or pushed mode without specs.
+pub(crate) fn compile_modes<T, K>(
+    specs: &[TokenSpec<T>],
+    tag: impl Fn(usize, &TokenSpec<T>) -> K,
+) -> Result<Vec<(String, LexerDFA<K>)>, Vec<String>>
+where
+    T: Clone + PartialEq + Eq + Hash + Debug,
+    K: Clone + PartialEq + Eq + Hash + Debug,
+{
+    let parser = RegexParser::new();
+    let mut errors = Vec::new();
+    let mut tagged_automata: Vec<(&str, Vec<_>)> = vec![(DEFAULT_MODE, Vec::new())];
+    for (index, spec) in specs.iter().enumerate() {
+        match parser.parse(&spec.patt) {
+            Ok(pattern) => {
+                let mut builder = NfaBuild::new();
+                let nfa = builder.build_from_regex(&pattern);
+                let automata = match tagged_automata.iter().position(|(mode, _)| *mode == spec.mode) {
+                    Some(position) => &mut tagged_automata[position].1,
+                    None => {
+                        tagged_automata.push((spec.mode.as_str(), Vec::new()));
+                        &mut tagged_automata.last_mut().unwrap().1
+                    }
+                };
+                automata.push((nfa, tag(index, spec)));
+            }
+            Err(error) => errors.push(format!("Invalid pattern `{}` for token {:?}: {}", spec.patt, spec.kind, error)),
+        }
+    }
+    for spec in specs {
+        if let Some(ModeAction::Push(mode)) = &spec.action
+            && mode != DEFAULT_MODE
+            && !specs.iter().any(|other| other.mode == *mode)
+        {
+            errors.push(format!("Token {:?} pushes the undefined mode {}", spec.kind, mode));
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    Ok(tagged_automata
+        .into_iter()
+        .map(|(mode, automata)| (mode.to_string(), LexerDFA::new(&LexerNFA::new(&automata))))
+        .collect())
+}
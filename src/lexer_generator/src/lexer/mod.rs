@@ -1,4 +1,8 @@
 pub mod lexer;
 pub mod token_spec;
+pub mod token_stream;
+pub mod recovery;
 pub use lexer::Lexer;
-pub use token_spec::TokenSpec;
+pub use token_spec::{TokenSpec, ModeAction, DEFAULT_MODE};
+pub use token_stream::{TokenStream, Checkpoint};
+pub use recovery::Recovery;
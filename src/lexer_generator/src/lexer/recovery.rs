@@ -0,0 +1,16 @@
+/// How a lexer skips the input after a character that starts no token.
+///
+/// # Type Parameters
+///
+/// * `T`: The token kind type.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Recovery<T> {
+    /// Skips the unexpected character alone.
+    #[default]
+    SkipChar,
+    /// Skips up to the next whitespace character, or to the end of the input.
+    SkipToWhitespace,
+    /// Skips up to the next offset where a token of one of these kinds starts, or to
+    /// the end of the input, e.g. to resume lexing at the next `;`.
+    SkipToToken(Vec<T>),
+}
@@ -1,8 +1,24 @@
 use std::hash::Hash;
 
+/// The mode a lexer starts in, and the one of the specs that name no other.
+pub const DEFAULT_MODE: &str = "DEFAULT";
+
+/// A change of lexer mode made after matching a token, like the start
+/// conditions of flex.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModeAction {
+    /// Enters the named mode, remembering the current one.
+    Push(String),
+    /// Returns to the mode that was current before the last push.
+    Pop,
+}
+
 /// Defines a token specification consisting of a pattern, token kind, and
 /// whether the token should be ignored (e.g., whitespace).
 ///
+/// Each spec belongs to a lexer mode and is only matched while the lexer is in
+/// it. Matching a token may push a new mode or pop the current one.
+///
 /// # Type Parameters
 ///
 /// * `T`: The token kind type, which must implement `Eq`, `PartialEq`, `Clone`, and `Hash`.
@@ -18,6 +34,12 @@ where
 
     /// Whether tokens matching this pattern should be ignored by the lexer.
     pub ignore: bool,
+
+    /// The lexer mode in which the token is recognized.
+    pub mode: String,
+
+    /// The change of mode made after matching the token, if any.
+    pub action: Option<ModeAction>,
 }
 
 impl<T> TokenSpec<T>
@@ -34,12 +56,14 @@ where
     ///
     /// # Returns
     ///
-    /// A `TokenSpec` instance with `ignore` set to `false`.
+    /// A `TokenSpec` instance with `ignore` set to `false`, in the default mode.
     pub fn build(kind: T, patt: impl Into<String>) -> Self {
         Self {
             patt: patt.into(),
             kind,
             ignore: false,
+            mode: DEFAULT_MODE.to_string(),
+            action: None,
         }
     }
 
@@ -53,12 +77,32 @@ where
     ///
     /// # Returns
     ///
-    /// A `TokenSpec` instance with `ignore` set to `true`.
+    /// A `TokenSpec` instance with `ignore` set to `true`, in the default mode.
     pub fn build_ignorable(kind: T, patt: impl Into<String>) -> Self {
         Self {
             patt: patt.into(),
             kind,
             ignore: true,
+            mode: DEFAULT_MODE.to_string(),
+            action: None,
         }
     }
+
+    /// Moves the token to the lexer mode `mode`.
+    pub fn in_mode(mut self, mode: impl Into<String>) -> Self {
+        self.mode = mode.into();
+        self
+    }
+
+    /// Makes the lexer enter `mode` after matching the token.
+    pub fn push_mode(mut self, mode: impl Into<String>) -> Self {
+        self.action = Some(ModeAction::Push(mode.into()));
+        self
+    }
+
+    /// Makes the lexer return to the previous mode after matching the token.
+    pub fn pop_mode(mut self) -> Self {
+        self.action = Some(ModeAction::Pop);
+        self
+    }
 }
\ No newline at end of file
@@ -0,0 +1,150 @@
+use std::{fmt::Debug, hash::Hash};
+
+use crate::automata::lexeme::{Lexeme, Position};
+use crate::automata::lex_error::{LexError, LexErrorKind};
+use super::lexer::Lexer;
+use super::token_spec::{ModeAction, DEFAULT_MODE};
+
+/// The state of a [`TokenStream`] between two tokens, from which lexing can be resumed.
+///
+/// # Fields
+///
+/// * `offset`: The byte offset of the next token.
+/// * `position`: The line and column of the next token.
+/// * `modes`: The stack of lexer modes, the current one last.
+/// * `scanned`: A bound on the input read to lex the previous tokens: they only depend on
+///   the bytes before it, so an edit at or after it leaves them, and this checkpoint, valid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub offset: usize,
+    pub position: Position,
+    pub modes: Vec<String>,
+    pub scanned: usize,
+}
+
+impl Checkpoint {
+    /// The checkpoint at the start of an input.
+    pub fn start() -> Self {
+        Checkpoint {
+            offset: 0,
+            position: Position::start(),
+            modes: vec![DEFAULT_MODE.to_string()],
+            scanned: 0,
+        }
+    }
+}
+
+/// An iterator over the lexemes of an input, lexed lazily by a [`Lexer`].
+///
+/// Tokens flagged as `ignore` are skipped. Each unexpected character, and each attempt to
+/// pop the default mode, yields a [`LexError`], and lexing goes on after it: after an
+/// unexpected character, the lexer skips the input as told by its [`crate::lexer::Recovery`].
+pub struct TokenStream<'a, 'l, T>
+where
+    T: Clone + PartialEq + Eq + Hash + Debug,
+{
+    lexer: &'l Lexer<T>,
+    input: &'a str,
+    offset: usize,
+    position: Position,
+    modes: Vec<&'l str>,
+    scanned: usize,
+}
+
+impl<'a, 'l, T> TokenStream<'a, 'l, T>
+where
+    T: Clone + PartialEq + Eq + Hash + Debug,
+{
+    /// Creates a stream over `input` that resumes lexing from `checkpoint`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the checkpoint names a mode the lexer does not have.
+    pub fn new(lexer: &'l Lexer<T>, input: &'a str, checkpoint: &Checkpoint) -> Self {
+        let modes = checkpoint
+            .modes
+            .iter()
+            .map(|mode| lexer.mode_name(mode).unwrap_or_else(|| panic!("Unknown lexer mode {}", mode)))
+            .collect();
+        TokenStream {
+            lexer,
+            input,
+            offset: checkpoint.offset,
+            position: checkpoint.position,
+            modes,
+            scanned: checkpoint.scanned,
+        }
+    }
+
+    /// Records that the character at byte offset `stop` was read, all of its bytes.
+    /// At the end of the input, its end was read: appending to it could change the tokens.
+    fn read_through(&mut self, stop: usize) {
+        let end = self.input[stop..].chars().next().map_or(self.input.len() + 1, |ch| stop + ch.len_utf8());
+        self.scanned = self.scanned.max(end);
+    }
+
+    /// The state of the stream before its next token.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            offset: self.offset,
+            position: self.position,
+            modes: self.modes.iter().map(|mode| mode.to_string()).collect(),
+            scanned: self.scanned,
+        }
+    }
+}
+
+impl<'a, 'l, T> Iterator for TokenStream<'a, 'l, T>
+where
+    T: Clone + PartialEq + Eq + Hash + Debug,
+{
+    type Item = Result<Lexeme<'a, T>, LexError<'a, T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.offset < self.input.len() {
+            let start = self.offset;
+            let position = self.position;
+            let mode = *self.modes.last().unwrap();
+            let (found, stop) = self.lexer.match_in(mode, self.input, start);
+            self.read_through(stop);
+
+            let Some((end, spec)) = found else {
+                let (end, stop) = self.lexer.recover(mode, self.input, start);
+                self.read_through(stop);
+                self.offset = end;
+                self.position.advance(&self.input[start..end]);
+                let ch = self.input[start..].chars().next().unwrap();
+                return Some(Err(LexError::with(
+                    LexErrorKind::UnexpectedCharacter(ch),
+                    &self.input[start..end],
+                    position,
+                    start,
+                    end,
+                )));
+            };
+            self.offset = end;
+            self.position.advance(&self.input[start..end]);
+
+            match &spec.action {
+                Some(ModeAction::Push(mode)) => self.modes.push(mode.as_str()),
+                Some(ModeAction::Pop) if self.modes.len() > 1 => {
+                    self.modes.pop();
+                }
+                Some(ModeAction::Pop) => {
+                    return Some(Err(LexError::with(
+                        LexErrorKind::LeftDefaultMode(spec.kind.clone()),
+                        &self.input[start..end],
+                        position,
+                        start,
+                        end,
+                    )));
+                }
+                None => {}
+            }
+            if !spec.ignore {
+                return Some(Ok(Lexeme::with(spec.kind.clone(), &self.input[start..end], position, start, end)));
+            }
+        }
+        None
+    }
+}
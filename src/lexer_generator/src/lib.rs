@@ -6,6 +6,7 @@ pub use parser::RegexParser;
 
 pub mod ast;
 pub mod automata;
+pub mod codegen;
 pub mod lexer;
 
 #[cfg(test)]
@@ -15,4 +16,7 @@ pub mod test {
     pub mod test_parsing;
     pub mod test_nfa;
     pub mod test_lexer;
+    pub mod test_minimization;
+    pub mod test_codegen;
+    pub mod test_dot;
 }
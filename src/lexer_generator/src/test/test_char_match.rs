@@ -79,4 +79,29 @@ fn test_matchable_atom_match_atomset_wildcard() {
     assert!(ma == 'a');
     assert!(ma == 'Z');
     assert!(ma == '!');
-}
\ No newline at end of file
+}
+#[test]
+fn test_charset_ranges_are_merged() {
+    let set = CharSet::new(vec![('m', 'z'), ('a', 'f'), ('g', 'k'), ('x', 'y')], false);
+    assert_eq!(set.ranges(), vec![('a', 'k'), ('m', 'z')]);
+}
+
+#[test]
+fn test_negated_charset_ranges_cover_unicode() {
+    let set = CharSet::new(vec![('"', '"'), ('\u{E000}', '\u{F8FF}')], true);
+    assert_eq!(
+        set.ranges(),
+        vec![('\0', '!'), ('#', '\u{D7FF}'), ('\u{F900}', char::MAX)]
+    );
+    assert!(set == 'ñ');
+    assert!(set == '🦀');
+    assert!(set != '"');
+}
+
+#[test]
+fn test_range_atom_matching() {
+    let range = RegexAtom::Range('α', 'ω');
+    assert!(range == 'λ');
+    assert!(range != 'a');
+    assert_eq!(range.to_string(), "[α-ω]");
+}
@@ -0,0 +1,98 @@
+use crate::codegen::{generate, generate_from_spec_file, SpecFile};
+use crate::lexer::{ModeAction, TokenSpec};
+
+#[test]
+pub fn spec_file_lines_become_token_specs() {
+    let spec_file = SpecFile::parse(
+        r#"
+# Strings with escapes
+enum Token
+
+Assign = =
+ignore Whitespace = \s+
+push STRING Quote = "
+in STRING Text = [^"\\]+
+in STRING pop Quote = "
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(spec_file.kind_type, "Token");
+    assert_eq!(spec_file.kinds(), vec!["Assign", "Whitespace", "Quote", "Text"]);
+    assert_eq!(
+        spec_file
+            .specs
+            .iter()
+            .map(|spec| (spec.kind.as_str(), spec.patt.as_str(), spec.ignore, spec.mode.as_str(), spec.action.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("Assign", "=", false, "DEFAULT", None),
+            ("Whitespace", r"\s+", true, "DEFAULT", None),
+            ("Quote", "\"", false, "DEFAULT", Some(ModeAction::Push("STRING".to_string()))),
+            ("Text", r#"[^"\\]+"#, false, "STRING", None),
+            ("Quote", "\"", false, "STRING", Some(ModeAction::Pop)),
+        ]
+    );
+}
+
+#[test]
+pub fn malformed_spec_file_lines_are_reported() {
+    let errors = SpecFile::parse("Number\nlazy Word = \\w+\nin Text = .\n2x = x\nenum my-kind").err().unwrap();
+
+    assert_eq!(
+        errors,
+        vec![
+            "Line 1: Expected `<Kind> = <pattern>`",
+            "Line 2: Unknown attribute `lazy`",
+            "Line 3: Missing mode after `in`",
+            "Line 4: `2x` is not a valid name",
+            "Line 5: `my-kind` is not a valid name",
+        ]
+    );
+}
+
+#[test]
+pub fn generated_lexer_has_tables_for_each_mode() {
+    let specs = vec![
+        TokenSpec::build("Word", r"\w+"),
+        TokenSpec::build("Quote", "\"").push_mode("STRING"),
+        TokenSpec::build("Text", "[^\"]+").in_mode("STRING"),
+        TokenSpec::build("Quote", "\"").in_mode("STRING").pop_mode(),
+    ];
+
+    let source = generate(&specs, "crate::Kind").unwrap();
+
+    assert!(source.contains("type Kind = crate::Kind;"));
+    assert!(source.contains("pub static MODES: [&str; 2] = [\n    \"DEFAULT\",\n    \"STRING\",\n];"));
+    assert!(source.contains("Rule { kind: crate::Kind::Quote, ignore: false, action: ModeAction::Push(1) },"));
+    assert!(source.contains("Rule { kind: crate::Kind::Quote, ignore: false, action: ModeAction::Pop },"));
+    assert_eq!(source.matches("    Dfa {").count(), 2);
+    assert!(source.contains("pub fn scan(input: &str)"));
+    assert!(source.contains("pub enum Recovery"));
+    assert!(source.contains("pub fn split_with_errors<'a>(&self, input: &'a str)"));
+}
+
+#[test]
+pub fn generated_tables_are_the_minimal_dfa() {
+    let source = generate(&[TokenSpec::build("AB", "ab|b")], "Kind").unwrap();
+
+    // `a` leads to a state that needs a `b`, and `b` to the accepting state
+    assert!(source.contains("        table: &[\n            X, 1, 2,\n            X, X, 2,\n            X, X, X,\n        ],"));
+    assert!(source.contains("        accepting: &[X, X, 0],"));
+}
+
+#[test]
+pub fn spec_file_generates_the_kind_enum() {
+    let source = generate_from_spec_file("enum Token\nA = a\nB = b\nA = aa").unwrap();
+
+    assert!(source.starts_with("#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\npub enum Token {\n    A,\n    B,\n}\n"));
+    assert!(source.contains("type Kind = Token;"));
+}
+
+#[test]
+pub fn invalid_patterns_are_not_generated() {
+    let errors = generate_from_spec_file("A = a{2,1}").err().unwrap();
+
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("\"A\""));
+}
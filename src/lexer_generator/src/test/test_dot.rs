@@ -0,0 +1,78 @@
+use crate::RegexParser;
+use crate::automata::{LexerDFA, LexerNFA, NfaBuild};
+
+#[test]
+pub fn nfa_edges_collapse_ranges() {
+    let regex = RegexParser::new().parse(r"[a-z_]\d").unwrap();
+    let nfa = NfaBuild::new().build_from_regex(&regex);
+
+    assert_eq!(
+        nfa.to_dot(),
+        r#"digraph NFA {
+    rankdir=LR;
+    node [shape=circle];
+    start [shape=point];
+    start -> 0;
+    0;
+    1;
+    3 [shape=doublecircle, label="3\naccept"];
+    0 -> 1 [label="[_a-z]"];
+    1 -> 3 [label="[0-9]"];
+}
+"#
+    );
+}
+
+#[test]
+pub fn nfa_epsilon_moves_have_their_own_edges() {
+    let regex = RegexParser::new().parse(r"a?").unwrap();
+    let nfa = NfaBuild::new().build_from_regex(&regex);
+    let dot = nfa.to_dot();
+
+    assert!(dot.contains("    0 -> 1 [label=\"a\"];\n"));
+    assert!(dot.contains("    0 -> 1 [label=\"ε\"];\n"));
+}
+
+#[test]
+pub fn lexer_nfa_labels_accepting_states_with_kinds() {
+    let parser = RegexParser::new();
+    let tagged_automata = vec![
+        (NfaBuild::new().build_from_regex(&parser.parse("if").unwrap()), "IF"),
+        (NfaBuild::new().build_from_regex(&parser.parse(r"\w+").unwrap()), "IDENTIFIER"),
+    ];
+    let dot = LexerNFA::new(&tagged_automata).to_dot();
+
+    assert!(dot.starts_with("digraph LexerNFA {\n"));
+    assert!(dot.contains("[shape=doublecircle, label=\"4\\n\\\"IF\\\"\"];"));
+    assert!(dot.contains("\\n\\\"IDENTIFIER\\\"\"];"));
+    assert!(dot.contains("    0 -> 1 [label=\"ε\"];\n"));
+}
+
+#[test]
+pub fn dfa_labels_complements_and_special_characters() {
+    let parser = RegexParser::new();
+    let tagged_automata = vec![
+        (NfaBuild::new().build_from_regex(&parser.parse(r#""[^"]*""#).unwrap()), "STRING"),
+        (NfaBuild::new().build_from_regex(&parser.parse(r"\s").unwrap()), "SPACE"),
+    ];
+    let dfa = LexerDFA::new(&LexerNFA::new(&tagged_automata));
+
+    assert_eq!(
+        dfa.to_dot(),
+        r#"digraph LexerDFA {
+    rankdir=LR;
+    node [shape=circle];
+    start [shape=point];
+    start -> 0;
+    0;
+    1 [shape=doublecircle, label="1\n\"SPACE\""];
+    2;
+    3 [shape=doublecircle, label="3\n\"STRING\""];
+    0 -> 1 [label="[\\t-\\r␣]"];
+    0 -> 2 [label="\""];
+    2 -> 2 [label="[^\"]"];
+    2 -> 3 [label="\""];
+}
+"#
+    );
+}
@@ -1,6 +1,8 @@
 use std::vec;
 
-use crate::lexer::{lexer::Lexer, token_spec::TokenSpec};
+use crate::RegexParser;
+use crate::automata::{NfaBuild, LexerDFA, LexerNFA, LexErrorKind};
+use crate::lexer::{lexer::Lexer, token_spec::TokenSpec, Recovery};
 
 #[test]
 pub fn collision_case() {
@@ -10,7 +12,7 @@ pub fn collision_case() {
     ];
 
     let str = "aaaaaaaaaaaaaaaaaaaaaaa";
-    let lexer = Lexer::new(rules);
+    let lexer = Lexer::new(rules).unwrap();
     let result = lexer.split(str);
 
     assert!(result.is_ok());
@@ -38,7 +40,7 @@ pub fn lex_hulk_line() {
         TokenSpec::build_ignorable("WhiteSpace", r"\s+"),
     ];
 
-    let lexer = Lexer::new(rules);
+    let lexer = Lexer::new(rules).unwrap();
     let result = lexer.split("let malanguita_frita: Number = 69 in x;");
 
     assert!(result.is_ok());
@@ -78,7 +80,7 @@ pub fn lex_another_hulk_line() {
         TokenSpec::build_ignorable("WhiteSpace", r"\s+"),
     ];
 
-    let lexer = Lexer::new(rules);
+    let lexer = Lexer::new(rules).unwrap();
     let result = lexer.split("function function_sexo (a:Number, b:Number): Number => a + b + 69;");
 
     assert!(result.is_ok());
@@ -128,7 +130,7 @@ pub fn lex_another_hulk_line_with_error() {
         TokenSpec::build_ignorable("WhiteSpace", r"\s+"),
     ];
 
-    let lexer = Lexer::new(rules);
+    let lexer = Lexer::new(rules).unwrap();
     let input = "function a() => b # c;";
     let result = lexer.split(input);
 
@@ -138,8 +140,8 @@ pub fn lex_another_hulk_line_with_error() {
 
     assert_eq!(errors.len(), 1);
     assert_eq!(
-        errors[0],
-        "Lexical Error!: Unexpected character '#' at line: 0, column: 18"
+        errors[0].to_string(),
+        "Lexical Error!: Unexpected character '#' at line: 1, column: 19"
     );
 }
 
@@ -159,7 +161,7 @@ pub fn lex_another_hulk_line_with_multiple_errors() {
         TokenSpec::build_ignorable("WhiteSpace", r"(\s|\t|\n)+"),
     ];
 
-    let lexer = Lexer::new(rules);
+    let lexer = Lexer::new(rules).unwrap();
     let input =
 "function 2a () => b $ c;
 function testicol () => d + e;
@@ -173,11 +175,439 @@ function minus() => f - g;";
 
     assert_eq!(errors.len(), 3);
     assert_eq!(
-        errors,
+        errors.iter().map(ToString::to_string).collect::<Vec<_>>(),
         vec![
-            "Lexical Error!: Unexpected character '2' at line: 0, column: 9",
-            "Lexical Error!: Unexpected character '$' at line: 0, column: 20",
-            "Lexical Error!: Unexpected character '-' at line: 4, column: 23"
+            "Lexical Error!: Unexpected character '2' at line: 1, column: 10",
+            "Lexical Error!: Unexpected character '$' at line: 1, column: 21",
+            "Lexical Error!: Unexpected character '-' at line: 3, column: 23"
         ]
     );
-}
\ No newline at end of file
+}
+#[test]
+pub fn lexemes_are_delimited_by_byte_offsets() {
+    let rules = vec![
+        TokenSpec::build("WORD", r"[a-zA-Z]+"),
+        TokenSpec::build("ACCENTED", "á|ñ"),
+        TokenSpec::build_ignorable("WhiteSpace", r"\s+"),
+    ];
+
+    let lexer = Lexer::new(rules).unwrap();
+    let input = "ñ atrás";
+    let tokens = lexer.split(input).unwrap();
+
+    assert_eq!(
+        tokens.iter().map(|t| (t.kind, t.fragment, t.start, t.end)).collect::<Vec<_>>(),
+        vec![
+            ("ACCENTED", "ñ", 0, 2),
+            ("WORD", "atr", 3, 6),
+            ("ACCENTED", "á", 6, 8),
+            ("WORD", "s", 8, 9),
+        ]
+    );
+    assert_eq!(&input[tokens[2].start..tokens[2].end], "á");
+}
+
+#[test]
+pub fn longest_match_from_an_offset() {
+    let rules = vec![
+        TokenSpec::build("ARROW", r"=>"),
+        TokenSpec::build("EQUAL", r"="),
+        TokenSpec::build("IDENTIFIER", r"[a-z]+"),
+        TokenSpec::build_ignorable("WhiteSpace", r"\s+"),
+    ];
+
+    let lexer = Lexer::new(rules).unwrap();
+    let input = "añb => c";
+
+    assert_eq!(lexer.longest_match(input, 0), Some((1, &"IDENTIFIER")));
+    assert_eq!(lexer.longest_match(input, 1), None);
+    assert_eq!(lexer.longest_match(input, 3), Some((4, &"IDENTIFIER")));
+    assert_eq!(lexer.longest_match(input, 4), Some((5, &"WhiteSpace")));
+    assert_eq!(lexer.longest_match(input, 5), Some((7, &"ARROW")));
+    assert_eq!(lexer.longest_match(input, 6), None);
+    assert!(lexer.is_ignored(&"WhiteSpace"));
+    assert!(!lexer.is_ignored(&"ARROW"));
+}
+
+#[test]
+pub fn classes_and_wildcards_cover_unicode() {
+    let rules = vec![
+        TokenSpec::build("STRING", r#""[^"-"]*""#),
+        TokenSpec::build("ANY", r"."),
+    ];
+
+    let lexer = Lexer::new(rules).unwrap();
+    let tokens = lexer.split(r#""año 🦀 λ"→"#).unwrap();
+
+    assert_eq!(
+        tokens.iter().map(|t| (t.kind, t.fragment)).collect::<Vec<_>>(),
+        vec![("STRING", r#""año 🦀 λ""#), ("ANY", "→")]
+    );
+}
+
+#[test]
+pub fn shorthand_classes_and_repetition() {
+    let rules = vec![
+        TokenSpec::build("HEX", r"0x[\da-fA-F]{1,4}"),
+        TokenSpec::build("IDENTIFIER", r"[a-zA-Z_]\w*"),
+        TokenSpec::build_ignorable("WHITESPACE", r"\s+"),
+    ];
+
+    let lexer = Lexer::new(rules).unwrap();
+    let tokens = lexer.split("0xBEEF\t_x1\n0x1234z").unwrap();
+
+    assert_eq!(
+        tokens.iter().map(|t| (t.kind, t.fragment)).collect::<Vec<_>>(),
+        vec![("HEX", "0xBEEF"), ("IDENTIFIER", "_x1"), ("HEX", "0x1234"), ("IDENTIFIER", "z")]
+    );
+}
+
+#[test]
+pub fn invalid_patterns_are_reported() {
+    let rules = vec![
+        TokenSpec::build("OPEN", r"["),
+        TokenSpec::build("WORD", r"\w+"),
+        TokenSpec::build("REPEAT", r"a{2,1}"),
+    ];
+
+    let errors = Lexer::new(rules).err().unwrap();
+
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].contains("OPEN"));
+    assert!(errors[1].contains("REPEAT"));
+}
+
+#[test]
+pub fn repetition_counts_over_the_limit_are_reported() {
+    let rules = vec![TokenSpec::build("A", r"a{20000}")];
+
+    let errors = Lexer::new(rules).err().unwrap();
+
+    assert_eq!(errors, vec!["Invalid pattern `a{20000}` for token \"A\": Repetition count is larger than 1000"]);
+}
+
+#[test]
+pub fn dfa_has_one_transition_per_range() {
+    let parser = RegexParser::new();
+    let tagged_automata = vec![(r"[^a-a]", "NOT_A"), (r"a", "A")]
+        .into_iter()
+        .map(|(pattern, kind)| (NfaBuild::new().build_from_regex(&parser.parse(pattern).unwrap()), kind))
+        .collect::<Vec<_>>();
+    let dfa = LexerDFA::new(&LexerNFA::new(&tagged_automata));
+
+    let ranges = dfa.transitions_from(dfa.start_state);
+    assert_eq!(ranges.len(), 3);
+    assert_eq!((ranges[0].0, ranges[0].1), ('\0', '`'));
+    assert_eq!((ranges[1].0, ranges[1].1), ('a', 'a'));
+    assert_eq!((ranges[2].0, ranges[2].1), ('b', char::MAX));
+    assert_eq!(ranges[0].2, ranges[2].2);
+    assert_eq!(dfa.next_state(dfa.start_state, '🦀'), Some(ranges[0].2));
+}
+
+#[test]
+pub fn modes_lex_strings_with_escapes() {
+    let rules = vec![
+        TokenSpec::build("QUOTE", r#"""#).push_mode("STRING"),
+        TokenSpec::build("IDENTIFIER", r"\w+"),
+        TokenSpec::build_ignorable("WHITESPACE", r"\s+"),
+        TokenSpec::build("TEXT", r#"[^"\\]+"#).in_mode("STRING"),
+        TokenSpec::build("ESCAPE", r"\\.").in_mode("STRING"),
+        TokenSpec::build("QUOTE", r#"""#).in_mode("STRING").pop_mode(),
+    ];
+
+    let lexer = Lexer::new(rules).unwrap();
+    let tokens = lexer.split(r#"say "a \"b\" c" now"#).unwrap();
+
+    assert_eq!(
+        tokens.iter().map(|t| (t.kind, t.fragment)).collect::<Vec<_>>(),
+        vec![
+            ("IDENTIFIER", "say"),
+            ("QUOTE", "\""),
+            ("TEXT", "a "),
+            ("ESCAPE", "\\\""),
+            ("TEXT", "b"),
+            ("ESCAPE", "\\\""),
+            ("TEXT", " c"),
+            ("QUOTE", "\""),
+            ("IDENTIFIER", "now"),
+        ]
+    );
+}
+
+#[test]
+pub fn modes_nest_block_comments() {
+    let rules = vec![
+        TokenSpec::build_ignorable("OPEN", r"/\*").push_mode("COMMENT"),
+        TokenSpec::build("IDENTIFIER", r"\w+"),
+        TokenSpec::build_ignorable("WHITESPACE", r"\s+"),
+        TokenSpec::build_ignorable("OPEN", r"/\*").in_mode("COMMENT").push_mode("COMMENT"),
+        TokenSpec::build_ignorable("CLOSE", r"\*/").in_mode("COMMENT").pop_mode(),
+        TokenSpec::build_ignorable("TEXT", r"[^*/]+|\*|/").in_mode("COMMENT"),
+    ];
+
+    let lexer = Lexer::new(rules).unwrap();
+    let tokens = lexer.split("a /* b /* c */ d */ e").unwrap();
+
+    assert_eq!(tokens.iter().map(|t| t.fragment).collect::<Vec<_>>(), vec!["a", "e"]);
+}
+
+#[test]
+pub fn specs_sharing_a_kind_keep_their_own_flags() {
+    let rules = vec![
+        TokenSpec::build("WORD", r"[a-z]+"),
+        TokenSpec::build_ignorable("WORD", r"\d+"),
+        TokenSpec::build("WORD", r"\(").push_mode("PAREN"),
+        TokenSpec::build("WORD", r"\)").in_mode("PAREN").pop_mode(),
+        TokenSpec::build_ignorable("WHITESPACE", r"\s+"),
+    ];
+
+    let lexer = Lexer::new(rules).unwrap();
+    let tokens = lexer.split("ab 12 () cd").unwrap();
+
+    assert_eq!(tokens.iter().map(|t| t.fragment).collect::<Vec<_>>(), vec!["ab", "(", ")", "cd"]);
+}
+
+#[test]
+pub fn popping_the_default_mode_is_an_error() {
+    let rules = vec![
+        TokenSpec::build("IDENTIFIER", r"\w+"),
+        TokenSpec::build("CLOSE", r"\)").pop_mode(),
+    ];
+
+    let lexer = Lexer::new(rules).unwrap();
+    let errors = lexer.split("a)").err().unwrap();
+
+    assert_eq!(errors[0].kind, LexErrorKind::LeftDefaultMode("CLOSE"));
+    assert_eq!(errors[0].to_string(), "Lexical Error!: Token \"CLOSE\" leaves the DEFAULT mode at line: 1, column: 2");
+}
+
+#[test]
+pub fn pushing_an_undefined_mode_is_an_error() {
+    let rules = vec![TokenSpec::build("QUOTE", r#"""#).push_mode("STRING")];
+
+    let errors = Lexer::new(rules).err().unwrap();
+
+    assert_eq!(errors, vec!["Token \"QUOTE\" pushes the undefined mode STRING"]);
+}
+
+#[test]
+pub fn tokens_have_one_based_positions() {
+    let rules = vec![
+        TokenSpec::build("IDENTIFIER", r"[\w\u{80}-\u{10FFFF}]+"),
+        TokenSpec::build("STRING", r#""[^"]*""#),
+        TokenSpec::build_ignorable("WHITESPACE", r"\s+"),
+    ];
+
+    let lexer = Lexer::new(rules).unwrap();
+    let tokens = lexer
+        .tokens("año \"a\nb\" c\n  ñ")
+        .map(|token| token.map(|t| (t.fragment, t.line, t.column, t.start, t.end)))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(
+        tokens,
+        vec![("año", 1, 1, 0, 4), ("\"a\nb\"", 1, 5, 5, 10), ("c", 2, 4, 11, 12), ("ñ", 3, 3, 15, 17)]
+    );
+}
+
+#[test]
+pub fn tokens_are_lexed_lazily() {
+    let rules = vec![TokenSpec::build("A", r"a")];
+
+    let lexer = Lexer::new(rules).unwrap();
+    let mut tokens = lexer.tokens("aa?a");
+
+    assert_eq!(tokens.next().unwrap().unwrap().start, 0);
+    assert_eq!(tokens.next().unwrap().unwrap().start, 1);
+    assert_eq!(tokens.checkpoint().offset, 2);
+    assert!(tokens.next().unwrap().is_err());
+    assert_eq!(tokens.next().unwrap().unwrap().start, 3);
+    assert!(tokens.next().is_none());
+}
+
+#[test]
+pub fn relex_restarts_before_tokens_that_read_the_edit() {
+    let rules = vec![
+        TokenSpec::build("NUMBER", r"\d+(\.\d+)?"),
+        TokenSpec::build("DOT", r"\."),
+        TokenSpec::build("IDENTIFIER", r"\w+"),
+        TokenSpec::build_ignorable("WHITESPACE", r"\s+"),
+    ];
+    let lexer = Lexer::new(rules).unwrap();
+
+    let before = "ab 12.x";
+    let mut stream = lexer.tokens(before);
+    let mut checkpoints = vec![stream.checkpoint()];
+    while stream.next().is_some() {
+        checkpoints.push(stream.checkpoint());
+    }
+
+    // `12` was lexed by reading `.x`, so editing `x` must lex it again
+    let after = "ab 12.5";
+    let relexed = lexer
+        .relex(after, &checkpoints, 6)
+        .map(|token| token.unwrap().fragment)
+        .collect::<Vec<_>>();
+
+    assert_eq!(relexed, vec!["12.5"]);
+}
+
+#[test]
+pub fn relex_restarts_before_tokens_that_read_an_edited_multibyte_char() {
+    let rules = vec![TokenSpec::build("WORD", r"[a-zê]+")];
+    let lexer = Lexer::new(rules).unwrap();
+
+    let before = "abé";
+    let mut stream = lexer.tokens(before);
+    let mut checkpoints = vec![stream.checkpoint()];
+    while stream.next().is_some() {
+        checkpoints.push(stream.checkpoint());
+    }
+
+    // `ab` stopped at `é`, whose second byte is the only one the edit changes
+    let after = "abê";
+    let relexed = lexer
+        .relex(after, &checkpoints, 3)
+        .map(|token| token.unwrap().fragment)
+        .collect::<Vec<_>>();
+
+    assert_eq!(relexed, vec!["abê"]);
+}
+
+#[test]
+pub fn resume_keeps_the_mode_stack() {
+    let rules = vec![
+        TokenSpec::build("QUOTE", r#"""#).push_mode("STRING"),
+        TokenSpec::build("IDENTIFIER", r"\w+"),
+        TokenSpec::build("TEXT", r#"[^"]+"#).in_mode("STRING"),
+        TokenSpec::build("QUOTE", r#"""#).in_mode("STRING").pop_mode(),
+    ];
+    let lexer = Lexer::new(rules).unwrap();
+
+    let input = r#"a"b c"d"#;
+    let mut stream = lexer.tokens(input);
+    stream.next();
+    stream.next();
+    let checkpoint = stream.checkpoint();
+
+    let resumed = lexer
+        .resume(input, &checkpoint)
+        .map(|token| token.unwrap())
+        .map(|t| (t.kind, t.fragment, t.column))
+        .collect::<Vec<_>>();
+
+    assert_eq!(checkpoint.modes, vec!["DEFAULT", "STRING"]);
+    assert_eq!(resumed, vec![("TEXT", "b c", 3), ("QUOTE", "\"", 6), ("IDENTIFIER", "d", 7)]);
+}
+
+#[test]
+pub fn lexer_without_specs_matches_nothing() {
+    let lexer = Lexer::<&str>::new(vec![]).unwrap();
+
+    assert!(lexer.split("").unwrap().is_empty());
+    assert!(lexer.split("a").is_err());
+}
+
+#[test]
+pub fn errors_carry_the_skipped_span() {
+    let rules = vec![
+        TokenSpec::build("IDENTIFIER", r"\w+"),
+        TokenSpec::build_ignorable("WHITESPACE", r"\s+"),
+    ];
+
+    let lexer = Lexer::new(rules).unwrap();
+    let errors = lexer.split("a\n b?c").err().unwrap();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, LexErrorKind::UnexpectedCharacter('?'));
+    assert_eq!((errors[0].fragment, errors[0].line, errors[0].column, errors[0].start, errors[0].end), ("?", 2, 3, 4, 5));
+}
+
+#[test]
+pub fn recovery_skips_to_whitespace() {
+    let rules = vec![
+        TokenSpec::build("IDENTIFIER", r"[a-z]+"),
+        TokenSpec::build_ignorable("WHITESPACE", r"\s+"),
+    ];
+
+    let lexer = Lexer::new(rules).unwrap().with_recovery(Recovery::SkipToWhitespace);
+    let (tokens, errors) = lexer.split_with_errors("ab 1cd?e fg 9");
+
+    assert_eq!(tokens.iter().map(|t| t.fragment).collect::<Vec<_>>(), vec!["ab", "fg"]);
+    assert_eq!(
+        errors.iter().map(|e| (e.fragment, e.start, e.end)).collect::<Vec<_>>(),
+        vec![("1cd?e", 3, 8), ("9", 12, 13)]
+    );
+}
+
+#[test]
+pub fn recovery_skips_to_a_sync_token() {
+    let rules = vec![
+        TokenSpec::build("SEMICOLON", r";"),
+        TokenSpec::build("IDENTIFIER", r"[a-z]+"),
+        TokenSpec::build_ignorable("WHITESPACE", r"\s+"),
+    ];
+
+    let lexer = Lexer::new(rules).unwrap().with_recovery(Recovery::SkipToToken(vec!["SEMICOLON"]));
+    let (tokens, errors) = lexer.split_with_errors("a; b ? c d; e");
+
+    assert_eq!(tokens.iter().map(|t| t.fragment).collect::<Vec<_>>(), vec!["a", ";", "b", ";", "e"]);
+    assert_eq!(errors.len(), 1);
+    assert_eq!((errors[0].fragment, errors[0].column), ("? c d", 6));
+}
+
+#[test]
+pub fn recovery_without_a_sync_token_skips_to_the_end() {
+    let rules = vec![
+        TokenSpec::build("SEMICOLON", r";"),
+        TokenSpec::build("IDENTIFIER", r"[a-z]+"),
+    ];
+
+    let lexer = Lexer::new(rules).unwrap().with_recovery(Recovery::SkipToToken(vec!["SEMICOLON"]));
+    let errors = lexer.split("a?bc").err().unwrap();
+
+    assert_eq!(errors.iter().map(|e| (e.fragment, e.start, e.end)).collect::<Vec<_>>(), vec![("?bc", 1, 4)]);
+}
+
+#[test]
+pub fn error_tokens_take_the_place_of_errors() {
+    let rules = vec![
+        TokenSpec::build("NUMBER", r"\d+"),
+        TokenSpec::build("PLUS", r"\+"),
+        TokenSpec::build_ignorable("WHITESPACE", r"\s+"),
+    ];
+
+    let lexer = Lexer::new(rules).unwrap().with_error_token("ERROR");
+    let tokens = lexer.split("1 + $ + 2").unwrap();
+
+    assert_eq!(
+        tokens.iter().map(|t| (t.kind, t.fragment, t.column)).collect::<Vec<_>>(),
+        vec![("NUMBER", "1", 1), ("PLUS", "+", 3), ("ERROR", "$", 5), ("PLUS", "+", 7), ("NUMBER", "2", 9)]
+    );
+}
+
+#[test]
+pub fn relex_restarts_before_recoveries_that_read_the_edit() {
+    let rules = vec![
+        TokenSpec::build("IDENTIFIER", r"[a-z]+"),
+        TokenSpec::build_ignorable("WHITESPACE", r"\s+"),
+    ];
+    let lexer = Lexer::new(rules).unwrap().with_recovery(Recovery::SkipToWhitespace);
+
+    let before = "ab ?cd ef";
+    let mut stream = lexer.tokens(before);
+    let mut checkpoints = vec![stream.checkpoint()];
+    while stream.next().is_some() {
+        checkpoints.push(stream.checkpoint());
+    }
+
+    // Skipping `?` read up to the space after `cd`, so editing `d` must skip it again
+    let after = "ab ?c d ef";
+    let relexed = lexer
+        .relex(after, &checkpoints, 5)
+        .map(|token| token.map_or_else(|e| e.fragment, |t| t.fragment))
+        .collect::<Vec<_>>();
+
+    assert_eq!(relexed, vec!["?c", "d", "ef"]);
+}
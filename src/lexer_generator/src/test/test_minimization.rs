@@ -0,0 +1,63 @@
+use crate::RegexParser;
+use crate::automata::{DEAD_STATE, LexerDFA, LexerNFA, NfaBuild, minimize};
+
+fn build_dfa(rules: Vec<(&str, &'static str)>) -> LexerDFA<&'static str> {
+    let parser = RegexParser::new();
+    let tagged_automata = rules
+        .into_iter()
+        .map(|(pattern, kind)| (NfaBuild::new().build_from_regex(&parser.parse(pattern).unwrap()), kind))
+        .collect::<Vec<_>>();
+    LexerDFA::new(&LexerNFA::new(&tagged_automata))
+}
+
+#[test]
+pub fn equivalent_states_are_merged() {
+    // After `a` or `c` the same suffix is expected
+    let dfa = build_dfa(vec![("ab|cb", "WORD")]);
+
+    assert_eq!(dfa.state_count(), 3);
+    assert_eq!(dfa.class_count, 3);
+}
+
+#[test]
+pub fn accepting_states_of_different_kinds_are_kept_apart() {
+    let dfa = build_dfa(vec![("a", "A"), ("b", "B")]);
+
+    assert_eq!(dfa.state_count(), 3);
+    let after_a = dfa.next_state(dfa.start_state, 'a').unwrap();
+    let after_b = dfa.next_state(dfa.start_state, 'b').unwrap();
+    assert_ne!(after_a, after_b);
+    assert_eq!(dfa.accepting_states[after_a], Some("A"));
+    assert_eq!(dfa.accepting_states[after_b], Some("B"));
+}
+
+#[test]
+pub fn keywords_share_the_identifier_tail() {
+    let dfa = build_dfa(vec![
+        ("let", "LET"),
+        ("in", "IN"),
+        ("[a-z]+", "IDENTIFIER"),
+    ]);
+
+    // start, l, le, let, i, in and any other identifier
+    assert_eq!(dfa.state_count(), 7);
+    let letter = dfa.next_state(dfa.start_state, 'x').unwrap();
+    let after_let = "let".chars().fold(dfa.start_state, |state, c| dfa.next_state(state, c).unwrap());
+    assert_eq!(dfa.next_state(after_let, 'x'), Some(letter));
+    assert_eq!(dfa.accepting_states[after_let], Some("LET"));
+    assert_eq!(dfa.next_state(dfa.start_state, '1'), None);
+}
+
+#[test]
+pub fn states_that_cannot_accept_are_dead() {
+    // 0 -a-> 1 -b-> 2 (accepting), and 0 -c-> 3, which never accepts
+    let table = vec![
+        1, DEAD_STATE, 3,
+        DEAD_STATE, 2, DEAD_STATE,
+        DEAD_STATE, DEAD_STATE, DEAD_STATE,
+        DEAD_STATE, DEAD_STATE, 3,
+    ];
+    let accepting = vec![None, None, Some("AB"), None];
+
+    assert_eq!(minimize(&table, 3, &accepting, 0), vec![0, 1, 2, DEAD_STATE]);
+}
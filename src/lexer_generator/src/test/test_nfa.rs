@@ -555,4 +555,57 @@ pub fn match_keyword_while_with_suffix() {
     assert!(nfa.simulate("while\r".chars().collect()));
     assert!(nfa.simulate("while()".chars().collect()));
     assert!(nfa.simulate("while (\n\r\t)".chars().collect()));
-}
\ No newline at end of file
+}
+#[test]
+pub fn match_bounded_repetition() {
+    // (ab){2,3}
+    let regex = Expression::UnOp(UnOp {
+        operand: Box::new(Expression::BinOp(BinOp {
+            left: Box::new(Expression::Atom(MatchableAtom::Atom(RegexAtom::Char('a')))),
+            right: Box::new(Expression::Atom(MatchableAtom::Atom(RegexAtom::Char('b')))),
+            op: BinaryOperator::Concat,
+        })),
+        op: UnaryOperator::Repeat { min: 2, max: Some(3) },
+    });
+
+    let mut builder = NfaBuild::new();
+    let nfa = builder.build_from_regex(&regex);
+
+    assert!(!nfa.simulate("".chars().collect()));
+    assert!(!nfa.simulate("ab".chars().collect()));
+    assert!(nfa.simulate("abab".chars().collect()));
+    assert!(nfa.simulate("ababab".chars().collect()));
+    assert!(!nfa.simulate("abababab".chars().collect()));
+    assert!(!nfa.simulate("aba".chars().collect()));
+}
+
+#[test]
+pub fn match_unbounded_repetition() {
+    // a{2,}
+    let regex = Expression::UnOp(UnOp {
+        operand: Box::new(Expression::Atom(MatchableAtom::Atom(RegexAtom::Char('a')))),
+        op: UnaryOperator::Repeat { min: 2, max: None },
+    });
+
+    let mut builder = NfaBuild::new();
+    let nfa = builder.build_from_regex(&regex);
+
+    assert!(!nfa.simulate("a".chars().collect()));
+    assert!(nfa.simulate("aa".chars().collect()));
+    assert!(nfa.simulate("aaaaaaa".chars().collect()));
+}
+
+#[test]
+pub fn match_empty_repetition() {
+    // a{0}
+    let regex = Expression::UnOp(UnOp {
+        operand: Box::new(Expression::Atom(MatchableAtom::Atom(RegexAtom::Char('a')))),
+        op: UnaryOperator::Repeat { min: 0, max: Some(0) },
+    });
+
+    let mut builder = NfaBuild::new();
+    let nfa = builder.build_from_regex(&regex);
+
+    assert!(nfa.simulate("".chars().collect()));
+    assert!(!nfa.simulate("a".chars().collect()));
+}
@@ -37,7 +37,7 @@ fn test_parse_whitespace() {
     let parser = RegexParser::new();
     let regex = parser.parse(r"\s").unwrap();
 
-    assert_eq!(regex.to_string(), " ");
+    assert_eq!(regex.to_string(), "[\t-\r ]");
 }
 
 #[test]
@@ -261,7 +261,7 @@ fn parse_wildcard_and_group() {
     let parser = RegexParser::new();
     let regex = parser.parse(r"(.)\s").unwrap();
 
-    assert_eq!(regex.to_string(), "(. )");
+    assert_eq!(regex.to_string(), "(.[\t-\r ])");
 }
 
 #[test]
@@ -270,4 +270,85 @@ fn parse_camel_case_identifier() {
     let regex = parser.parse(r"[a-z]+[A-Z][a-zA-Z0-9]*").unwrap();
 
     assert_eq!(regex.to_string(), "(([a-z]+[A-Z])[0-9A-Za-z]*)");
-}
\ No newline at end of file
+}
+
+#[test]
+pub fn parse_char_set_of_single_chars() {
+    let parser = RegexParser::new();
+    let regex = parser.parse(r"[abc][a-z_]").unwrap();
+
+    assert_eq!(regex.to_string(), "([abc][_a-z])");
+}
+
+#[test]
+pub fn parse_char_set_with_escapes_and_operators() {
+    let parser = RegexParser::new();
+    let regex = parser.parse(r#"[^"\\][(|)*.\]]"#).unwrap();
+
+    assert_eq!(regex.to_string(), r#"([^"\][()*.]|])"#);
+}
+
+#[test]
+pub fn parse_shorthand_classes() {
+    let parser = RegexParser::new();
+    let regex = parser.parse(r"\d\w[\d_]").unwrap();
+
+    assert_eq!(regex.to_string(), "(([0-9][0-9A-Z_a-z])[0-9_])");
+}
+
+#[test]
+pub fn parse_negated_shorthand_class() {
+    let parser = RegexParser::new();
+    let regex = parser.parse(r"\D").unwrap();
+
+    assert_eq!(regex.to_string(), "[^0-9]");
+}
+
+#[test]
+pub fn parse_repetition() {
+    let parser = RegexParser::new();
+    let regex = parser.parse(r"a{2}b{2,}c{2,13}").unwrap();
+
+    assert_eq!(regex.to_string(), "((a{2}b{2,})c{2,13})");
+}
+
+#[test]
+pub fn parse_inverted_repetition_fails() {
+    let parser = RegexParser::new();
+
+    assert!(parser.parse(r"a{3,2}").is_err());
+}
+
+#[test]
+pub fn parse_repetition_count_over_the_limit_fails() {
+    let parser = RegexParser::new();
+
+    assert!(parser.parse(r"a{1000}").is_ok());
+    assert!(parser.parse(r"a{1001}").is_err());
+    assert!(parser.parse(r"a{1,20000}").is_err());
+    assert!(parser.parse(r"a{99999999999999999999999,}").is_err());
+}
+
+#[test]
+pub fn parse_escaped_braces_and_quote() {
+    let parser = RegexParser::new();
+    let regex = parser.parse(r#"\{\"\}"#).unwrap();
+
+    assert_eq!(regex.to_string(), r#"(({")})"#);
+}
+
+#[test]
+pub fn parse_unicode_escape() {
+    let parser = RegexParser::new();
+    let regex = parser.parse(r"\u{41}\u{1F600}").unwrap();
+
+    assert_eq!(regex.to_string(), "(A\u{1F600})");
+}
+
+#[test]
+pub fn parse_invalid_unicode_escape_fails() {
+    let parser = RegexParser::new();
+
+    assert!(parser.parse(r"\u{D800}").is_err());
+    assert!(parser.parse(r"\u{110000}").is_err());
+}
@@ -11,6 +11,7 @@ use lalrpop_util::lalrpop_mod;
 pub mod ast_nodes;
 pub mod builtin;
 pub mod codegen;
+mod lexer;
 mod parser_w_errors;
 pub mod semantic_analyzer;
 mod tokens;
@@ -22,6 +23,7 @@ lalrpop_mod!(pub parser);
 
 use crate::builtin::FunctionInjector;
 use codegen::CodeGenerator;
+use codegen::target::Target;
 use std::path::Path;
 
 /// Entry point of the Hulk compiler.
@@ -32,7 +34,8 @@ use std::path::Path;
 /// 3. Parses the code into an AST.
 /// 4. Performs semantic analysis on the AST.
 /// 5. Generates LLVM IR from the AST.
-/// 6. Uses `clang` to compile the IR into a native executable.
+/// 6. Uses `clang` to compile the IR into a native executable (with the `llvm`
+///    feature, LLVM emits an object file that the system C compiler links).
 fn main() {
     // Show ASCII art logo in green
     println!("\x1b[32m");
@@ -48,13 +51,29 @@ fn main() {
 
     // Validate command-line arguments
     let args: Vec<String> = std::env::args().collect();
-    if args.len() < 2 {
-        eprintln!("Usage: {} <input_file>", args[0]);
-        std::process::exit(1);
+    let mut filename = None;
+    let mut target = Target::host();
+    let mut debug_info = false;
+    let mut checked = false;
+    let mut options = args.iter().skip(1);
+    while let Some(arg) = options.next() {
+        match arg.as_str() {
+            "--target" => {
+                let triple = options.next().unwrap_or_else(|| usage(&args[0]));
+                target = Target::from_triple(triple).unwrap_or_else(|err| {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                });
+            }
+            "-g" => debug_info = true,
+            "--checked" => checked = true,
+            _ if filename.is_none() => filename = Some(arg),
+            _ => usage(&args[0]),
+        }
     }
 
     // Read input source file
-    let filename = &args[1];
+    let filename = filename.unwrap_or_else(|| usage(&args[0]));
     let raw_input = std::fs::read_to_string(filename)
         .expect(&format!("Failed to read input file: {}", filename));
 
@@ -85,8 +104,16 @@ fn main() {
             }
 
             // Generate LLVM IR
-            let mut codegen = CodeGenerator::new();
-            let llvm_ir = codegen.generate(&mut expr);
+            let mut codegen = CodeGenerator::with_target(target.clone());
+            codegen.set_source(filename, &input, missplacement);
+            if debug_info {
+                codegen.enable_debug_info();
+            }
+            if checked {
+                codegen.enable_checks();
+            }
+            let module = codegen.generate_module(&mut expr);
+            let llvm_ir = module.to_string();
 
             // Ensure output directory exists
             if !Path::new("hulk").exists() {
@@ -98,13 +125,38 @@ fn main() {
             std::fs::write(ir_path, &llvm_ir).expect("Failed to write LLVM IR");
 
             // Define output executable path depending on OS
-            let executable = if cfg!(windows) {
+            let executable = if target.is_windows() {
                 "hulk/output.exe"
             } else {
                 "hulk/output"
             };
 
+            // Verify the module and emit an object file through LLVM, then link it
+            // and the runtime with the system C compiler
+            #[cfg(feature = "llvm")]
+            let object_path = "hulk/output.o";
+            #[cfg(feature = "llvm")]
+            if let Err(message) =
+                codegen::llvm_backend::emit_object(module, &input, missplacement, Path::new(object_path))
+            {
+                eprintln!("\x1b[31m{}\x1b[0m", message);
+                std::process::exit(4);
+            }
+
+            // The system toolchain can only link programs for the host
+            if !target.is_host() {
+                println!("Generated code for {} in hulk/, link it with a toolchain for that target", target.triple);
+                return;
+            }
+
+            #[cfg(feature = "llvm")]
+            let status = std::process::Command::new("cc")
+                .args([object_path, "runtime.c", "-o", executable, "-lm"])
+                .status()
+                .expect("Failed to link with cc");
+
             // Compile using clang and link runtime
+            #[cfg(not(feature = "llvm"))]
             let status = std::process::Command::new("clang")
                 .args(&[ir_path, "runtime.c", "-o", executable, "-lm"]) 
                 .status()
@@ -125,3 +177,9 @@ fn main() {
         }
     }
 }
+
+/// Prints the command-line usage and exits.
+fn usage(program: &str) -> ! {
+    eprintln!("Usage: {} [-g] [--checked] [--target <triple>] <input_file>", program);
+    std::process::exit(1);
+}
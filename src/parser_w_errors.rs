@@ -1,6 +1,7 @@
-//! Custom parser wrapper that integrates LALRPOP-generated parser and enhances syntax error messages.
+//! Custom parser wrapper that feeds the HULK lexer to the LALRPOP-generated parser and enhances syntax error messages.
 
 use crate::ast_nodes::program::Program;
+use crate::lexer::HulkLexer;
 use crate::parser::ProgramParser;
 use lalrpop_util::ParseError;
 use std::collections::HashSet;
@@ -8,6 +9,7 @@ use std::collections::HashSet;
 /// Wrapper around the LALRPOP `ProgramParser` that handles syntax errors
 /// and improves their reporting with line/column context and caret diagnostics.
 pub struct Parser {
+    lexer: HulkLexer,
     core: ProgramParser,
     /// Line offset due to code injected before the user program.
     pub missplacement: i32,
@@ -21,6 +23,7 @@ impl Parser {
     ///                     used to adjust error line numbers accordingly.
     pub fn new(missplacement: i32) -> Self {
         Parser {
+            lexer: HulkLexer::new(),
             core: ProgramParser::new(),
             missplacement,
         }
@@ -111,7 +114,7 @@ impl Parser {
     /// * `Err(Vec<String>)` with diagnostic error messages if parsing fails.
     pub fn parse(&self, input: &str) -> Result<Program, Vec<String>> {
         let mut errors = Vec::new();
-        let result = self.core.parse(input);
+        let result = self.core.parse(self.lexer.tokenize(input).tokens);
 
         match result {
             Ok(program) => Ok(program),
@@ -147,7 +150,7 @@ impl Parser {
                 }
                 ParseError::UnrecognizedToken { token, expected } => {
                     let (start, token_val, end) = token;
-                    let token_value = &token_val.1;
+                    let token_value = &token_val.text;
                     let token_str = &input[start..end];
 
                     let (line, col, line_str, _) =
@@ -178,12 +181,18 @@ impl Parser {
 
                     errors.push(format!(
                         "\x1b[31mSyntax Error (line {}, column {}): Extra token `{}`\n{}\n{}\x1b[0m",
-                        line, col, token_val.1, line_str, caret
+                        line, col, token_val.text, line_str, caret
                     ));
                     Err(errors)
                 }
                 ParseError::User { error } => {
-                    errors.push(format!("\x1b[31mSyntax Error: {}\x1b[0m", error));
+                    let (line, col, line_str, _) =
+                        Self::get_line_context(input, error.offset, self.missplacement);
+                    let caret = Self::build_caret_point(col);
+                    errors.push(format!(
+                        "\x1b[31mLexical Error (line {}, column {}): {}\n{}\n{}\x1b[0m",
+                        line, col, error, line_str, caret
+                    ));
                     Err(errors)
                 }
             },
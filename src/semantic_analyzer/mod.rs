@@ -1,3 +1,4 @@
 pub mod return_types;
 pub mod semantic_errors;
-pub mod semantic_analyzer;
\ No newline at end of file
+pub mod semantic_analyzer;
+pub mod symbol_table;
\ No newline at end of file
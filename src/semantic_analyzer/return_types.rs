@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use crate::ast_nodes::type_def::TypeDefNode;
+use super::symbol_table::SymbolTable;
 
 /// Holds information about a function signature, including its name,
 /// the list of argument names and their types, and the return type.
@@ -41,8 +42,8 @@ impl FunctionInfo {
 /// and the currently analyzed type and function.
 #[derive(Debug, Clone, PartialEq)]
 pub struct SemanticContext {
-    /// Maps variable names to their respective type names.
-    pub symbols: HashMap<String, String>,
+    /// Lexically scoped table mapping variable names to their types and declaration sites.
+    pub symbols: SymbolTable,
 
     /// Maps function names to their `FunctionInfo`.
     pub declared_functions: HashMap<String, FunctionInfo>,
@@ -1,5 +1,6 @@
 use super::return_types::{FunctionInfo, SemanticContext};
 use super::semantic_errors::SemanticError;
+use super::symbol_table::SymbolTable;
 use crate::ast_nodes::binary_op::BinaryOpNode;
 use crate::ast_nodes::block::BlockNode;
 use crate::ast_nodes::destructive_assign::DestructiveAssignNode;
@@ -13,6 +14,7 @@ use crate::ast_nodes::literals::{
     BooleanLiteralNode, IdentifierNode, NumberLiteralNode, StringLiteralNode,
 };
 use crate::ast_nodes::print::PrintNode;
+use crate::ast_nodes::read::{ReadKind, ReadNode};
 use crate::ast_nodes::program::{Program, Statement};
 use crate::ast_nodes::type_def::{TypeDefNode, TypeMember};
 use crate::ast_nodes::type_instance::TypeInstanceNode;
@@ -29,16 +31,16 @@ use std::collections::HashMap;
 
 /// SemanticAnalyzer performs semantic analysis of the AST,
 /// validating types, functions, variables, and type hierarchy.
-/// It maintains semantic context, scope stack, errors, and the type tree.
+/// It maintains semantic context, errors, and the type tree.
 pub struct SemanticAnalyzer {
     /// Current context containing declared symbols, functions, and types.
     context: SemanticContext,
-    /// Stack of contexts to handle nested scopes.
-    scopes: Vec<SemanticContext>,
     /// List of semantic errors found during analysis.
     errors: Vec<SemanticError>,
     /// Tree representing the hierarchy and relations of types.
     types_tree: TypeTree,
+    /// Span of the first expression that had to be typed as `Unknown` during the analysis.
+    first_unknown: Option<Span>,
 }
 
 impl SemanticAnalyzer {
@@ -46,31 +48,36 @@ impl SemanticAnalyzer {
     pub fn new() -> Self {
         let mut s_a = Self {
             context: SemanticContext {
-                symbols: HashMap::new(),
+                symbols: SymbolTable::new(),
                 declared_functions: HashMap::new(),
                 declared_types: HashMap::new(),
                 current_type: None,
                 current_function: None,
             },
-            scopes: Vec::new(),
             errors: Vec::new(),
             types_tree: TypeTree::new(),
+            first_unknown: None,
         };
         s_a.context
             .symbols
-            .insert("PI".to_string(), "Number".to_string());
+            .declare("PI".to_string(), "Number".to_string(), Span::new(0, 0));
         s_a.context
             .symbols
-            .insert("E".to_string(), "Number".to_string());
+            .declare("E".to_string(), "Number".to_string(), Span::new(0, 0));
         s_a
     }
-     /// Enters a new scope by pushing the current context onto the stack.
+    /// Enters a new lexical scope nested in the current one.
     fn enter_scope(&mut self) {
-        self.scopes.push(self.context.clone());
+        self.context.symbols.enter_scope();
     }
-    /// Exits the current scope, restoring the previous context from the stack.
+    /// Exits the current lexical scope, returning to its parent scope.
     fn exit_scope(&mut self) {
-        self.context = self.scopes.pop().unwrap();
+        self.context.symbols.exit_scope();
+    }
+    /// Returns the declaration span of the variable used at the given byte offset,
+    /// once the program has been analyzed.
+    pub fn definition_at(&self, offset: usize) -> Option<Span> {
+        self.context.symbols.definition_at(offset)
     }
     /// Adds a new semantic error to the list of errors.
     fn new_error(&mut self, error: SemanticError) {
@@ -87,6 +94,15 @@ impl SemanticAnalyzer {
         for statement in &mut node.statements {
             statement.accept(self);
         }
+        if self.errors.is_empty()
+            && let Some(span) = self.first_unknown
+        {
+            // Every `Unknown` should come from a reported error; never let one reach codegen
+            self.new_error(SemanticError::UnknownError(
+                "Could not infer the type of an expression".to_string(),
+                span,
+            ));
+        }
         if self.errors.is_empty() {
             Ok(())
         } else {
@@ -98,6 +114,55 @@ impl SemanticAnalyzer {
     pub fn get_built_in_types(&self, built_in: &BuiltInTypes) -> TypeNode {
         self.types_tree.get_type(built_in.as_str()).unwrap()
     }
+
+    /// Returns the `Unknown` type, used as the type of expressions whose analysis failed.
+    ///
+    /// `Unknown` silently conforms to every type, so only the root cause of an error
+    /// is reported instead of one error per enclosing expression. `span` is that of the
+    /// expression being typed, where the error is reported if no other one was.
+    fn unknown_type(&mut self, span: Span) -> TypeNode {
+        self.first_unknown.get_or_insert(span);
+        self.get_built_in_types(&BuiltInTypes::Unknown)
+    }
+
+    /// Checks whether `node` is the given built-in type, treating `Unknown` as a match.
+    fn is_type(&self, node: &TypeNode, built_in: &BuiltInTypes) -> bool {
+        node.type_name == built_in.as_str() || node.type_name == BuiltInTypes::Unknown.as_str()
+    }
+
+    /// Checks whether a value of type `found` can be stored where `expected` is required.
+    fn conforms_to(&self, expected: &str, found: &TypeNode) -> bool {
+        match self.types_tree.get_type(expected) {
+            Some(expected_type) => self.types_tree.is_ancestor(&expected_type, found),
+            None => false,
+        }
+    }
+
+    /// Resolves the declared type of a variable or attribute and checks that its
+    /// initial value conforms to it. Returns the declared type, or Unknown if the
+    /// declared type does not exist.
+    fn check_declared_type(
+        &mut self,
+        name: &str,
+        signature: &str,
+        value_type: &TypeNode,
+        span: Span,
+    ) -> TypeNode {
+        if let Some(declared_type) = self.types_tree.get_type(signature) {
+            if !self.types_tree.is_ancestor(&declared_type, value_type) {
+                self.new_error(SemanticError::InvalidDeclarationType(
+                    name.to_string(),
+                    declared_type.type_name.clone(),
+                    value_type.type_name.clone(),
+                    span,
+                ));
+            }
+            declared_type
+        } else {
+            self.new_error(SemanticError::UndefinedType(signature.to_string(), span));
+            self.unknown_type(span)
+        }
+    }
     
     /// Collects function names and signatures declared in the program,
     /// validating parameter types and reporting redefinitions or undefined types.
@@ -128,7 +193,7 @@ impl SemanticAnalyzer {
                             ));
                             arg_types.push((
                                 param.name.clone(),
-                                self.get_built_in_types(&BuiltInTypes::Unknown).type_name,
+                                self.unknown_type(param.span).type_name,
                             ));
                         }
                     }
@@ -219,9 +284,9 @@ impl SemanticAnalyzer {
                         variables.insert(
                             prop.identifier.clone(),
                             Box::new(
-                                prop.node_type
-                                    .as_ref()
-                                    .map(|t| t.type_name.clone())
+                                prop.signature
+                                    .clone()
+                                    .or_else(|| prop.node_type.as_ref().map(|t| t.type_name.clone()))
                                     .unwrap_or_else(|| "Unknown".to_string())
                             ),
                         );
@@ -301,10 +366,10 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
         self.enter_scope();
         self.context
             .symbols
-            .insert(node.variable.clone(), "Number".to_string());
+            .declare(node.variable.clone(), "Number".to_string(), node.span);
         let start_type = node.start.accept(self);
         let end_type = node.end.accept(self);
-        if start_type.type_name != "Number" {
+        if !self.is_type(&start_type, &BuiltInTypes::Number) {
             self.new_error(SemanticError::InvalidTypeArgument(
                 "for loop".to_string(),
                 start_type.type_name.clone(),
@@ -314,7 +379,7 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                 node.span.clone(),
             ));
         }
-        if end_type.type_name != "Number" {
+        if !self.is_type(&end_type, &BuiltInTypes::Number) {
             self.new_error(SemanticError::InvalidTypeArgument(
                 "for loop".to_string(),
                 end_type.type_name.clone(),
@@ -331,15 +396,21 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
     }
 
     /// Visits a destructive assignment node, validating that assignment
-    /// targets are identifiers or type property accesses and updating context accordingly.
+    /// targets are identifiers or type property accesses and that the new value
+    /// conforms to the type the target was declared with.
     fn visit_destructive_assign(&mut self, node: &mut DestructiveAssignNode) -> TypeNode {
         match *node.identifier.clone() {
             Expression::Identifier(ref id) => {
-                if self.context.symbols.contains_key(&id.value) {
+                if let Some(symbol) = self.context.symbols.lookup(&id.value).cloned() {
                     let new_type = node.expression.accept(self);
-                    self.context
-                        .symbols
-                        .insert(id.value.clone(), new_type.type_name.clone());
+                    if !self.conforms_to(&symbol.type_name, &new_type) {
+                        self.new_error(SemanticError::InvalidDestructiveAssignment(
+                            id.value.clone(),
+                            symbol.type_name.clone(),
+                            new_type.type_name.clone(),
+                            node.span,
+                        ));
+                    }
                     node.set_type(new_type.clone());
                     new_type
                 } else {
@@ -347,19 +418,27 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                         id.value.clone(),
                         id.span.clone(),
                     ));
-                    self.get_built_in_types(&BuiltInTypes::Unknown)
+                    self.unknown_type(id.span)
                 }
             }
             Expression::TypePropAccess(ref mut access_node) => {
-                let mut object_type = access_node.object.accept(self);
-                if let Some(_property_type) =
-                    object_type.variables.get_mut(access_node.member.as_ref())
+                let object_type = access_node.object.accept(self);
+                if object_type.type_name == BuiltInTypes::Unknown.as_str() {
+                    node.expression.accept(self);
+                    return self.unknown_type(access_node.span);
+                }
+                if let Some(property_type) =
+                    object_type.variables.get(access_node.member.as_ref())
                 {
                     let new_type = node.expression.accept(self);
-                    object_type.variables.insert(
-                        access_node.member.as_ref().clone(),
-                        Box::new(new_type.type_name.clone()),
-                    );
+                    if !self.conforms_to(property_type, &new_type) {
+                        self.new_error(SemanticError::InvalidDestructiveAssignment(
+                            access_node.member.as_ref().clone(),
+                            property_type.as_ref().clone(),
+                            new_type.type_name.clone(),
+                            node.span,
+                        ));
+                    }
                     node.set_type(new_type.clone());
                     new_type
                 } else {
@@ -368,12 +447,12 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                         access_node.member.as_ref().clone(),
                         access_node.span.clone(),
                     ));
-                    self.get_built_in_types(&BuiltInTypes::Unknown)
+                    self.unknown_type(access_node.span)
                 }
             }
             _ => {
                 self.new_error(SemanticError::UnknownError("Destructive assignment can only be done to an identifier or type property access".to_string(), node.span.clone()));
-                self.get_built_in_types(&BuiltInTypes::Unknown)
+                self.unknown_type(node.span)
             }
         }
     }
@@ -384,20 +463,28 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
     /// Returns the function's return type node.
     fn visit_function_def(&mut self, node: &mut FunctionDefNode) -> TypeNode {
         self.enter_scope();
-        self.context.current_function = Some(node.name.clone());
+        let previous_function = self.context.current_function.replace(node.name.clone());
         if let Some(function) = self.context.declared_functions.get(&node.name) {
-            for param in &function.arguments_types {
+            for (param_name, param_type) in function.arguments_types.clone() {
+                let declaration = node
+                    .params
+                    .iter()
+                    .find(|param| param.name == param_name)
+                    .map(|param| param.span)
+                    .unwrap_or(node.span);
                 self.context
                     .symbols
-                    .insert(param.0.clone(), param.1.clone());
+                    .declare(param_name, param_type, declaration);
             }
         } else if let Some(current_type) = self.context.current_type.clone() {
             if let Some(type_node) = self.types_tree.get_type(&current_type) {
                 if let Some(function) = type_node.methods.get(&node.name) {
                     for param in &function.params.clone() {
-                        self.context
-                            .symbols
-                            .insert(param.name.clone(), param.signature.clone());
+                        self.context.symbols.declare(
+                            param.name.clone(),
+                            param.signature.clone(),
+                            param.span,
+                        );
                     }
                 } else {
                     self.new_error(SemanticError::UndeclaredFunction(
@@ -418,8 +505,7 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
             ));
         }
         let body_type = node.body.accept(self);
-        let mut return_type_node = self.get_built_in_types(&BuiltInTypes::Unknown);
-        if let Some(func_type) = self.types_tree.get_type(&node.return_type.clone()) {
+        let return_type_node = if let Some(func_type) = self.types_tree.get_type(&node.return_type.clone()) {
             if !self.types_tree.is_ancestor(&func_type, &body_type) {
                 self.new_error(SemanticError::InvalidFunctionReturn(
                     body_type,
@@ -428,14 +514,16 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                     node.span.clone(),
                 ));
             }
-            return_type_node = func_type;
+            func_type
         } else {
             self.new_error(SemanticError::UndefinedType(
                 node.return_type.clone(),
                 node.span.clone(),
             ));
-        }
+            self.unknown_type(node.span)
+        };
         self.exit_scope();
+        self.context.current_function = previous_function;
         node.set_type(return_type_node.clone());
         return_type_node
     }
@@ -462,7 +550,8 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
     /// Reports errors if the identifier or its type is undefined.
     /// Returns the resolved type node or Unknown type if unresolved.
     fn visit_identifier(&mut self, node: &mut IdentifierNode) -> TypeNode {
-        if let Some(return_type) = self.context.symbols.get(&node.value) {
+        if let Some(symbol) = self.context.symbols.resolve(&node.value, node.span) {
+            let return_type = symbol.type_name.clone();
             if let Some(node_type) = self.types_tree.get_type(&return_type) {
                 node.set_type(node_type.clone());
                 node_type.clone()
@@ -471,7 +560,7 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                     return_type.clone(),
                     node.span.clone(),
                 ));
-                self.get_built_in_types(&BuiltInTypes::Unknown)
+                self.unknown_type(node.span)
             }
         } else if node.value == "self" {
             if let Some(current_type) = &self.context.current_type {
@@ -483,21 +572,21 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                         current_type.clone(),
                         node.span.clone(),
                     ));
-                    self.get_built_in_types(&BuiltInTypes::Unknown)
+                    self.unknown_type(node.span)
                 }
             } else {
                 self.new_error(SemanticError::UndefinedIdentifier(
                     node.value.clone(),
                     node.span.clone(),
                 ));
-                self.get_built_in_types(&BuiltInTypes::Unknown)
+                self.unknown_type(node.span)
             }
         } else {
             self.new_error(SemanticError::UndefinedIdentifier(
                 node.value.clone(),
                 node.span.clone(),
             ));
-            self.get_built_in_types(&BuiltInTypes::Unknown)
+            self.unknown_type(node.span)
         }
     }
 
@@ -528,7 +617,7 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                                     ));
                                 } else {
                                     for (index, arg) in arg_types.iter_mut().enumerate() {
-                                        if arg.type_name != func.params[index].signature {
+                                        if !self.conforms_to(&func.params[index].signature, arg) {
                                             self.new_error(SemanticError::InvalidTypeArgument(
                                                 "function".to_string(),
                                                 arg.type_name.clone(),
@@ -550,7 +639,7 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                                         func.return_type.clone(),
                                         node.span.clone(),
                                     ));
-                                    return self.get_built_in_types(&BuiltInTypes::Unknown);
+                                    return self.unknown_type(node.span);
                                 }
                             }
                         }
@@ -595,14 +684,14 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                     func_type.clone(),
                     node.span.clone(),
                 ));
-                self.get_built_in_types(&BuiltInTypes::Unknown)
+                self.unknown_type(node.span)
             }
         } else {
             self.new_error(SemanticError::UndeclaredFunction(
                 node.function_name.clone(),
                 node.span.clone(),
             ));
-            self.get_built_in_types(&BuiltInTypes::Unknown)
+            self.unknown_type(node.span)
         }
     }
 
@@ -610,7 +699,7 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
     /// and returns the type of the loop body.
     fn visit_while_loop(&mut self, node: &mut WhileNode) -> TypeNode {
         let condition_type = node.condition.accept(self);
-        if condition_type != self.get_built_in_types(&BuiltInTypes::Boolean) {
+        if !self.is_type(&condition_type, &BuiltInTypes::Boolean) {
             self.new_error(SemanticError::InvalidConditionType(
                 condition_type,
                 node.span.clone(),
@@ -623,9 +712,10 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
 
      /// Visits a code block node, entering a new scope for the block,
     /// visiting each expression in sequence and returning the type of the last expression.
+    /// An empty block produces no value and is typed as `Object`.
     fn visit_code_block(&mut self, node: &mut BlockNode) -> TypeNode {
         self.enter_scope();
-        let mut last_type = self.get_built_in_types(&BuiltInTypes::Unknown);
+        let mut last_type = self.get_built_in_types(&BuiltInTypes::Object);
         for expr in node.expression_list.expressions.iter_mut() {
             last_type = expr.accept(self);
         }
@@ -647,8 +737,8 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
             | OperatorToken::DIV
             | OperatorToken::MOD
             | OperatorToken::POW => {
-                if left_type == self.get_built_in_types(&BuiltInTypes::Number)
-                    && right_type == self.get_built_in_types(&BuiltInTypes::Number)
+                if self.is_type(&left_type, &BuiltInTypes::Number)
+                    && self.is_type(&right_type, &BuiltInTypes::Number)
                 {
                     node.set_type(self.get_built_in_types(&BuiltInTypes::Number));
                     self.get_built_in_types(&BuiltInTypes::Number)
@@ -659,13 +749,15 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                         node.operator.clone(),
                         node.span.clone(),
                     ));
-                    self.get_built_in_types(&BuiltInTypes::Unknown)
+                    self.unknown_type(node.span)
                 }
             }
 
             OperatorToken::GT | OperatorToken::GTE | OperatorToken::LT | OperatorToken::LTE => {
-                if left_type == self.get_built_in_types(&BuiltInTypes::Number)
-                    && right_type == self.get_built_in_types(&BuiltInTypes::Number)
+                if self.is_type(&left_type, &BuiltInTypes::Number)
+                    && self.is_type(&right_type, &BuiltInTypes::Number)
+                    || self.is_type(&left_type, &BuiltInTypes::String)
+                        && self.is_type(&right_type, &BuiltInTypes::String)
                 {
                     node.set_type(self.get_built_in_types(&BuiltInTypes::Boolean));
                     self.get_built_in_types(&BuiltInTypes::Boolean)
@@ -676,16 +768,16 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                         node.operator.clone(),
                         node.span.clone(),
                     ));
-                    self.get_built_in_types(&BuiltInTypes::Unknown)
+                    self.unknown_type(node.span)
                 }
             }
             OperatorToken::NEQ | OperatorToken::EQ => {
-                if left_type == self.get_built_in_types(&BuiltInTypes::String)
-                    && right_type == self.get_built_in_types(&BuiltInTypes::String)
-                    || left_type == self.get_built_in_types(&BuiltInTypes::Boolean)
-                        && right_type == self.get_built_in_types(&BuiltInTypes::Boolean)
-                    || left_type == self.get_built_in_types(&BuiltInTypes::Number)
-                        && right_type == self.get_built_in_types(&BuiltInTypes::Number)
+                if self.is_type(&left_type, &BuiltInTypes::String)
+                    && self.is_type(&right_type, &BuiltInTypes::String)
+                    || self.is_type(&left_type, &BuiltInTypes::Boolean)
+                        && self.is_type(&right_type, &BuiltInTypes::Boolean)
+                    || self.is_type(&left_type, &BuiltInTypes::Number)
+                        && self.is_type(&right_type, &BuiltInTypes::Number)
                 {
                     node.set_type(self.get_built_in_types(&BuiltInTypes::Boolean));
                     self.get_built_in_types(&BuiltInTypes::Boolean)
@@ -696,13 +788,19 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                         node.operator.clone(),
                         node.span.clone(),
                     ));
-                    self.get_built_in_types(&BuiltInTypes::Unknown)
+                    self.unknown_type(node.span)
                 }
             }
 
-            OperatorToken::CONCAT => {
+            OperatorToken::CONCAT | OperatorToken::DCONCAT => {
                 let string_type = self.get_built_in_types(&BuiltInTypes::String);
-                if left_type == string_type && right_type == string_type {
+                // Numbers and booleans are converted to strings
+                let printable = |ty: &TypeNode| {
+                    [BuiltInTypes::String, BuiltInTypes::Number, BuiltInTypes::Boolean]
+                        .iter()
+                        .any(|built_in| self.is_type(ty, built_in))
+                };
+                if printable(&left_type) && printable(&right_type) {
                     node.set_type(string_type.clone());
                     string_type
                 } else {
@@ -712,12 +810,12 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                         node.operator.clone(),
                         node.span.clone(),
                     ));
-                    self.get_built_in_types(&BuiltInTypes::Unknown)
+                    self.unknown_type(node.span)
                 }
             }
             OperatorToken::AND | OperatorToken::OR => {
-                if left_type == self.get_built_in_types(&BuiltInTypes::Boolean)
-                    && right_type == self.get_built_in_types(&BuiltInTypes::Boolean)
+                if self.is_type(&left_type, &BuiltInTypes::Boolean)
+                    && self.is_type(&right_type, &BuiltInTypes::Boolean)
                 {
                     node.set_type(self.get_built_in_types(&BuiltInTypes::Boolean));
                     self.get_built_in_types(&BuiltInTypes::Boolean)
@@ -728,7 +826,7 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                         node.operator.clone(),
                         node.span.clone(),
                     ));
-                    self.get_built_in_types(&BuiltInTypes::Unknown)
+                    self.unknown_type(node.span)
                 }
             }
             _ => {
@@ -739,7 +837,7 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                     ),
                     node.span.clone(),
                 ));
-                self.get_built_in_types(&BuiltInTypes::Unknown)
+                self.unknown_type(node.span)
             }
         }
     }
@@ -751,7 +849,7 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
 
         match node.operator {
             OperatorToken::NEG => {
-                if operand_type == self.get_built_in_types(&BuiltInTypes::Number) {
+                if self.is_type(&operand_type, &BuiltInTypes::Number) {
                     node.set_type(self.get_built_in_types(&BuiltInTypes::Number));
                     self.get_built_in_types(&BuiltInTypes::Number)
                 } else {
@@ -760,11 +858,11 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                         node.operator.clone(),
                         node.span.clone(),
                     ));
-                    self.get_built_in_types(&BuiltInTypes::Unknown)
+                    self.unknown_type(node.span)
                 }
             }
             OperatorToken::NOT => {
-                if operand_type == self.get_built_in_types(&BuiltInTypes::Boolean) {
+                if self.is_type(&operand_type, &BuiltInTypes::Boolean) {
                     node.set_type(self.get_built_in_types(&BuiltInTypes::Boolean));
                     self.get_built_in_types(&BuiltInTypes::Boolean)
                 } else {
@@ -773,7 +871,7 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                         node.operator.clone(),
                         node.span.clone(),
                     ));
-                    self.get_built_in_types(&BuiltInTypes::Unknown)
+                    self.unknown_type(node.span)
                 }
             }
             _ => {
@@ -784,61 +882,89 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                     ),
                     node.span.clone(),
                 ));
-                self.get_built_in_types(&BuiltInTypes::Unknown)
+                self.unknown_type(node.span)
             }
         }
     }
 
-     /// Visits an if-else node, ensuring conditions are Boolean,
-    /// and checks type consistency across branches. Returns the type of the if-expression.
+    /// Visits an if-else node, checking conditions and branches in source order,
+    /// ensuring conditions are Boolean and that the branch types share a common ancestor.
+    /// An `if` without a final `else` may produce no value, so it is typed as `Object`.
+    /// Returns the type of the if-expression.
     fn visit_if_else(&mut self, node: &mut IfElseNode) -> TypeNode {
         let if_condition_type = node.condition.accept(self);
-        if if_condition_type != self.get_built_in_types(&BuiltInTypes::Boolean) {
+        if !self.is_type(&if_condition_type, &BuiltInTypes::Boolean) {
             self.new_error(SemanticError::InvalidConditionType(
                 if_condition_type,
                 node.condition.span(),
             ));
         }
         let if_expr_type = node.if_expression.accept(self);
-        let mut result = if_expr_type.clone();
-        for (condition , body_expr) in node.elifs.iter_mut() {
-
-            let expr_type = body_expr.accept(self);
+        let mut branches = vec![(if_expr_type.clone(), node.if_expression.span())];
+        let mut has_else = false;
+        for (condition, body_expr) in node.elifs.iter_mut() {
             if let Some(cond) = condition {
                 let cond_type = cond.accept(self);
-                if cond_type != self.get_built_in_types(&BuiltInTypes::Boolean) {
+                if !self.is_type(&cond_type, &BuiltInTypes::Boolean) {
                     self.new_error(SemanticError::InvalidConditionType(
                         cond_type,
-                        node.span.clone(),
+                        cond.span(),
                     ));
                 }
+            } else {
+                has_else = true;
             }
-            if result != expr_type {
-                let lca = self.types_tree.find_lca(&result, &expr_type);
-                if lca.type_name == "Unknown" || lca.type_name == "Object" {
-                    // TODO añadir error más específico para este error
-                    self.new_error(SemanticError::UnknownError(
-                        "Incompatible types in if-else branches".to_string(),
-                        node.span.clone(),
-                    ));
+            let expr_type = body_expr.accept(self);
+            branches.push((expr_type, body_expr.span()));
+        }
+
+        if !has_else {
+            let object_type = self.get_built_in_types(&BuiltInTypes::Object);
+            node.set_type(object_type.clone());
+            return object_type;
+        }
+
+        let mut result = if_expr_type;
+        let mut compatible = true;
+        for (expr_type, _) in branches.iter().skip(1) {
+            if result != *expr_type {
+                let lca = self.types_tree.find_lca(&result, expr_type);
+                if lca.type_name == "Object" {
+                    compatible = false;
                 }
-                result = lca ;
+                result = lca;
             }
         }
+        if !compatible {
+            self.new_error(SemanticError::IncompatibleBranchTypes(
+                branches
+                    .into_iter()
+                    .map(|(branch_type, span)| (branch_type.type_name, span))
+                    .collect(),
+                node.span,
+            ));
+        }
         node.set_type(result.clone());
         result
     }
 
     /// Visits a let-in node, entering a new scope, visiting assignments,
-    /// registering variable types, and returning the type of the body expression.
+    /// registering each variable with its declared (or inferred) type,
+    /// and returning the type of the body expression.
     fn visit_let_in(&mut self, node: &mut LetInNode) -> TypeNode {
         self.enter_scope();
         for assig in node.assignments.iter_mut() {
             let expr_type = assig.expression.accept(self);
-            assig.set_type(expr_type.clone());
+            let binding_type = match assig.signature.clone() {
+                Some(signature) => {
+                    self.check_declared_type(&assig.identifier, &signature, &expr_type, assig.span)
+                }
+                None => expr_type,
+            };
+            assig.set_type(binding_type.clone());
             self.context
                 .symbols
-                .insert(assig.identifier.clone(), expr_type.type_name);
+                .declare(assig.identifier.clone(), binding_type.type_name, assig.span);
         }
         let return_type = node.body.accept(self);
         self.exit_scope();
@@ -851,10 +977,10 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
     /// visits members (properties and methods), and returns the type node of the defined type.
     fn visit_type_def(&mut self, node: &mut TypeDefNode) -> TypeNode {
         self.enter_scope();
-        self.context.current_type = Some(node.identifier.clone());
+        let previous_type = self.context.current_type.replace(node.identifier.clone());
 
         for param in &node.params {
-            if self.context.symbols.contains_key(&param.name) {
+            if self.context.symbols.contains_local(&param.name) {
                 self.new_error(SemanticError::ParamNameAlreadyExist(
                     param.name.clone(),
                     node.identifier.clone(),
@@ -864,19 +990,22 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
             }
 
             if let Some(type_node) = self.types_tree.get_type(&param.signature) {
-                self.context
-                    .symbols
-                    .insert(param.name.clone(), type_node.type_name.clone());
+                self.context.symbols.declare(
+                    param.name.clone(),
+                    type_node.type_name.clone(),
+                    param.span,
+                );
             } else {
                 self.new_error(SemanticError::UndefinedType(
                     param.signature.clone(),
                     param.span,
                 ));
-                self.context.symbols.insert(
+                self.context.symbols.declare(
                     param.name.clone(),
                     self.get_built_in_types(&BuiltInTypes::Unknown)
                         .type_name
                         .clone(),
+                    param.span,
                 );
             }
         }
@@ -893,7 +1022,7 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                 } else {
                     for (index, arg) in node.parent_args.iter_mut().enumerate() {
                         let arg_type = arg.accept(self);
-                        if arg_type.type_name != parent_node.params[index].signature {
+                        if !self.conforms_to(&parent_node.params[index].signature, &arg_type) {
                             self.new_error(SemanticError::InvalidTypeArgument(
                                 "types".to_string(),
                                 arg_type.type_name,
@@ -913,7 +1042,11 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
         for member in node.members.iter_mut() {
             match member {
                 TypeMember::Property(prop) => {
-                    let prop_type = prop.expression.accept(self);
+                    let mut prop_type = prop.expression.accept(self);
+                    if let Some(signature) = prop.signature.clone() {
+                        prop_type =
+                            self.check_declared_type(&prop.identifier, &signature, &prop_type, prop.span);
+                    }
                     prop.set_type(prop_type.clone());
                     if let Some(type_node) = self.types_tree.nodes.get_mut(&node.identifier) {
                         type_node
@@ -934,6 +1067,7 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
         }
 
         self.exit_scope();
+        self.context.current_type = previous_type;
         let return_type = self.types_tree.get_type(&node.identifier).unwrap();
         node.set_type(return_type.clone());
         return_type
@@ -950,7 +1084,7 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                     node.type_name.clone(),
                     node.span.clone(),
                 ));
-                self.get_built_in_types(&BuiltInTypes::Unknown)
+                self.unknown_type(node.span)
             } else {
                 for (index, arg) in node.arguments.iter_mut().enumerate() {
                     let arg_type = arg.accept(self);
@@ -977,7 +1111,7 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                 node.type_name.clone(),
                 node.span.clone(),
             ));
-            self.get_built_in_types(&BuiltInTypes::Unknown)
+            self.unknown_type(node.span)
         }
     }
 
@@ -986,6 +1120,13 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
     /// Reports errors if method is not found or arguments mismatch.
     fn visit_type_function_access(&mut self, node: &mut TypeFunctionAccessNode) -> TypeNode {
         let object = node.object.accept(self);
+        if object.type_name == BuiltInTypes::Unknown.as_str() {
+            // The object's type is already an error, so its methods cannot be checked
+            for arg in node.member.arguments.iter_mut() {
+                arg.accept(self);
+            }
+            return self.unknown_type(node.span);
+        }
         let member_function = self
             .types_tree
             .find_method(object.type_name.clone(), node.member.function_name.clone());
@@ -997,7 +1138,7 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                     node.member.function_name.clone(),
                     node.member.span.clone(),
                 ));
-                self.get_built_in_types(&BuiltInTypes::Unknown)
+                self.unknown_type(node.member.span)
             } else {
                 for (index, arg) in node.member.arguments.iter_mut().enumerate() {
                     let arg_type = arg.accept(self);
@@ -1025,7 +1166,7 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                         func.return_type.clone(),
                         node.member.span.clone(),
                     ));
-                    self.get_built_in_types(&BuiltInTypes::Unknown)
+                    self.unknown_type(node.member.span)
                 }
             }
         } else {
@@ -1034,7 +1175,7 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                 node.member.function_name.clone(),
                 node.member.span.clone(),
             ));
-            self.get_built_in_types(&BuiltInTypes::Unknown)
+            self.unknown_type(node.member.span)
         }
     }
 
@@ -1055,14 +1196,14 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                         node.member.as_ref().clone(),
                         node.span.clone(),
                     ));
-                    self.get_built_in_types(&BuiltInTypes::Unknown)
+                    self.unknown_type(node.span)
                 }
             } else {
                 self.new_error(SemanticError::UndefinedType(
                     current_type.clone(),
                     node.span.clone(),
                 ));
-                self.get_built_in_types(&BuiltInTypes::Unknown)
+                self.unknown_type(node.span)
             }
         } else {
             self.new_error(SemanticError::InvalidTypePropertyAccess(
@@ -1070,7 +1211,7 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
                 node.member.as_ref().clone(),
                 node.span.clone(),
             ));
-            self.get_built_in_types(&BuiltInTypes::Unknown)
+            self.unknown_type(node.span)
         }
     }
 
@@ -1078,9 +1219,9 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
     /// (Number, String, or Boolean), and returns the expression's type.
     fn visit_print(&mut self, node: &mut PrintNode) -> TypeNode {
         let expr_type = node.expression.accept(self);
-        if expr_type.type_name != "Number"
-            && expr_type.type_name != "String"
-            && expr_type.type_name != "Boolean"
+        if !self.is_type(&expr_type, &BuiltInTypes::Number)
+            && !self.is_type(&expr_type, &BuiltInTypes::String)
+            && !self.is_type(&expr_type, &BuiltInTypes::Boolean)
         {
             self.new_error(SemanticError::InvalidPrint(
                 expr_type.type_name.clone(),
@@ -1090,4 +1231,15 @@ impl Visitor<TypeNode> for SemanticAnalyzer {
         node.set_type(expr_type.clone());
         expr_type
     }
+
+    /// Visits a console input node, whose type depends only on what it reads.
+    fn visit_read(&mut self, node: &mut ReadNode) -> TypeNode {
+        let read_type = match node.kind {
+            ReadKind::Line => self.get_built_in_types(&BuiltInTypes::String),
+            ReadKind::Number => self.get_built_in_types(&BuiltInTypes::Number),
+            ReadKind::Eof => self.get_built_in_types(&BuiltInTypes::Boolean),
+        };
+        node.set_type(read_type.clone());
+        read_type
+    }
 }
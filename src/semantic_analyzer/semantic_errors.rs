@@ -74,6 +74,16 @@ pub enum SemanticError {
 
     /// Invalid iterable passed to a `for` loop (should be `range()`).
     InvalidIterable(String, usize, Span),
+
+    /// The initial value of a variable or attribute does not conform to its declared type.
+    InvalidDeclarationType(String, String, String, Span),
+
+    /// A destructive assignment stores a value that does not conform to the target's type.
+    InvalidDestructiveAssignment(String, String, String, Span),
+
+    /// The branches of an `if` expression have types without a common ancestor.
+    /// Holds the type and span of every branch, in source order.
+    IncompatibleBranchTypes(Vec<(String, Span)>, Span),
 }
 
 impl SemanticError {
@@ -147,10 +157,31 @@ impl SemanticError {
             SemanticError::InvalidIterable(fn_name, cnt, _) => {
                 format!("For loops require range() function, found '{fn_name}({cnt} arguments)'")
             }
+            SemanticError::InvalidDeclarationType(name, declared, found, _) => {
+                format!("'{name}' is declared as {declared}, but its initial value is {found}")
+            }
+            SemanticError::InvalidDestructiveAssignment(name, expected, found, _) => {
+                format!("Cannot assign a value of type {found} to '{name}' of type {expected}")
+            }
+            SemanticError::IncompatibleBranchTypes(branches, _) => {
+                let types: Vec<&str> = branches.iter().map(|(ty, _)| ty.as_str()).collect();
+                format!("Incompatible types in if-else branches: {}", types.join(", "))
+            }
             SemanticError::UnknownError(msg, _) => msg.clone(),
         }
     }
 
+    /// Returns additional locations related to the error, each with a short label.
+    fn secondary_spans(&self) -> Vec<(Span, String)> {
+        match self {
+            SemanticError::IncompatibleBranchTypes(branches, _) => branches
+                .iter()
+                .map(|(ty, span)| (*span, format!("this branch has type {ty}")))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
     /// Returns the source `Span` where the error occurred.
     fn span(&self) -> &Span {
         match self {
@@ -175,7 +206,10 @@ impl SemanticError {
             | SemanticError::InvalidTypePropertyAccess(_, _, sp)
             | SemanticError::InvalidTypeProperty(_, _, sp)
             | SemanticError::InvalidPrint(_, sp)
-            | SemanticError::InvalidIterable(_, _, sp) => sp,
+            | SemanticError::InvalidIterable(_, _, sp)
+            | SemanticError::InvalidDeclarationType(_, _, _, sp)
+            | SemanticError::InvalidDestructiveAssignment(_, _, _, sp)
+            | SemanticError::IncompatibleBranchTypes(_, sp) => sp,
         }
     }
 
@@ -192,15 +226,25 @@ impl SemanticError {
         let message = self.message();
         let location = format!("(line {line}, column {col})");
 
-        format!(
-            "\x1b[31mError {location}: {message}\n  {}\n  {}\x1b[0m",
+        let mut report = format!(
+            "\x1b[31mError {location}: {message}\n  {}\n  {}",
             line_str, caret
-        )
+        );
+        for (span, label) in self.secondary_spans() {
+            let (line, col, line_str, _) = get_line_context(input, span.start, missplacement);
+            let caret = build_caret_point(col);
+            report.push_str(&format!(
+                "\n  note (line {line}, column {col}): {label}\n    {}\n    {}",
+                line_str, caret
+            ));
+        }
+        report.push_str("\x1b[0m");
+        report
     }
 }
 
 /// Retrieves line and column number from a byte offset, for error context.
-fn get_line_context(
+pub(crate) fn get_line_context(
     input: &str,
     offset: usize,
     missplacement: i32,
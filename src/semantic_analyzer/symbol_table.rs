@@ -0,0 +1,148 @@
+//! Defines the `SymbolTable` used by the semantic analyzer to resolve variables.
+//!
+//! Scopes live in an arena and point to their enclosing scope, so entering a scope
+//! is O(1) and never copies the symbols of the outer scopes. Every symbol keeps the
+//! span where it was declared, which makes go-to-definition lookups possible.
+
+use crate::tokens::Span;
+use std::collections::HashMap;
+
+/// Information recorded for every declared variable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    /// The name of the type bound to the variable.
+    pub type_name: String,
+
+    /// The source span of the declaration that introduced the variable.
+    pub declaration: Span,
+}
+
+impl Symbol {
+    /// Creates a new `Symbol` with the given type name and declaration span.
+    pub fn new(type_name: String, declaration: Span) -> Self {
+        Symbol {
+            type_name,
+            declaration,
+        }
+    }
+}
+
+/// A single lexical scope, linked to the scope that encloses it.
+#[derive(Debug, Clone, PartialEq)]
+struct Scope {
+    /// Index of the enclosing scope in the arena, `None` for the global scope.
+    parent: Option<usize>,
+
+    /// Variables declared directly in this scope.
+    symbols: HashMap<String, Symbol>,
+}
+
+/// Symbol table made of parent-linked lexical scopes.
+///
+/// Scopes are never removed from the arena when they are exited, so the
+/// declarations of every scope stay available after the analysis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolTable {
+    /// Arena holding every scope created so far. Index 0 is the global scope.
+    scopes: Vec<Scope>,
+
+    /// Index of the innermost scope currently being analyzed.
+    current: usize,
+
+    /// Resolved uses of variables, paired with the span of their declaration.
+    references: Vec<(Span, Span)>,
+}
+
+impl Default for SymbolTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SymbolTable {
+    /// Creates a symbol table containing only the global scope.
+    pub fn new() -> Self {
+        SymbolTable {
+            scopes: vec![Scope {
+                parent: None,
+                symbols: HashMap::new(),
+            }],
+            current: 0,
+            references: Vec::new(),
+        }
+    }
+
+    /// Opens a new scope nested in the current one.
+    pub fn enter_scope(&mut self) {
+        self.scopes.push(Scope {
+            parent: Some(self.current),
+            symbols: HashMap::new(),
+        });
+        self.current = self.scopes.len() - 1;
+    }
+
+    /// Closes the current scope and makes its parent the current scope again.
+    ///
+    /// Exiting the global scope has no effect.
+    pub fn exit_scope(&mut self) {
+        if let Some(parent) = self.scopes[self.current].parent {
+            self.current = parent;
+        }
+    }
+
+    /// Declares a variable in the current scope, shadowing any outer declaration.
+    ///
+    /// The type bound to a variable never changes after its declaration.
+    ///
+    /// # Arguments
+    /// * `name` - The variable's name.
+    /// * `type_name` - The type bound to the variable.
+    /// * `declaration` - The span of the declaration.
+    pub fn declare(&mut self, name: String, type_name: String, declaration: Span) {
+        self.scopes[self.current]
+            .symbols
+            .insert(name, Symbol::new(type_name, declaration));
+    }
+
+    /// Finds the innermost visible declaration of `name`.
+    pub fn lookup(&self, name: &str) -> Option<&Symbol> {
+        let mut scope = Some(self.current);
+        while let Some(index) = scope {
+            if let Some(symbol) = self.scopes[index].symbols.get(name) {
+                return Some(symbol);
+            }
+            scope = self.scopes[index].parent;
+        }
+        None
+    }
+
+    /// Returns the type name of the innermost visible declaration of `name`.
+    pub fn get(&self, name: &str) -> Option<&String> {
+        self.lookup(name).map(|symbol| &symbol.type_name)
+    }
+
+    /// Checks whether `name` is visible from the current scope.
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.lookup(name).is_some()
+    }
+
+    /// Checks whether `name` is declared directly in the current scope.
+    pub fn contains_local(&self, name: &str) -> bool {
+        self.scopes[self.current].symbols.contains_key(name)
+    }
+
+    /// Resolves a use of `name` located at `usage`, remembering which declaration it refers to.
+    pub fn resolve(&mut self, name: &str, usage: Span) -> Option<&Symbol> {
+        let declaration = self.lookup(name)?.declaration;
+        self.references.push((usage, declaration));
+        self.lookup(name)
+    }
+
+    /// Returns the declaration span of the variable used at the given byte offset.
+    pub fn definition_at(&self, offset: usize) -> Option<Span> {
+        self.references
+            .iter()
+            .find(|(usage, _)| usage.start <= offset && offset < usage.end)
+            .map(|(_, declaration)| *declaration)
+    }
+}
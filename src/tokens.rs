@@ -5,6 +5,7 @@
 //! - `KeywordToken`: Reserved words in the language.
 //! - `OperatorToken`: Language operators (e.g., +, ==, :=).
 //! - `DelimiterToken`: Punctuation used to delimit expressions and blocks.
+//! - `LexicalError`: A malformed token, such as an invalid escape in a string.
 
 use std::fmt;
 
@@ -38,6 +39,9 @@ impl Span {
 #[derive(Debug, PartialEq, Clone)]
 pub enum KeywordToken {
     PRINT,
+    READ,
+    READNUMBER,
+    EOF,
     WHILE,
     FOR,
     ELIF,
@@ -73,6 +77,7 @@ pub enum OperatorToken {
     LTE,
     ASSIGN,
     CONCAT,
+    DCONCAT,
     DASSIGN,
     OR,
     AND,
@@ -105,6 +110,7 @@ impl fmt::Display for OperatorToken {
             OperatorToken::LTE => "<=",
             OperatorToken::ASSIGN => "=",
             OperatorToken::CONCAT => "@",
+            OperatorToken::DCONCAT => "@@",
             OperatorToken::DASSIGN => ":=",
             OperatorToken::AND => "&",
             OperatorToken::OR => "|",
@@ -128,3 +134,70 @@ pub enum DelimiterToken {
     RBRACE,
     ARROW,
 }
+
+/// A token that matches the grammar but is malformed.
+///
+/// # Fields
+/// * `message` - Description of the problem.
+/// * `offset` - Byte offset in the input where the problem starts.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LexicalError {
+    pub message: String,
+    pub offset: usize,
+}
+
+impl fmt::Display for LexicalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Decodes the escape sequences of a string literal.
+///
+/// Supported escapes are `\n`, `\t`, `\"`, `\\` and `\u{XXXX}` (a Unicode
+/// scalar value of 1 to 6 hexadecimal digits).
+///
+/// # Arguments
+/// * `raw` - The text between the quotes of the literal.
+/// * `offset` - Byte offset of `raw` in the input, used to locate errors.
+///
+/// # Returns
+/// The decoded string, or a `LexicalError` pointing at the first invalid escape.
+pub fn unescape_string(raw: &str, offset: usize) -> Result<String, LexicalError> {
+    let mut value = String::with_capacity(raw.len());
+    let mut chars = raw.char_indices();
+    while let Some((index, c)) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        let error = |message: String| LexicalError { message, offset: offset + index };
+        match chars.next().map(|(_, escaped)| escaped) {
+            Some('n') => value.push('\n'),
+            Some('t') => value.push('\t'),
+            Some('"') => value.push('"'),
+            Some('\\') => value.push('\\'),
+            Some('u') => {
+                let rest = &raw[index + 2..];
+                let digits = rest
+                    .strip_prefix('{')
+                    .and_then(|rest| rest.split_once('}'))
+                    .map(|(digits, _)| digits)
+                    .filter(|digits| (1..=6).contains(&digits.len()) && digits.chars().all(|c| c.is_ascii_hexdigit()))
+                    .ok_or_else(|| error("Expected `{` followed by 1 to 6 hex digits and `}` after `\\u`".to_string()))?;
+                let decoded = u32::from_str_radix(digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| error(format!("Invalid Unicode escape `\\u{{{}}}`", digits)))?;
+                value.push(decoded);
+                // Skip `{`, the digits and `}`
+                for _ in 0..digits.len() + 2 {
+                    chars.next();
+                }
+            }
+            Some(other) => return Err(error(format!("Unknown escape sequence `\\{}`", other))),
+            None => return Err(error("Unterminated escape sequence".to_string())),
+        }
+    }
+    Ok(value)
+}
@@ -2,7 +2,9 @@
 //! along with utilities for inheritance, type checking, and method resolution.
 
 use std::collections::HashMap;
+use crate::ast_nodes::expression::Expression;
 use crate::ast_nodes::function_def::{FunctionDefNode, FunctionParams};
+use crate::tokens::Span;
 
 use super::tree_node::TypeNode;
 
@@ -28,6 +30,40 @@ impl BuiltInTypes {
     }
 }
 
+/// Signature of a method implemented by the runtime:
+/// `(name, [(parameter, type)], return type)`.
+pub type BuiltinMethod = (&'static str, &'static [(&'static str, &'static str)], &'static str);
+
+/// Methods of the built-in `String` type.
+///
+/// They are implemented by the runtime, so their definitions only carry the signature.
+pub const STRING_METHODS: &[BuiltinMethod] = &[
+    ("size", &[], "Number"),
+    ("substr", &[("start", "Number"), ("length", "Number")], "String"),
+    ("at", &[("index", "Number")], "String"),
+    ("indexOf", &[("value", "String")], "Number"),
+    ("toUpper", &[], "String"),
+    ("toLower", &[], "String"),
+    ("parse", &[], "Number"),
+];
+
+/// Builds the definitions of `STRING_METHODS`, keyed by name.
+fn string_methods() -> HashMap<String, Box<FunctionDefNode>> {
+    let span = Span::new(0, 0);
+    STRING_METHODS
+        .iter()
+        .map(|(name, params, return_type)| {
+            let params = params
+                .iter()
+                .map(|(param, signature)| FunctionParams::new(param.to_string(), signature.to_string(), span))
+                .collect();
+            let body = Expression::new_identifier("self".to_string(), span);
+            let method = FunctionDefNode::new_expr(name.to_string(), params, return_type.to_string(), body, span);
+            (name.to_string(), Box::new(method))
+        })
+        .collect()
+}
+
 /// Represents the tree structure of all types (user-defined and built-in).
 /// 
 /// Each node corresponds to a type and maintains its position in the inheritance hierarchy.
@@ -46,7 +82,7 @@ impl TypeTree {
             nodes: HashMap::new(),
         };
         newtree.nodes.insert("Object".to_string(), newtree.root.clone());
-        newtree.add_type("String".to_string(), vec![], None, HashMap::new(), HashMap::new());
+        newtree.add_type("String".to_string(), vec![], None, HashMap::new(), string_methods());
         newtree.add_type("Number".to_string(), vec![], None, HashMap::new(), HashMap::new());
         newtree.add_type("Boolean".to_string(), vec![], None, HashMap::new(), HashMap::new());
         newtree.add_type("Unknown".to_string(), vec![], None, HashMap::new(), HashMap::new());
@@ -91,10 +127,16 @@ impl TypeTree {
     }
 
     /// Finds the Lowest Common Ancestor (LCA) of two types in the hierarchy.
+    ///
+    /// `Unknown` is absorbed by the other type, since it only stands for an expression
+    /// whose error was already reported.
     pub fn find_lca(&self, type1: &TypeNode, type2: &TypeNode) -> TypeNode {
-        if type1.type_name == type2.type_name {
+        if type1.type_name == type2.type_name || type2.type_name == BuiltInTypes::Unknown.as_str() {
             return type1.clone();
         }
+        if type1.type_name == BuiltInTypes::Unknown.as_str() {
+            return type2.clone();
+        }
         if type1.depth < type2.depth {
             if let Some(ref parent2) = type2.parent {
                 if let Some(ref parent2_node) = self.nodes.get(parent2) {
@@ -129,7 +171,15 @@ impl TypeTree {
     }
 
     /// Checks if `ancestor` is a true ancestor (direct or indirect) of `descendant`.
+    ///
+    /// `Unknown` is the error-recovery type: it conforms to every type and every type
+    /// conforms to it, so errors involving it are reported only once, at their origin.
     pub fn is_ancestor(&self, ancestor: &TypeNode, descendant: &TypeNode) -> bool {
+        if ancestor.type_name == BuiltInTypes::Unknown.as_str()
+            || descendant.type_name == BuiltInTypes::Unknown.as_str()
+        {
+            return true;
+        }
         let mut current = Some(descendant);
         while let Some(node) = current {
             if node.type_name == ancestor.type_name {
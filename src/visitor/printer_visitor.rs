@@ -5,6 +5,7 @@
 use crate::ast_nodes::binary_op::BinaryOpNode;
 use crate::ast_nodes::function_call::FunctionCallNode;
 use crate::ast_nodes::print::PrintNode;
+use crate::ast_nodes::read::{ReadKind, ReadNode};
 use crate::ast_nodes::type_def::{TypeDefNode, TypeMember};
 use crate::ast_nodes::unary_op::UnaryOpNode;
 use crate::ast_nodes::if_else::IfElseNode;
@@ -129,7 +130,10 @@ impl Visitor<String> for PrinterVisitor {
 
     fn visit_let_in(&mut self, node: &mut LetInNode) -> String {
         let assignments: Vec<String> = node.assignments.iter_mut()
-            .map(|assignment| format!("{} = {}", assignment.identifier, assignment.expression.accept(self)))
+            .map(|assignment| match &assignment.signature {
+                Some(signature) => format!("{}: {} = {}", assignment.identifier, signature, assignment.expression.accept(self)),
+                None => format!("{} = {}", assignment.identifier, assignment.expression.accept(self)),
+            })
             .collect();
         let body = node.body.accept(self);
         format!("let {} in {}", assignments.join(", "), body)
@@ -150,7 +154,10 @@ impl Visitor<String> for PrinterVisitor {
         let members: Vec<String> = node.members.iter_mut()
             .map(|member| match member {
                 TypeMember::Property(assign) => {
-                    let name = &assign.identifier;
+                    let name = match &assign.signature {
+                        Some(signature) => format!("{}: {}", assign.identifier, signature),
+                        None => assign.identifier.clone(),
+                    };
                     let value = assign.expression.accept(self);
                     format!("{} = {}\n", name, value)
                 }
@@ -214,4 +221,12 @@ impl Visitor<String> for PrinterVisitor {
         let expr = node.expression.accept(self);
         format!("print( {} )", expr)
     }
+
+    fn visit_read(&mut self, node: &mut ReadNode) -> String {
+        match node.kind {
+            ReadKind::Line => "read()".to_string(),
+            ReadKind::Number => "readNumber()".to_string(),
+            ReadKind::Eof => "eof()".to_string(),
+        }
+    }
 }
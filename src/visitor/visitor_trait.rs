@@ -9,6 +9,7 @@ use crate::ast_nodes::destructive_assign::DestructiveAssignNode;
 use crate::ast_nodes::for_loop::ForNode;
 use crate::ast_nodes::function_call::FunctionCallNode;
 use crate::ast_nodes::print::PrintNode;
+use crate::ast_nodes::read::ReadNode;
 use crate::ast_nodes::type_def::TypeDefNode;
 use crate::ast_nodes::unary_op::UnaryOpNode;
 use crate::ast_nodes::if_else::IfElseNode;
@@ -84,4 +85,7 @@ pub trait Visitor<T> {
 
     /// Visit a `print(...)` expression node.
     fn visit_print(&mut self, node: &mut PrintNode) -> T;
+
+    /// Visit a console input node (`read()`, `readNumber()` or `eof()`).
+    fn visit_read(&mut self, node: &mut ReadNode) -> T;
 }